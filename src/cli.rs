@@ -17,17 +17,22 @@ pub fn cli() -> Command {
 
 | Image Format  | Input | Output | Note            |
 | ------------- | ----- | ------ | --------------- |
-| avif          | O     | O      | Static only     |
+| avif          | O     | O      |                 |
 | bmp           | O     | X      |                 |
+| dds           | X     | O      | BCn textures    |
 | farbfeld      | O     | O      |                 |
+| gif           | O     | O      | 256 colors      |
 | hdr           | O     | O      |                 |
+| ico           | X     | O      | Multiple sizes  |
 | jpeg          | O     | O      |                 |
 | jpeg_xl(jxl)  | O     | O      |                 |
+| ktx2          | X     | O      | BCn textures    |
 | mozjpeg(moz)  | O     | O      |                 |
 | oxipng(oxi)   | O     | O      | Static only     |
 | png           | O     | O      | Static only     |
 | ppm           | O     | O      |                 |
 | psd           | O     | X      |                 |
+| raw(dng)      | O     | X      | Camera RAW      |
 | qoi           | O     | O      |                 |
 | webp          | O     | O      | Static only     |
 
@@ -24,6 +24,27 @@ pub fn avif() -> Command {
                 .default_value("ycbcr"),
             arg!(--alpha_mode <MODE> "Configure handling of color channels in transparent images.")
                 .value_parser(["UnassociatedDirty", "UnassociatedClean", "Premultiplied"])
-                .default_value("UnassociatedClean")
+                .default_value("UnassociatedClean"),
+            arg!(--depth <BITS> "Bit depth of AVIF being written.")
+                .long_help(indoc! {r#"Bit depth of AVIF being written.
+
+                By default 8-bit inputs are written in 8-bit and high bit depth inputs in 10-bit.
+                12-bit is not written by ravif backend."#})
+                .value_parser(["8", "10", "12"]),
+            arg!(--backend <LIB> "Library used to encode still images.")
+                .long_help(indoc! {r#"Library used to encode still images.
+
+                auto = ravif, or libavif for images with metadata or 12-bit depth
+                ravif = rav1e, metadata of the image is not written
+                libavif = aom, keeps ICC profile, EXIF and XMP
+
+                Animated images are always encoded with libavif."#})
+                .value_parser(["auto", "ravif", "libavif"])
+                .default_value("auto"),
+            arg!(--loop_count <NUM> "Number of times animation is played, 0 = infinite.")
+                .long_help(indoc! {r#"Number of times animation is played, 0 = infinite.
+
+                By default loop count of the animated source is kept."#})
+                .value_parser(value_parser!(u32))
         ]).common_args()
 }
@@ -0,0 +1,29 @@
+use clap::{arg, value_parser, Command};
+use indoc::indoc;
+
+use crate::cli::common::CommonArgs;
+
+pub fn gif() -> Command {
+    Command::new("gif")
+        .about("Encode images into GIF format. (Animated and Paletted)")
+        .args([
+            arg!(-q --quality <NUM> "Quality of the palette chosen for each frame.")
+                .value_parser(value_parser!(u8).range(1..=100))
+                .default_value("100"),
+            arg!(--dither <LEVEL> "Dithering level of the quantized frames, 0 = disabled.")
+                .value_parser(value_parser!(u8).range(0..=100))
+                .default_value("100"),
+            arg!(--global_palette "Use one palette for all frames.").long_help(indoc! {r#"Use one palette for all frames.
+
+                Palette is stored once as the global color table instead of a local palette in every frame.
+                Usually makes animations with similar frames smaller."#}),
+            arg!(--loop_count <NUM> "Number of times animation is played, 0 = infinite.")
+                .long_help(
+                    indoc! {r#"Number of times animation is played, 0 = infinite.
+
+                By default loop count of the animated source is kept."#},
+                )
+                .value_parser(value_parser!(u32)),
+        ])
+        .common_args()
+}
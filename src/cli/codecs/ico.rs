@@ -0,0 +1,21 @@
+use clap::{arg, value_parser, Command};
+use indoc::indoc;
+
+use crate::cli::common::CommonArgs;
+
+pub fn ico() -> Command {
+    Command::new("ico")
+        .about("Encode images into ICO format. (Favicons with multiple sizes)")
+        .args([
+            arg!(--sizes <SIZES> "Comma separated sizes of icons packed into the file.")
+                .long_help(
+                    indoc! {r#"Comma separated sizes of icons packed into the file.
+
+                Every size is rendered from the source image, sizes above 64 are stored as PNG."#},
+                )
+                .value_parser(value_parser!(u32).range(1..=256))
+                .value_delimiter(',')
+                .default_value("16,32,48,64,256"),
+        ])
+        .common_args()
+}
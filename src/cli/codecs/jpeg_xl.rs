@@ -1,14 +1,32 @@
-use clap::Command;
+use clap::{arg, value_parser, Command};
 use indoc::indoc;
 
-use crate::cli::common::CommonArgs;
+use crate::cli::{common::CommonArgs, preprocessors::OPERATIONS};
 
 pub fn jpeg_xl() -> Command {
     Command::new("jpeg_xl")
         .alias("jxl")
-        .about("Encode images into JpegXL format. (Big but Lossless)")
-        .long_about(indoc! {r#"Encode images into jpeg xl format.
+        .about("Encode images into JpegXL format. (Small and Lossless-able)")
+        .args([
+            arg!(-q --quality <NUM> "Quality, mapped to distance like cjxl does, 100 = lossless.")
+                .long_help(indoc! {r#"Quality, mapped to distance like cjxl does, 100 = lossless.
 
-        Only supports lossless encoding"#})
+                Without quality or distance image is encoded losslessly, 90 = visually lossless."#})
+                .value_parser(value_parser!(u8).range(1..=100)),
+            arg!(--distance <NUM> "Butteraugli distance, 1.0 = visually lossless, overrides quality.")
+                .long_help(indoc! {r#"Butteraugli distance, 1.0 = visually lossless, overrides quality.
+
+                Lower is better, 0.0 is lossless and values above 3.0 are noticeably lossy."#})
+                .value_parser(value_parser!(f32))
+                .conflicts_with("quality"),
+            arg!(--effort <NUM> "Encoder effort, higher is slower but smaller.")
+                .value_parser(value_parser!(u8).range(1..=10))
+                .default_value("7"),
+            arg!(--lossless "Encode image without quality loss.")
+                .conflicts_with_all(["quality", "distance"]),
+            arg!(--lossless_jpeg "Recompress JPEG input without re-encoding, it can be restored bit-exact.")
+                .conflicts_with_all(["quality", "distance", "lossless"])
+                .conflicts_with_all(OPERATIONS),
+        ])
         .common_args()
 }
@@ -1,12 +1,26 @@
 use clap::Command;
 
 use self::{
-    avif::avif, farbfeld::farbfeld, jpeg::jpeg, jpeg_xl::jpeg_xl, mozjpeg::mozjpeg, oxipng::oxipng,
-    png::png, ppm::ppm, qoi::qoi, webp::webp,
+    avif::avif,
+    farbfeld::farbfeld,
+    gif::gif,
+    ico::ico,
+    jpeg::jpeg,
+    jpeg_xl::jpeg_xl,
+    mozjpeg::mozjpeg,
+    oxipng::oxipng,
+    png::png,
+    ppm::ppm,
+    qoi::qoi,
+    texture::{dds, ktx2},
+    tiff::tiff,
+    webp::webp,
 };
 
 mod avif;
 mod farbfeld;
+mod gif;
+mod ico;
 mod jpeg;
 mod jpeg_xl;
 mod mozjpeg;
@@ -14,20 +28,27 @@ mod oxipng;
 mod png;
 mod ppm;
 mod qoi;
+mod texture;
+mod tiff;
 mod webp;
 
 impl Codecs for Command {
     fn codecs(self) -> Self {
         self.subcommands([
             avif(),
+            dds(),
             farbfeld(),
+            gif(),
+            ico(),
             jpeg(),
             jpeg_xl(),
+            ktx2(),
             mozjpeg(),
             oxipng(),
             png(),
             ppm(),
             qoi(),
+            tiff(),
             webp(),
         ])
     }
@@ -1,6 +1,7 @@
-use clap::{arg, value_parser, Command};
+use clap::{arg, value_parser, ArgGroup, Command};
+use rimage::codecs::mozjpeg::{JpegCrop, RestartInterval};
 
-use crate::cli::common::CommonArgs;
+use crate::cli::{common::CommonArgs, preprocessors::OPERATIONS};
 
 pub fn mozjpeg() -> Command {
     Command::new("mozjpeg")
@@ -13,6 +14,27 @@ pub fn mozjpeg() -> Command {
             arg!(--chroma_quality <NUM> "Separate chrome quality.")
                 .value_parser(value_parser!(u8).range(1..=100)),
             arg!(--baseline "Set to use baseline encoding (by default is progressive)."),
+            arg!(--lossless "Optimize JPEG input without re-encoding, other JPEG options are ignored.")
+                .conflicts_with_all(OPERATIONS),
+            arg!(--strip "Remove metadata markers in lossless mode.")
+                .requires("lossless"),
+            arg!(--rotate <DEG> "Losslessly rotate image clockwise, by default EXIF orientation is applied.")
+                .value_parser(["90", "180", "270"])
+                .requires("lossless"),
+            arg!(--flip <DIR> "Losslessly mirror image.")
+                .value_parser(["horizontal", "vertical"])
+                .requires("lossless"),
+            arg!(--transpose "Losslessly flip image across the upper-left to lower-right diagonal.")
+                .requires("lossless"),
+            arg!(--transverse "Losslessly flip image across the upper-right to lower-left diagonal.")
+                .requires("lossless"),
+            arg!(--crop <GEOMETRY> "Losslessly crop image to WIDTHxHEIGHT+X+Y, offsets are aligned to MCU.")
+                .value_parser(|s: &str| s.parse::<JpegCrop>())
+                .requires("lossless"),
+            arg!(--no_trim "Keep edge blocks which can't be transformed losslessly, they are left untransformed.")
+                .requires("lossless"),
+            arg!(--perfect "Fail if edge blocks can't be transformed losslessly.")
+                .requires("lossless"),
             arg!(--no_optimize_coding "Set to make files larger for no reason."),
             arg!(--smoothing <NUM> "Use MozJPEG's smoothing.")
                 .value_parser(value_parser!(u8).range(1..=100)),
@@ -20,8 +42,19 @@ pub fn mozjpeg() -> Command {
                 .value_parser(["ycbcr", "grayscale", "rgb"])
                 .default_value("ycbcr"),
             arg!(--multipass "Specifies whether multiple scans should be considered during trellis quantization."),
-            arg!(--subsample <PIX> "Sets chroma subsampling.")
-                .value_parser(value_parser!(u8).range(1..=4)),
+            arg!(--subsample <PIX> "Sets chroma subsampling, as one factor or HORIZONTALxVERTICAL, e.g. 2x1 for 4:2:2.")
+                .value_parser(parse_subsample),
+            arg!(--restart <N> "Emit restart marker every N MCU rows, or every N MCU blocks with B suffix.")
+                .value_parser(|s: &str| s.parse::<RestartInterval>()),
+            arg!(--dc_scan_opt <MODE> "Split DC coefficients of progressive image into scans.")
+                .value_parser(["together", "per_component", "auto"])
+                .default_value("together"),
+            arg!(--no_trellis "Disable trellis quantization."),
+            arg!(--no_trellis_dc "Disable trellis quantization of DC coefficients."),
+            arg!(--no_deringing "Disable overshoot deringing."),
+            arg!(--dither <METHOD> "Dithering used to reduce high bit depth input to 8 bits.")
+                .value_parser(["none", "ordered", "diffusion"])
+                .default_value("none"),
             arg!(--qtable <TABLE> "Use a specific quantization table.")
                 .value_parser([
                     "AhumadaWatsonPeterson",
@@ -34,6 +67,26 @@ pub fn mozjpeg() -> Command {
                     "PetersonAhumadaWatson",
                     "WatsonTaylorBorthwick"
                 ])
-                .default_value("NRobidoux")
-        ]).common_args()
+                .default_value("NRobidoux"),
+            arg!(--qtable_index <IDX> "Use a MozJpeg quantization table scaled by quality, 0 - Annex K, 1 - Flat, 2 - MSSIM, 3 - ImageMagick, 4 - PSNR-HVS-M, 5 - Klein, 6 - Watson, 7 - Ahumada, 8 - Peterson.")
+                .value_parser(value_parser!(u8).range(0..=8))
+                .conflicts_with("qtable")
+        ])
+        .group(ArgGroup::new("transform").args(["rotate", "flip", "transpose", "transverse"]))
+        .common_args()
+}
+
+/// Parse subsampling factor for both axes or `HORIZONTALxVERTICAL` factors
+fn parse_subsample(s: &str) -> Result<(u8, u8), String> {
+    let factor = |value: &str| match value.parse::<u8>() {
+        Ok(factor @ 1..=4) => Ok(factor),
+        _ => Err(format!(
+            "Invalid subsampling '{s}', factors must be in 1..=4"
+        )),
+    };
+
+    match s.split_once('x') {
+        Some((h, v)) => Ok((factor(h)?, factor(v)?)),
+        None => factor(s).map(|factor| (factor, factor)),
+    }
 }
@@ -23,5 +23,12 @@ pub fn oxipng() -> Command {
             6   => (10 trials)"#})
             .value_parser(value_parser!(u8).range(0..=6))
             .default_value("2"),
+            arg!(--loop_count <NUM> "Number of times animation is played, 0 = infinite.")
+                .long_help(
+                    indoc! {r#"Number of times animation is played, 0 = infinite.
+
+                By default loop count of the animated source is kept."#},
+                )
+                .value_parser(value_parser!(u32)),
         ]).common_args()
 }
@@ -0,0 +1,31 @@
+use clap::{arg, Arg, Command};
+use indoc::indoc;
+
+use crate::cli::common::CommonArgs;
+
+pub fn dds() -> Command {
+    Command::new("dds")
+        .about("Encode images into DDS texture. (GPU Block Compressed)")
+        .args(texture_args())
+        .common_args()
+}
+
+pub fn ktx2() -> Command {
+    Command::new("ktx2")
+        .about("Encode images into KTX2 texture. (GPU Block Compressed)")
+        .args(texture_args())
+        .common_args()
+}
+
+fn texture_args() -> [Arg; 3] {
+    [
+        arg!(--compression <FORMAT> "Block compression of the texture.")
+            .long_help(indoc! {r#"Block compression of the texture.
+
+            bc1 = RGB without alpha, bc3 = RGBA, bc4 = single channel, bc5 = two channels (normal maps), bc7 = RGBA with the best quality."#})
+            .value_parser(["bc1", "bc3", "bc4", "bc5", "bc7"])
+            .default_value("bc7"),
+        arg!(--linear "Mark color data as linear instead of sRGB."),
+        arg!(--no_mipmaps "Write only the base level without the mipmap chain."),
+    ]
+}
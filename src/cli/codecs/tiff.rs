@@ -0,0 +1,19 @@
+use clap::{arg, Command};
+use indoc::indoc;
+
+use crate::cli::common::CommonArgs;
+
+pub fn tiff() -> Command {
+    Command::new("tiff")
+        .alias("tif")
+        .about("Encode images into TIFF format. (Lossless and Multi-page)")
+        .args([
+            arg!(--compression <METHOD> "Compression of the image data.")
+                .value_parser(["none", "lzw", "deflate", "packbits"])
+                .default_value("lzw"),
+            arg!(--predictor "Apply predictor before compression.").long_help(indoc! {r#"Apply predictor before compression.
+
+                Usually makes photos smaller when used with lzw or deflate compression, ignored otherwise."#}),
+        ])
+        .common_args()
+}
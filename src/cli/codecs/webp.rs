@@ -1,4 +1,5 @@
 use clap::{arg, value_parser, Command};
+use indoc::indoc;
 
 use crate::cli::common::CommonArgs;
 
@@ -16,6 +17,13 @@ pub fn webp() -> Command {
                 .requires("lossless"),
             arg!(--discrete "Discrete tone image.").requires("lossless"),
             arg!(--exact "Preserve transparent data."),
+            arg!(--loop_count <NUM> "Number of times animation is played, 0 = infinite.")
+                .long_help(
+                    indoc! {r#"Number of times animation is played, 0 = infinite.
+
+                By default loop count of the animated source is kept."#},
+                )
+                .value_parser(value_parser!(u32)),
         ])
         .common_args()
 }
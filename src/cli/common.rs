@@ -57,6 +57,12 @@ impl CommonArgs for Command {
 
                 By default, progress bar is enabled."#})
         )
+        .arg(
+            arg!(--raw_preview "Decodes embedded JPEG preview of camera RAW file(s).")
+                .long_help(indoc! {r#"Decodes embedded JPEG preview of camera RAW file(s).
+
+                Much faster than developing the sensor data, but the preview may be smaller than the original image."#})
+        )
         .arg(
             arg!(--quiet "Disables all output.")
                 .long_help(indoc! {r#"Disables all output.
@@ -1,95 +1,76 @@
-use std::io::{Seek, SeekFrom};
-use std::{collections::BTreeMap, fs::File, io::Read, path::Path};
+use std::{collections::BTreeMap, path::Path};
 
 use clap::ArgMatches;
 #[cfg(feature = "avif")]
 use rimage::codecs::avif::AvifEncoder;
+#[cfg(feature = "gif")]
+use rimage::codecs::gif::GifEncoder;
+#[cfg(feature = "ico")]
+use rimage::codecs::ico::IcoEncoder;
+#[cfg(feature = "jpegxl")]
+use rimage::codecs::jpegxl::{JpegXlEncoder, JpegXlTranscoder};
 #[cfg(feature = "mozjpeg")]
-use rimage::codecs::mozjpeg::MozJpegEncoder;
+use rimage::codecs::mozjpeg::{MozJpegEncoder, MozJpegTranscoder};
 #[cfg(feature = "oxipng")]
 use rimage::codecs::oxipng::OxiPngEncoder;
+#[cfg(feature = "texture")]
+use rimage::codecs::texture::{DdsEncoder, Ktx2Encoder};
+#[cfg(feature = "tiff")]
+use rimage::codecs::tiff::TiffEncoder;
 #[cfg(feature = "webp")]
 use rimage::codecs::webp::WebPEncoder;
-use zune_core::{bytestream::ZByteWriterTrait, options::EncoderOptions};
+use rimage::codecs::{
+    dynamic::ZuneEncoder,
+    registry::{self, DecodeOptions, Decoded, Format, SourceInfo},
+    DynEncoder,
+};
+use rimage::operations::icc::ApplySRGB;
+#[cfg(feature = "quantization")]
+use rimage::operations::quantize::Quantize;
+use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace, options::EncoderOptions};
 use zune_image::{
     codecs::{
-        farbfeld::FarbFeldEncoder, jpeg::JpegEncoder, jpeg_xl::JxlEncoder, png::PngEncoder,
-        ppm::PPMEncoder, qoi::QoiEncoder, ImageFormat,
+        farbfeld::FarbFeldEncoder, jpeg::JpegEncoder, png::PngEncoder, ppm::PPMEncoder,
+        qoi::QoiEncoder,
     },
+    core_filters::{colorspace::ColorspaceConv, depth::Depth},
     errors::ImageErrors,
     image::Image,
     metadata::AlphaState,
-    traits::{EncoderTrait, OperationsTrait},
+    traits::OperationsTrait,
 };
-use zune_imageprocs::premul_alpha::PremultiplyAlpha;
-
-pub fn decode<P: AsRef<Path>>(f: P) -> Result<Image, ImageErrors> {
-    Image::open(f.as_ref()).or_else(|e| {
-        if matches!(e, ImageErrors::ImageDecoderNotImplemented(_)) {
-            #[cfg(any(feature = "avif", feature = "webp"))]
-            let mut file = File::open(f.as_ref())?;
-
-            #[cfg(feature = "avif")]
-            {
-                let mut file_content = vec![];
-
-                file.read_to_end(&mut file_content)?;
-                file.seek(SeekFrom::Start(0))?;
-
-                if libavif::is_avif(&file_content) {
-                    use rimage::codecs::avif::AvifDecoder;
-
-                    let decoder = AvifDecoder::try_new(file)?;
-
-                    return Image::from_decoder(decoder);
-                };
-                file.seek(SeekFrom::Start(0))?;
-            }
-
-            #[cfg(feature = "webp")]
-            {
-                if f.as_ref()
-                    .extension()
-                    .is_some_and(|f| f.eq_ignore_ascii_case("webp"))
-                {
-                    use rimage::codecs::webp::WebPDecoder;
-
-                    let decoder = WebPDecoder::try_new(file)?;
-
-                    return Image::from_decoder(decoder);
-                }
-
-                file.seek(SeekFrom::Start(0))?;
-            }
-
-            #[cfg(feature = "tiff")]
-            {
-                if f.as_ref()
-                    .extension()
-                    .is_some_and(|f| f.eq_ignore_ascii_case("tiff") | f.eq_ignore_ascii_case("tif"))
-                {
-                    use rimage::codecs::tiff::TiffDecoder;
+use zune_imageprocs::{auto_orient::AutoOrient, premul_alpha::PremultiplyAlpha};
 
-                    let decoder = TiffDecoder::try_new(file)?;
+#[cfg(not(feature = "jpegxl"))]
+use zune_image::codecs::jpeg_xl::JxlEncoder;
 
-                    return Image::from_decoder(decoder);
-                }
-
-                file.seek(SeekFrom::Start(0))?;
-            }
-
-            Err(ImageErrors::ImageDecoderNotImplemented(
-                ImageFormat::Unknown,
-            ))
-        } else {
-            Err(e)
-        }
-    })
+/// Decode file `data` read from `f` with options of the subcommand
+#[allow(unused_variables)]
+pub fn decode<P: AsRef<Path>>(
+    f: P,
+    data: Vec<u8>,
+    matches: &ArgMatches,
+) -> Result<Decoded, ImageErrors> {
+    let options = DecodeOptions {
+        #[cfg(feature = "raw")]
+        raw: rimage::codecs::raw::RawDecoderOptions {
+            preview: matches.get_flag("raw_preview"),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let extension = f.as_ref().extension().and_then(|ext| ext.to_str());
+
+    registry::decode_data(data, extension, &options)
 }
 
 #[allow(unused_variables)]
 #[allow(unused_mut)]
-pub fn operations(matches: &ArgMatches, img: &Image) -> BTreeMap<usize, Box<dyn OperationsTrait>> {
+pub fn operations(
+    matches: &ArgMatches,
+    image: &Image,
+) -> BTreeMap<usize, Box<dyn OperationsTrait>> {
     let mut map: BTreeMap<usize, Box<dyn OperationsTrait>> = BTreeMap::new();
 
     #[cfg(feature = "resize")]
@@ -101,7 +82,7 @@ pub fn operations(matches: &ArgMatches, img: &Image) -> BTreeMap<usize, Box<dyn
         if let Some(values) = matches.get_many::<ResizeValue>("resize") {
             let filter = matches.get_one::<ResizeFilter>("filter");
 
-            let (w, h) = img.dimensions();
+            let (w, h) = image.dimensions();
 
             values
                 .into_iter()
@@ -126,24 +107,15 @@ pub fn operations(matches: &ArgMatches, img: &Image) -> BTreeMap<usize, Box<dyn
     }
 
     #[cfg(feature = "quantization")]
-    {
-        use rimage::operations::quantize::Quantize;
-
-        if let Some(values) = matches.get_many::<u8>("quantization") {
-            let dithering = matches.get_one::<u8>("dithering");
-
-            values
-                .into_iter()
-                .zip(matches.indices_of("quantization").unwrap())
-                .for_each(|(value, idx)| {
-                    log::trace!("setup quantization {value} on index {idx}");
+    if let Some(values) = matches.get_many::<u8>("quantization") {
+        values
+            .into_iter()
+            .zip(matches.indices_of("quantization").unwrap())
+            .for_each(|(value, idx)| {
+                log::trace!("setup quantization {value} on index {idx}");
 
-                    map.insert(
-                        idx,
-                        Box::new(Quantize::new(*value, dithering.map(|q| *q as f32 / 100.))),
-                    );
-                })
-        }
+                map.insert(idx, Box::new(quantize(matches, *value)));
+            })
     }
 
     if let Some(values) = matches.get_many::<bool>("premultiply") {
@@ -178,68 +150,124 @@ pub fn operations(matches: &ArgMatches, img: &Image) -> BTreeMap<usize, Box<dyn
     map
 }
 
-pub enum AvailableEncoders {
-    FarbFeld(Box<FarbFeldEncoder>),
-    Jpeg(Box<JpegEncoder>),
-    JpegXl(Box<JxlEncoder>),
-    #[cfg(feature = "mozjpeg")]
-    MozJpeg(Box<MozJpegEncoder>),
-    #[cfg(feature = "oxipng")]
-    OxiPng(Box<OxiPngEncoder>),
-    #[cfg(feature = "avif")]
-    Avif(Box<AvifEncoder>),
-    #[cfg(feature = "webp")]
-    Webp(Box<WebPEncoder>),
-    Png(Box<PngEncoder>),
-    Ppm(Box<PPMEncoder>),
-    Qoi(Box<QoiEncoder>),
+#[cfg(feature = "quantization")]
+fn quantize(matches: &ArgMatches, quality: u8) -> Quantize {
+    let dithering = matches.get_one::<u8>("dithering");
+
+    Quantize::new(quality, dithering.map(|q| *q as f32 / 100.))
+}
+
+/// Remove the quantization done after all other operations from `operations`
+#[cfg(feature = "quantization")]
+fn take_last_quantize(
+    matches: &ArgMatches,
+    operations: &mut BTreeMap<usize, Box<dyn OperationsTrait>>,
+) -> Option<Quantize> {
+    let (&idx, _) = operations
+        .last_key_value()
+        .filter(|(_, op)| op.name() == "quantize")?;
+
+    operations.remove(&idx);
+
+    let (quality, _) = matches
+        .get_many::<u8>("quantization")?
+        .zip(matches.indices_of("quantization")?)
+        .find(|&(_, i)| i == idx)?;
+
+    Some(quantize(matches, *quality))
+}
+
+/// Operations converting the decoded image for the encoder
+pub struct Pipeline {
+    image: Image,
+    operations: Vec<Box<dyn OperationsTrait>>,
+    /// Quantization done last, its indexed pixels are written by the encoder
+    #[cfg(feature = "quantization")]
+    quantize: Option<Quantize>,
 }
 
-impl AvailableEncoders {
-    pub fn to_extension(&self) -> &'static str {
-        match self {
-            AvailableEncoders::FarbFeld(_) => "ff",
-            AvailableEncoders::Jpeg(_) => "jpg",
-            AvailableEncoders::JpegXl(_) => "jxl",
-            #[cfg(feature = "mozjpeg")]
-            AvailableEncoders::MozJpeg(_) => "jpg",
-            #[cfg(feature = "oxipng")]
-            AvailableEncoders::OxiPng(_) => "png",
-            #[cfg(feature = "avif")]
-            AvailableEncoders::Avif(_) => "avif",
-            #[cfg(feature = "webp")]
-            AvailableEncoders::Webp(_) => "webp",
-            AvailableEncoders::Png(_) => "png",
-            AvailableEncoders::Ppm(_) => "ppm",
-            AvailableEncoders::Qoi(_) => "qoi",
+impl Pipeline {
+    /// Run the operations, indexed pixels of the quantized image are passed to the `encoder`
+    #[allow(unused_variables)]
+    pub fn run(mut self, encoder: &mut dyn DynEncoder) -> Result<Image, ImageErrors> {
+        for operation in &self.operations {
+            operation.execute(&mut self.image)?;
         }
-    }
 
-    pub fn encode<T: ZByteWriterTrait>(
-        &mut self,
-        img: &Image,
-        sink: T,
-    ) -> Result<usize, ImageErrors> {
-        match self {
-            AvailableEncoders::FarbFeld(enc) => enc.encode(img, sink),
-            AvailableEncoders::Jpeg(enc) => enc.encode(img, sink),
-            AvailableEncoders::JpegXl(enc) => enc.encode(img, sink),
-            AvailableEncoders::MozJpeg(enc) => enc.encode(img, sink),
-            AvailableEncoders::OxiPng(enc) => enc.encode(img, sink),
-            AvailableEncoders::Avif(enc) => enc.encode(img, sink),
-            AvailableEncoders::Webp(enc) => enc.encode(img, sink),
-            AvailableEncoders::Png(enc) => enc.encode(img, sink),
-            AvailableEncoders::Ppm(enc) => enc.encode(img, sink),
-            AvailableEncoders::Qoi(enc) => enc.encode(img, sink),
+        #[cfg(feature = "quantization")]
+        if let Some(quantize) = &self.quantize {
+            if let Some(indexed) = quantize.quantize(&mut self.image)? {
+                encoder.set_indexed(indexed);
+            }
         }
+
+        Ok(self.image)
     }
 }
 
-pub fn encoder(name: &str, matches: &ArgMatches) -> Result<AvailableEncoders, ImageErrors> {
+/// Pipeline converting the `decoded` image for the `encoder` and running the operations
+pub fn build_pipeline(
+    matches: &ArgMatches,
+    decoded: Decoded,
+    encoder: &dyn DynEncoder,
+) -> Pipeline {
+    let mut operations = operations(matches, &decoded.image);
+
+    // quantization supports only 8-bit images
+    let depth = if operations.values().any(|op| op.name() == "quantize") {
+        BitDepth::Eight
+    } else {
+        encoder.default_depth(decoded.image.depth())
+    };
+
+    #[cfg(feature = "quantization")]
+    let quantize = take_last_quantize(matches, &mut operations);
+
+    let mut pipeline: Vec<Box<dyn OperationsTrait>> = vec![
+        Box::new(Depth::new(depth)),
+        Box::new(ColorspaceConv::new(ColorSpace::RGBA)),
+        Box::new(AutoOrient),
+        Box::new(ApplySRGB),
+    ];
+
+    operations
+        .into_iter()
+        .for_each(|(_, operations)| match operations.name() {
+            "quantize" => {
+                pipeline.push(Box::new(Depth::new(BitDepth::Eight)));
+                pipeline.push(Box::new(ColorspaceConv::new(ColorSpace::RGBA)));
+                pipeline.push(operations);
+            }
+            _ => {
+                pipeline.push(operations);
+            }
+        });
+
+    #[cfg(feature = "quantization")]
+    if quantize.is_some() {
+        pipeline.push(Box::new(Depth::new(BitDepth::Eight)));
+        pipeline.push(Box::new(ColorspaceConv::new(ColorSpace::RGBA)));
+    }
+
+    Pipeline {
+        image: decoded.image,
+        operations: pipeline,
+        #[cfg(feature = "quantization")]
+        quantize,
+    }
+}
+
+#[allow(unused_variables)]
+pub fn encoder(
+    name: &str,
+    matches: &ArgMatches,
+    source: &SourceInfo,
+) -> Result<Box<dyn DynEncoder>, ImageErrors> {
     match name {
-        "farbfeld" => Ok(AvailableEncoders::FarbFeld(
-            Box::new(FarbFeldEncoder::new()),
-        )),
+        "farbfeld" => Ok(Box::new(ZuneEncoder::new(
+            FarbFeldEncoder::new(),
+            Format::FarbFeld,
+        ))),
         "jpeg" => {
             let options = EncoderOptions::default();
 
@@ -249,15 +277,106 @@ pub fn encoder(name: &str, matches: &ArgMatches) -> Result<AvailableEncoders, Im
 
             options.set_jpeg_encode_progressive(matches.get_flag("progressive"));
 
-            Ok(AvailableEncoders::Jpeg(Box::new(
+            Ok(Box::new(ZuneEncoder::new(
                 JpegEncoder::new_with_options(options),
+                Format::Jpeg,
             )))
         }
-        "jpeg_xl" => Ok(AvailableEncoders::JpegXl(Box::new(JxlEncoder::new()))),
+        #[cfg(feature = "ico")]
+        "ico" => {
+            use rimage::codecs::ico::IcoOptions;
+
+            let options = IcoOptions {
+                sizes: matches.get_many::<u32>("sizes").unwrap().copied().collect(),
+                ..Default::default()
+            };
+
+            Ok(Box::new(IcoEncoder::new_with_options(options)))
+        }
+        #[cfg(not(feature = "jpegxl"))]
+        "jpeg_xl" => Ok(Box::new(ZuneEncoder::new(
+            JxlEncoder::new(),
+            Format::JpegXl,
+        ))),
+        #[cfg(feature = "jpegxl")]
+        "jpeg_xl" => {
+            use rimage::codecs::jpegxl::{JpegXlOptions, JpegXlTranscoderOptions};
+
+            let effort = *matches.get_one::<u8>("effort").unwrap();
+
+            if matches.get_flag("lossless_jpeg") {
+                return Ok(Box::new(JpegXlTranscoder::new_with_options(
+                    JpegXlTranscoderOptions { effort },
+                )));
+            }
+
+            let quality = matches.get_one::<u8>("quality").copied();
+            let distance = matches.get_one::<f32>("distance").copied();
+
+            let options = JpegXlOptions {
+                // lossless unless lossy encoding is asked for, like without libjxl
+                lossless: matches.get_flag("lossless") || (quality.is_none() && distance.is_none()),
+                distance: distance.unwrap_or_else(|| {
+                    JpegXlOptions::distance_from_quality(quality.unwrap_or(100) as f32)
+                }),
+                effort,
+            };
+
+            Ok(Box::new(JpegXlEncoder::new_with_options(options)))
+        }
+        #[cfg(feature = "gif")]
+        "gif" => {
+            use rimage::codecs::gif::GifOptions;
+
+            let options = GifOptions {
+                quality: *matches.get_one::<u8>("quality").unwrap(),
+                dithering: *matches.get_one::<u8>("dither").unwrap() as f32 / 100.,
+                global_palette: matches.get_flag("global_palette"),
+                loop_count: matches
+                    .get_one::<u32>("loop_count")
+                    .copied()
+                    .unwrap_or(source.loop_count),
+            };
+
+            Ok(Box::new(GifEncoder::new_with_options(options)))
+        }
         #[cfg(feature = "mozjpeg")]
         "mozjpeg" => {
             use mozjpeg::qtable;
-            use rimage::codecs::mozjpeg::MozJpegOptions;
+            use rimage::codecs::mozjpeg::{
+                CopyMarkers, Dithering, JpegCrop, JpegTransform, MozJpegOptions,
+                MozJpegTranscoderOptions, RestartInterval,
+            };
+
+            if matches.get_flag("lossless") {
+                let options = MozJpegTranscoderOptions {
+                    progressive: !matches.get_flag("baseline"),
+                    copy_markers: if matches.get_flag("strip") {
+                        CopyMarkers::None
+                    } else {
+                        CopyMarkers::All
+                    },
+                    // same as orientation fix of the pipeline unless set explicitly
+                    transform: match (
+                        matches.get_one::<String>("rotate").map(String::as_str),
+                        matches.get_one::<String>("flip").map(String::as_str),
+                    ) {
+                        (Some("90"), _) => JpegTransform::Rotate90,
+                        (Some("180"), _) => JpegTransform::Rotate180,
+                        (Some("270"), _) => JpegTransform::Rotate270,
+                        (_, Some("horizontal")) => JpegTransform::FlipHorizontal,
+                        (_, Some("vertical")) => JpegTransform::FlipVertical,
+                        _ if matches.get_flag("transpose") => JpegTransform::Transpose,
+                        _ if matches.get_flag("transverse") => JpegTransform::Transverse,
+                        _ => JpegTransform::AutoOrient,
+                    },
+                    crop: matches.get_one::<JpegCrop>("crop").copied(),
+                    trim: !matches.get_flag("no_trim"),
+                    perfect: matches.get_flag("perfect"),
+                };
+
+                return Ok(Box::new(MozJpegTranscoder::new_with_options(options)));
+            }
 
             let quality = *matches.get_one::<u8>("quality").unwrap() as f32;
             let chroma_quality = matches
@@ -265,6 +384,11 @@ pub fn encoder(name: &str, matches: &ArgMatches) -> Result<AvailableEncoders, Im
                 .map(|q| *q as f32)
                 .unwrap_or(quality);
 
+            // built-in table index replaces explicit tables
+            let qtable = matches
+                .get_one::<String>("qtable")
+                .filter(|_| !matches.contains_id("qtable_index"));
+
             let options = MozJpegOptions {
                 quality,
                 progressive: !matches.get_flag("baseline"),
@@ -275,84 +399,93 @@ pub fn encoder(name: &str, matches: &ArgMatches) -> Result<AvailableEncoders, Im
                     .unwrap_or_default(),
                 color_space: match matches.get_one::<String>("colorspace").unwrap().as_str() {
                     "ycbcr" => mozjpeg::ColorSpace::JCS_YCbCr,
-                    "rgb" => mozjpeg::ColorSpace::JCS_EXT_RGB,
+                    "rgb" => mozjpeg::ColorSpace::JCS_RGB,
                     "grayscale" => mozjpeg::ColorSpace::JCS_GRAYSCALE,
                     _ => unreachable!(),
                 },
                 trellis_multipass: matches.get_flag("multipass"),
-                chroma_subsample: matches.get_one::<u8>("subsample").copied(),
-
-                luma_qtable: matches
-                    .get_one::<String>("qtable")
-                    .map(|c| match c.as_str() {
-                        "AhumadaWatsonPeterson" => {
-                            qtable::AhumadaWatsonPeterson.scaled(quality, quality)
-                        }
-                        "AnnexK" => qtable::AnnexK_Luma.scaled(quality, quality),
-                        "Flat" => qtable::Flat.scaled(quality, quality),
-                        "KleinSilversteinCarney" => {
-                            qtable::KleinSilversteinCarney.scaled(quality, quality)
-                        }
-                        "MSSSIM" => qtable::MSSSIM_Luma.scaled(quality, quality),
-                        "NRobidoux" => qtable::NRobidoux.scaled(quality, quality),
-                        "PSNRHVS" => qtable::PSNRHVS_Luma.scaled(quality, quality),
-                        "PetersonAhumadaWatson" => {
-                            qtable::PetersonAhumadaWatson.scaled(quality, quality)
-                        }
-                        "WatsonTaylorBorthwick" => {
-                            qtable::WatsonTaylorBorthwick.scaled(quality, quality)
-                        }
-                        _ => unreachable!(),
-                    }),
+                chroma_subsample: matches.get_one::<(u8, u8)>("subsample").copied(),
+                restart_interval: matches.get_one::<RestartInterval>("restart").copied(),
+                dc_scan_mode: match matches.get_one::<String>("dc_scan_opt").unwrap().as_str() {
+                    "together" => mozjpeg::ScanMode::AllComponentsTogether,
+                    "per_component" => mozjpeg::ScanMode::ScanPerComponent,
+                    "auto" => mozjpeg::ScanMode::Auto,
+                    _ => unreachable!(),
+                },
+                trellis: !matches.get_flag("no_trellis"),
+                trellis_dc: !matches.get_flag("no_trellis_dc"),
+                overshoot_deringing: !matches.get_flag("no_deringing"),
+                qtable_index: matches.get_one::<u8>("qtable_index").copied(),
+                dithering: match matches.get_one::<String>("dither").unwrap().as_str() {
+                    "none" => Dithering::None,
+                    "ordered" => Dithering::Ordered,
+                    "diffusion" => Dithering::ErrorDiffusion,
+                    _ => unreachable!(),
+                },
 
-                chroma_qtable: matches
-                    .get_one::<String>("qtable")
-                    .map(|c| match c.as_str() {
-                        "AhumadaWatsonPeterson" => {
-                            qtable::AhumadaWatsonPeterson.scaled(chroma_quality, chroma_quality)
-                        }
-                        "AnnexK" => qtable::AnnexK_Chroma.scaled(chroma_quality, chroma_quality),
-                        "Flat" => qtable::Flat.scaled(chroma_quality, chroma_quality),
-                        "KleinSilversteinCarney" => {
-                            qtable::KleinSilversteinCarney.scaled(chroma_quality, chroma_quality)
-                        }
-                        "MSSSIM" => qtable::MSSSIM_Chroma.scaled(chroma_quality, chroma_quality),
-                        "NRobidoux" => qtable::NRobidoux.scaled(chroma_quality, chroma_quality),
-                        "PSNRHVS" => qtable::PSNRHVS_Chroma.scaled(chroma_quality, chroma_quality),
-                        "PetersonAhumadaWatson" => {
-                            qtable::PetersonAhumadaWatson.scaled(chroma_quality, chroma_quality)
-                        }
-                        "WatsonTaylorBorthwick" => {
-                            qtable::WatsonTaylorBorthwick.scaled(chroma_quality, chroma_quality)
-                        }
-                        _ => unreachable!(),
-                    }),
+                luma_qtable: qtable.map(|c| match c.as_str() {
+                    "AhumadaWatsonPeterson" => {
+                        qtable::AhumadaWatsonPeterson.scaled(quality, quality)
+                    }
+                    "AnnexK" => qtable::AnnexK_Luma.scaled(quality, quality),
+                    "Flat" => qtable::Flat.scaled(quality, quality),
+                    "KleinSilversteinCarney" => {
+                        qtable::KleinSilversteinCarney.scaled(quality, quality)
+                    }
+                    "MSSSIM" => qtable::MSSSIM_Luma.scaled(quality, quality),
+                    "NRobidoux" => qtable::NRobidoux.scaled(quality, quality),
+                    "PSNRHVS" => qtable::PSNRHVS_Luma.scaled(quality, quality),
+                    "PetersonAhumadaWatson" => {
+                        qtable::PetersonAhumadaWatson.scaled(quality, quality)
+                    }
+                    "WatsonTaylorBorthwick" => {
+                        qtable::WatsonTaylorBorthwick.scaled(quality, quality)
+                    }
+                    _ => unreachable!(),
+                }),
+
+                chroma_qtable: qtable.map(|c| match c.as_str() {
+                    "AhumadaWatsonPeterson" => {
+                        qtable::AhumadaWatsonPeterson.scaled(chroma_quality, chroma_quality)
+                    }
+                    "AnnexK" => qtable::AnnexK_Chroma.scaled(chroma_quality, chroma_quality),
+                    "Flat" => qtable::Flat.scaled(chroma_quality, chroma_quality),
+                    "KleinSilversteinCarney" => {
+                        qtable::KleinSilversteinCarney.scaled(chroma_quality, chroma_quality)
+                    }
+                    "MSSSIM" => qtable::MSSSIM_Chroma.scaled(chroma_quality, chroma_quality),
+                    "NRobidoux" => qtable::NRobidoux.scaled(chroma_quality, chroma_quality),
+                    "PSNRHVS" => qtable::PSNRHVS_Chroma.scaled(chroma_quality, chroma_quality),
+                    "PetersonAhumadaWatson" => {
+                        qtable::PetersonAhumadaWatson.scaled(chroma_quality, chroma_quality)
+                    }
+                    "WatsonTaylorBorthwick" => {
+                        qtable::WatsonTaylorBorthwick.scaled(chroma_quality, chroma_quality)
+                    }
+                    _ => unreachable!(),
+                }),
             };
 
-            Ok(AvailableEncoders::MozJpeg(Box::new(
-                MozJpegEncoder::new_with_options(options),
-            )))
+            Ok(Box::new(MozJpegEncoder::new_with_options(options)))
         }
         #[cfg(feature = "oxipng")]
         "oxipng" => {
             use rimage::codecs::oxipng::OxiPngOptions;
 
-            let mut options =
-                OxiPngOptions::from_preset(*matches.get_one::<u8>("effort").unwrap_or(&2));
-
-            options.interlace = if matches.get_flag("interlace") {
-                Some(oxipng::Interlacing::Adam7)
-            } else {
-                None
+            let options = OxiPngOptions {
+                interlace: matches.get_flag("interlace"),
+                loop_count: matches
+                    .get_one::<u32>("loop_count")
+                    .copied()
+                    .unwrap_or(source.loop_count),
+                ..OxiPngOptions::from_preset(*matches.get_one::<u8>("effort").unwrap_or(&2))
             };
 
-            Ok(AvailableEncoders::OxiPng(Box::new(
-                OxiPngEncoder::new_with_options(options),
-            )))
+            Ok(Box::new(OxiPngEncoder::new_with_options(options)))
         }
         #[cfg(feature = "avif")]
         "avif" => {
-            use rimage::codecs::avif::AvifOptions;
+            use rimage::codecs::avif::{AvifBackend, AvifBitDepth, AvifOptions};
 
             let options = AvifOptions {
                 quality: *matches.get_one::<u8>("quality").unwrap() as f32,
@@ -369,33 +502,102 @@ pub fn encoder(name: &str, matches: &ArgMatches) -> Result<AvailableEncoders, Im
                     "Premultiplied" => ravif::AlphaColorMode::Premultiplied,
                     _ => unreachable!(),
                 },
+                bit_depth: matches
+                    .get_one::<String>("depth")
+                    .map(|depth| match depth.as_str() {
+                        "8" => AvifBitDepth::Eight,
+                        "10" => AvifBitDepth::Ten,
+                        "12" => AvifBitDepth::Twelve,
+                        _ => unreachable!(),
+                    }),
+                backend: match matches.get_one::<String>("backend").unwrap().as_str() {
+                    "auto" => AvifBackend::Auto,
+                    "ravif" => AvifBackend::Ravif,
+                    "libavif" => AvifBackend::Libavif,
+                    _ => unreachable!(),
+                },
+                loop_count: matches
+                    .get_one::<u32>("loop_count")
+                    .copied()
+                    .unwrap_or(source.loop_count),
+                xmp: source.xmp.clone(),
             };
 
-            Ok(AvailableEncoders::Avif(Box::new(
-                AvifEncoder::new_with_options(options),
-            )))
+            Ok(Box::new(AvifEncoder::new_with_options(options)))
+        }
+        #[cfg(feature = "texture")]
+        "dds" | "ktx2" => {
+            use rimage::codecs::texture::{TextureCompression, TextureOptions};
+
+            let options = TextureOptions {
+                compression: match matches.get_one::<String>("compression").unwrap().as_str() {
+                    "bc1" => TextureCompression::Bc1,
+                    "bc3" => TextureCompression::Bc3,
+                    "bc4" => TextureCompression::Bc4,
+                    "bc5" => TextureCompression::Bc5,
+                    "bc7" => TextureCompression::Bc7,
+                    _ => unreachable!(),
+                },
+                srgb: !matches.get_flag("linear"),
+                mipmaps: !matches.get_flag("no_mipmaps"),
+                ..Default::default()
+            };
+
+            if name == "dds" {
+                Ok(Box::new(DdsEncoder::new_with_options(options)))
+            } else {
+                Ok(Box::new(Ktx2Encoder::new_with_options(options)))
+            }
+        }
+        #[cfg(feature = "tiff")]
+        "tiff" => {
+            use rimage::codecs::tiff::{TiffCompression, TiffOptions};
+
+            let options = TiffOptions {
+                compression: match matches.get_one::<String>("compression").unwrap().as_str() {
+                    "none" => TiffCompression::None,
+                    "lzw" => TiffCompression::Lzw,
+                    "deflate" => TiffCompression::Deflate,
+                    "packbits" => TiffCompression::PackBits,
+                    _ => unreachable!(),
+                },
+                predictor: matches.get_flag("predictor"),
+                xmp: source.xmp.clone(),
+            };
+
+            Ok(Box::new(TiffEncoder::new_with_options(options)))
         }
         #[cfg(feature = "webp")]
         "webp" => {
             use rimage::codecs::webp::WebPOptions;
 
-            let mut options = WebPOptions::new().unwrap();
+            let mut options = WebPOptions {
+                loop_count: matches
+                    .get_one::<u32>("loop_count")
+                    .copied()
+                    .unwrap_or(source.loop_count),
+                background_color: source.background_color,
+                xmp: source.xmp.clone(),
+                ..Default::default()
+            };
 
-            options.quality = *matches.get_one::<u8>("quality").unwrap() as f32;
-            options.lossless = matches.get_flag("lossless") as i32;
-            options.near_lossless = 100 - *matches.get_one::<u8>("slight_loss").unwrap() as i32;
-            options.exact = matches.get_flag("exact") as i32;
+            options.config.quality = *matches.get_one::<u8>("quality").unwrap() as f32;
+            options.config.lossless = matches.get_flag("lossless") as i32;
+            options.config.near_lossless =
+                100 - *matches.get_one::<u8>("slight_loss").unwrap() as i32;
+            options.config.exact = matches.get_flag("exact") as i32;
 
-            Ok(AvailableEncoders::Webp(Box::new(
-                WebPEncoder::new_with_options(options),
-            )))
+            Ok(Box::new(WebPEncoder::new_with_options(options)))
         }
-        "png" => Ok(AvailableEncoders::Png(Box::new(PngEncoder::new()))),
-        "ppm" => Ok(AvailableEncoders::Ppm(Box::new(PPMEncoder::new()))),
-        "qoi" => Ok(AvailableEncoders::Qoi(Box::new(QoiEncoder::new()))),
+        "png" => Ok(Box::new(ZuneEncoder::new(PngEncoder::new(), Format::Png))),
+        "ppm" => Ok(Box::new(ZuneEncoder::new(PPMEncoder::new(), Format::Ppm))),
+        "qoi" => Ok(Box::new(ZuneEncoder::new(QoiEncoder::new(), Format::Qoi))),
 
         name => Err(ImageErrors::GenericString(format!(
             "Encoder \"{name}\" not found",
         ))),
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,69 @@
+use crate::cli::cli;
+
+use super::*;
+
+/// Matches of the `rimage <args>` subcommand
+fn matches(args: &[&str]) -> ArgMatches {
+    let matches = cli().get_matches_from(std::iter::once("rimage").chain(args.iter().copied()));
+
+    matches.subcommand().unwrap().1.clone()
+}
+
+#[test]
+#[cfg(feature = "resize")]
+fn decode_raster() {
+    let matches = matches(&["png", "--resize", "24w", "tests/files/png/f1t.png"]);
+
+    let path = "tests/files/png/f1t.png";
+    let decoded = decode(path, std::fs::read(path).unwrap(), &matches).unwrap();
+
+    assert_eq!(decoded.image.dimensions(), (48, 80));
+    assert_eq!(operations(&matches, &decoded.image).len(), 1);
+}
+
+#[test]
+#[cfg(all(feature = "oxipng", feature = "quantization"))]
+fn quantize_sixteen_bit() {
+    let matches = matches(&["oxipng", "--quantization", "tests/files/png/f1t.png"]);
+
+    let image = Image::from_fn(32, 32, ColorSpace::RGBA, |x, y, px: &mut [u16; 4]| {
+        *px = [(x * 2000) as u16, (y * 2000) as u16, 0, u16::MAX];
+    });
+    let decoded = Decoded {
+        image,
+        source: SourceInfo::default(),
+    };
+
+    let mut encoder = encoder("oxipng", &matches, &SourceInfo::default()).unwrap();
+    assert_eq!(encoder.default_depth(BitDepth::Sixteen), BitDepth::Sixteen);
+
+    let pipeline = build_pipeline(&matches, decoded, encoder.as_ref());
+    let image = pipeline.run(encoder.as_mut()).unwrap();
+
+    assert_eq!(image.depth(), BitDepth::Eight);
+
+    let mut data = vec![];
+    encoder.encode(&image, &mut data).unwrap();
+
+    let decoded = rimage::decode(data.as_slice()).unwrap();
+    assert_eq!(decoded.depth(), BitDepth::Eight);
+}
+
+#[test]
+#[cfg(feature = "webp")]
+fn decode_source_info() {
+    use rimage::codecs::webp::WebPDecoder;
+
+    let path = "tests/files/webp/f1t.webp";
+    let matches = matches(&["webp", path]);
+
+    let data = std::fs::read(path).unwrap();
+
+    let decoded = decode(path, data.clone(), &matches).unwrap();
+    let decoder = WebPDecoder::try_new(std::io::Cursor::new(data)).unwrap();
+
+    assert_eq!(decoded.image.dimensions(), (48, 80));
+    assert_eq!(decoded.source.loop_count, decoder.loop_count());
+    assert_eq!(decoded.source.background_color, decoder.background_color());
+    assert_eq!(decoded.source.xmp.as_deref(), decoder.xmp());
+}
@@ -9,6 +9,16 @@ pub use resize::{ResizeFilter, ResizeValue};
 #[cfg(feature = "resize")]
 mod resize;
 
+/// Arguments adding operations to the pipeline
+pub const OPERATIONS: &[&str] = &[
+    #[cfg(feature = "resize")]
+    "resize",
+    #[cfg(feature = "quantization")]
+    "quantization",
+    #[cfg(any(feature = "resize", feature = "quantization"))]
+    "premultiply",
+];
+
 impl Preprocessors for Command {
     #[cfg(any(feature = "resize", feature = "quantization"))]
     fn preprocessors(self) -> Self {
@@ -1,12 +1,20 @@
-use std::{io::Read, marker::PhantomData};
+use std::{
+    io::{Read, Seek},
+    marker::PhantomData,
+};
 
-use zune_core::colorspace::ColorSpace;
-use zune_image::{errors::ImageErrors, image::Image, traits::DecoderTrait};
+use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
+use zune_image::{errors::ImageErrors, frame::Frame, image::Image, traits::DecoderTrait};
+
+use super::sys;
+use crate::{Format, ImageInfo};
 
 /// A AVIF decoder
 pub struct AvifDecoder<R: Read> {
     inner: Vec<u8>,
-    dimensions: Option<(usize, usize)>,
+    info: sys::AvifInfo,
+    loop_count: u32,
+    xmp: Option<Vec<u8>>,
     phantom: PhantomData<R>,
 }
 
@@ -16,12 +24,74 @@ impl<R: Read> AvifDecoder<R> {
         let mut buf = Vec::new();
         source.read_to_end(&mut buf)?;
 
+        let (info, loop_count, xmp) = {
+            let decoder = sys::AvifDecoder::parse(&buf)?;
+            (decoder.info(), decoder.loop_count(), decoder.xmp())
+        };
+
         Ok(AvifDecoder {
             inner: buf,
-            dimensions: None,
+            info,
+            loop_count,
+            xmp,
             phantom: PhantomData,
         })
     }
+
+    /// Image information read from the headers
+    pub fn info(&self) -> ImageInfo {
+        image_info(&self.info)
+    }
+
+    fn colorspace(&self) -> ColorSpace {
+        colorspace(&self.info)
+    }
+
+    fn depth(&self) -> BitDepth {
+        depth(&self.info)
+    }
+
+    /// Number of times animation is played, `0` means infinite
+    pub fn loop_count(&self) -> u32 {
+        self.loop_count
+    }
+
+    /// Raw XMP packet of the image
+    pub fn xmp(&self) -> Option<&[u8]> {
+        self.xmp.as_deref()
+    }
+}
+
+/// Read image information of the AVIF file without loading its image data
+pub(crate) fn probe<R: Read + Seek>(source: R) -> Result<ImageInfo, ImageErrors> {
+    Ok(image_info(&sys::AvifDecoder::parse_stream(source)?.info()))
+}
+
+fn image_info(info: &sys::AvifInfo) -> ImageInfo {
+    ImageInfo {
+        format: Format::Avif,
+        dimensions: (info.width, info.height),
+        colorspace: colorspace(info),
+        depth: depth(info),
+        frame_count: info.frame_count,
+        has_alpha: info.has_alpha,
+    }
+}
+
+fn colorspace(info: &sys::AvifInfo) -> ColorSpace {
+    if info.has_alpha {
+        ColorSpace::RGBA
+    } else {
+        ColorSpace::RGB
+    }
+}
+
+fn depth(info: &sys::AvifInfo) -> BitDepth {
+    if info.depth > 8 {
+        BitDepth::Sixteen
+    } else {
+        BitDepth::Eight
+    }
 }
 
 impl<R> DecoderTrait for AvifDecoder<R>
@@ -29,21 +99,46 @@ where
     R: Read,
 {
     fn decode(&mut self) -> Result<Image, ImageErrors> {
-        let img = libavif::decode_rgb(&self.inner)
-            .map_err(|e| ImageErrors::ImageDecodeErrors(e.to_string()))?;
+        let mut decoder = sys::AvifDecoder::parse(&self.inner)?;
+
+        let (w, h) = (self.info.width, self.info.height);
+        let colorspace = self.colorspace();
+        let depth = self.depth();
+
+        let mut frames = Vec::with_capacity(self.info.frame_count);
+
+        if depth == BitDepth::Sixteen {
+            while let Some((data, duration)) = decoder.next_frame::<u16>()? {
+                frames.push(Frame::from_u16(&data, colorspace, duration as usize, 1000));
+            }
+        } else {
+            while let Some((data, duration)) = decoder.next_frame::<u8>()? {
+                frames.push(Frame::from_u8(&data, colorspace, duration as usize, 1000));
+            }
+        }
 
-        let (w, h) = (img.width() as usize, img.height() as usize);
-        self.dimensions = Some((w, h));
+        let mut image = Image::new_frames(frames, depth, w, h, colorspace);
 
-        Ok(Image::from_u8(&img, w, h, ColorSpace::RGBA))
+        if let Some(icc) = decoder.icc() {
+            image.metadata_mut().set_icc_chunk(icc);
+        }
+
+        #[cfg(feature = "metadata")]
+        if let Some(exif) = decoder.exif() {
+            image
+                .metadata_mut()
+                .parse_raw_exif(exif.strip_prefix(b"Exif\0\0").unwrap_or(&exif));
+        }
+
+        Ok(image)
     }
 
     fn dimensions(&self) -> Option<(usize, usize)> {
-        self.dimensions
+        Some((self.info.width, self.info.height))
     }
 
     fn out_colorspace(&self) -> ColorSpace {
-        ColorSpace::RGBA
+        self.colorspace()
     }
 
     fn name(&self) -> &'static str {
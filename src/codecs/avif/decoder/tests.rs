@@ -1,4 +1,8 @@
-use std::fs::File;
+use std::{fs::File, io::Cursor};
+
+use zune_image::traits::EncoderTrait;
+
+use crate::{codecs::avif::AvifEncoder, test_utils::*};
 
 use super::*;
 
@@ -11,5 +15,52 @@ fn decode() {
     let img = Image::from_decoder(decoder).unwrap();
 
     assert_eq!(img.dimensions(), (48, 80));
-    assert_eq!(img.colorspace(), ColorSpace::RGBA);
+    assert_eq!(img.colorspace(), ColorSpace::RGB);
+    assert_eq!(img.depth(), BitDepth::Eight);
+}
+
+#[test]
+fn decode_high_bit_depth() {
+    let image = create_test_image_u16(48, 80, ColorSpace::RGBA);
+
+    let mut buf = Cursor::new(vec![]);
+    AvifEncoder::new().encode(&image, &mut buf).unwrap();
+    buf.set_position(0);
+
+    let decoder = AvifDecoder::try_new(buf).unwrap();
+
+    let img = Image::from_decoder(decoder).unwrap();
+
+    assert_eq!(img.depth(), BitDepth::Sixteen);
+}
+
+#[test]
+fn decode_animated() {
+    let image = create_test_image_animated(48, 80, ColorSpace::RGB);
+
+    let mut buf = Cursor::new(vec![]);
+    AvifEncoder::new().encode(&image, &mut buf).unwrap();
+    buf.set_position(0);
+
+    let decoder = AvifDecoder::try_new(buf).unwrap();
+
+    let img = Image::from_decoder(decoder).unwrap();
+
+    assert_eq!(img.frames_ref().len(), 6);
+}
+
+#[test]
+fn info_without_decoding() {
+    let image = create_test_image_animated(48, 80, ColorSpace::RGB);
+
+    let mut buf = Cursor::new(vec![]);
+    AvifEncoder::new().encode(&image, &mut buf).unwrap();
+    buf.set_position(0);
+
+    let decoder = AvifDecoder::try_new(buf).unwrap();
+    let info = decoder.info();
+
+    assert_eq!(decoder.dimensions(), Some((48, 80)));
+    assert_eq!(info.frame_count, 6);
+    assert!(!info.has_alpha);
 }
@@ -1,4 +1,5 @@
-use ravif::Img;
+use rav1e::color::PixelRange;
+use ravif::{Img, MatrixCoefficients};
 use rgb::FromSlice;
 use zune_core::{
     bit_depth::BitDepth,
@@ -8,11 +9,23 @@ use zune_core::{
 use zune_image::{
     codecs::ImageFormat,
     errors::{ImageErrors, ImgEncodeErrors},
+    frame::Frame,
     image::Image,
     traits::EncoderTrait,
 };
 
+use super::sys;
+use crate::{
+    codecs::{frame_duration_ms, MetadataBlocks},
+    options::{check_range, OptionsError, Validate},
+};
+
 /// Advanced options for AVIF encoding
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct AvifOptions {
     /// Quality `1..=100`
     pub quality: f32,
@@ -26,9 +39,78 @@ pub struct AvifOptions {
     /// Changes how color channels are stored in the image.
     ///
     /// Note that this is only internal detail for the AVIF file, and doesn't change color space of inputs to encode functions.
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::options::remote::avif_color_space")
+    )]
     pub color_space: ravif::ColorSpace,
     /// Configure handling of color channels in transparent images
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::options::remote::avif_alpha_mode")
+    )]
     pub alpha_color_mode: ravif::AlphaColorMode,
+    /// Bit depth of the AVIF file being written
+    ///
+    /// Leave as `None` to pick the depth based on the input image:
+    /// 8-bit inputs are encoded in the depth chosen by `ravif`, 16-bit and float inputs are encoded in 10-bit.
+    /// 12-bit is not written by [`AvifBackend::Ravif`].
+    pub bit_depth: Option<AvifBitDepth>,
+    /// Library used to encode still images
+    pub backend: AvifBackend,
+    /// Number of times animation is played, `0` means infinite
+    ///
+    /// Only used for animated images.
+    pub loop_count: u32,
+    /// Raw XMP packet written along with ICC profile and EXIF of the image
+    ///
+    /// Metadata is not written by [`AvifBackend::Ravif`].
+    pub xmp: Option<Vec<u8>>,
+}
+
+/// Bit depth of the encoded AVIF file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum AvifBitDepth {
+    /// 8 bits per sample
+    Eight,
+    /// 10 bits per sample
+    Ten,
+    /// 12 bits per sample
+    Twelve,
+}
+
+/// Library which encodes the AVIF file
+///
+/// Animated images are always encoded with libavif, `ravif` doesn't write image sequences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum AvifBackend {
+    /// `ravif`, switching to libavif for images with metadata or 12-bit depth
+    #[default]
+    Auto,
+    /// `ravif` with rav1e, writes 8 and 10-bit images without metadata
+    Ravif,
+    /// libavif with aom, writes 8, 10 and 12-bit images along with ICC profile, EXIF and XMP
+    Libavif,
+}
+
+impl AvifBitDepth {
+    fn bits(self) -> u32 {
+        match self {
+            AvifBitDepth::Eight => 8,
+            AvifBitDepth::Ten => 10,
+            AvifBitDepth::Twelve => 12,
+        }
+    }
 }
 
 /// A AVIF encoder
@@ -45,7 +127,23 @@ impl Default for AvifOptions {
             speed: 6,
             color_space: ravif::ColorSpace::YCbCr,
             alpha_color_mode: ravif::AlphaColorMode::UnassociatedClean,
+            bit_depth: None,
+            backend: AvifBackend::Auto,
+            loop_count: 0,
+            xmp: None,
+        }
+    }
+}
+
+impl Validate for AvifOptions {
+    fn validate(&self) -> Result<(), OptionsError> {
+        check_range("quality", self.quality, 1.0..=100.0)?;
+
+        if let Some(alpha_quality) = self.alpha_quality {
+            check_range("alpha_quality", alpha_quality, 1.0..=100.0)?;
         }
+
+        check_range("speed", self.speed, 1..=10)
     }
 }
 
@@ -59,6 +157,13 @@ impl AvifEncoder {
     pub fn new_with_options(options: AvifOptions) -> AvifEncoder {
         AvifEncoder { options }
     }
+
+    /// Create a new encoder with specified options, rejecting out of range values
+    pub fn try_new_with_options(options: AvifOptions) -> Result<AvifEncoder, OptionsError> {
+        options.validate()?;
+
+        Ok(AvifEncoder::new_with_options(options))
+    }
 }
 
 impl EncoderTrait for AvifEncoder {
@@ -71,64 +176,292 @@ impl EncoderTrait for AvifEncoder {
         image: &Image,
         sink: T,
     ) -> Result<usize, ImageErrors> {
-        let (width, height) = image.dimensions();
-        let data = &image.flatten_to_u8()[0];
+        let bit_depth = self.options.bit_depth.unwrap_or(match image.depth() {
+            BitDepth::Eight => AvifBitDepth::Eight,
+            _ => AvifBitDepth::Ten,
+        });
+
+        match image.colorspace() {
+            ColorSpace::RGB | ColorSpace::RGBA => {}
+            cs => {
+                return Err(ImageErrors::EncodeErrors(
+                    ImgEncodeErrors::UnsupportedColorspace(cs, self.supported_colorspaces()),
+                ))
+            }
+        }
 
         let mut writer = ZWriter::new(sink);
 
-        let encoder = ravif::Encoder::new()
+        let metadata = MetadataBlocks::from_image(image, self.options.xmp.as_deref());
+
+        let avif_file = match (self.options.backend, bit_depth) {
+            _ if image.is_animated() => self.encode_animated(image, bit_depth, &metadata)?,
+            (AvifBackend::Libavif, _) => self.encode_libavif(image, bit_depth, &metadata)?,
+            (AvifBackend::Auto, AvifBitDepth::Twelve) => {
+                self.encode_libavif(image, bit_depth, &metadata)?
+            }
+            (AvifBackend::Auto, _) if !metadata.is_empty() => {
+                self.encode_libavif(image, bit_depth, &metadata)?
+            }
+            (AvifBackend::Auto, _) => self.encode_ravif(image, bit_depth)?,
+            (AvifBackend::Ravif, AvifBitDepth::Twelve) => {
+                return Err(ImageErrors::EncodeErrors(ImgEncodeErrors::GenericStatic(
+                    "12-bit AVIF is written only by libavif backend",
+                )))
+            }
+            (AvifBackend::Ravif, _) if !metadata.is_empty() => {
+                log::warn!("ravif doesn't write metadata, use libavif backend to keep it");
+
+                self.encode_ravif(image, bit_depth)?
+            }
+            (AvifBackend::Ravif, _) => self.encode_ravif(image, bit_depth)?,
+        };
+
+        writer.write(&avif_file).map_err(|e| {
+            ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
+        })?;
+
+        Ok(writer.bytes_written())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[ColorSpace::RGB, ColorSpace::RGBA]
+    }
+
+    /// zune-image has no AVIF format, [`DynEncoder::format`](crate::codecs::DynEncoder::format)
+    /// reports [`Format::Avif`](crate::Format::Avif)
+    fn format(&self) -> ImageFormat {
+        ImageFormat::Unknown
+    }
+
+    fn supported_bit_depth(&self) -> &'static [BitDepth] {
+        &[BitDepth::Eight, BitDepth::Sixteen, BitDepth::Float32]
+    }
+
+    fn default_depth(&self, depth: BitDepth) -> BitDepth {
+        match depth {
+            BitDepth::Sixteen | BitDepth::Float32 => depth,
+            _ => BitDepth::Eight,
+        }
+    }
+
+    fn supports_animated_images(&self) -> bool {
+        true
+    }
+}
+
+impl AvifEncoder {
+    fn ravif_encoder(&self) -> ravif::Encoder {
+        ravif::Encoder::new()
             .with_quality(self.options.quality)
             .with_alpha_quality(self.options.alpha_quality.unwrap_or(self.options.quality))
             .with_speed(self.options.speed)
             .with_internal_color_space(self.options.color_space)
-            .with_alpha_color_mode(self.options.alpha_color_mode);
+            .with_alpha_color_mode(self.options.alpha_color_mode)
+    }
 
-        match image.colorspace() {
-            ColorSpace::RGB => {
-                let img = Img::new(data.as_slice().as_rgb(), width, height);
-                let result = encoder
-                    .encode_rgb(img)
-                    .map_err(|e| ImgEncodeErrors::ImageEncodeErrors(e.to_string()))?;
+    fn encode_ravif(&self, image: &Image, bit_depth: AvifBitDepth) -> Result<Vec<u8>, ImageErrors> {
+        match bit_depth {
+            AvifBitDepth::Eight if self.options.bit_depth.is_none() => self.encode_u8(image, None),
+            AvifBitDepth::Eight => self.encode_u8(image, Some(8)),
+            _ => self.encode_10_bit(image),
+        }
+    }
+
+    fn encode_u8(&self, image: &Image, depth: Option<u8>) -> Result<Vec<u8>, ImageErrors> {
+        let (width, height) = image.dimensions();
+        let data = &image.flatten_to_u8()[0];
 
-                writer.write(&result.avif_file).map_err(|e| {
-                    ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
-                })?;
+        let encoder = self.ravif_encoder().with_depth(depth);
 
-                Ok(writer.bytes_written())
+        let result = match image.colorspace() {
+            ColorSpace::RGB => {
+                encoder.encode_rgb(Img::new(data.as_slice().as_rgb(), width, height))
             }
-            ColorSpace::RGBA => {
-                let img = Img::new(data.as_slice().as_rgba(), width, height);
-                let result = encoder
-                    .encode_rgba(img)
-                    .map_err(|e| ImgEncodeErrors::ImageEncodeErrors(e.to_string()))?;
+            _ => encoder.encode_rgba(Img::new(data.as_slice().as_rgba(), width, height)),
+        }
+        .map_err(|e| ImgEncodeErrors::ImageEncodeErrors(e.to_string()))?;
+
+        Ok(result.avif_file)
+    }
+
+    fn encode_10_bit(&self, image: &Image) -> Result<Vec<u8>, ImageErrors> {
+        let (width, height) = image.dimensions();
+        let has_alpha = image.colorspace() == ColorSpace::RGBA;
+        let channels = image.colorspace().num_components();
+
+        let data = self.flatten_to_u16(image, &image.frames_ref()[0]);
+
+        let (matrix_coefficients, to_planes): (_, fn([f32; 3]) -> [f32; 3]) =
+            match self.options.color_space {
+                ravif::ColorSpace::YCbCr => (MatrixCoefficients::BT601, rgb_to_ycbcr),
+                ravif::ColorSpace::RGB => (MatrixCoefficients::Identity, |[r, g, b]| [g, b, r]),
+            };
+
+        let planes = data.chunks_exact(channels).map(|px| {
+            to_planes([px[0], px[1], px[2]].map(|c| c as f32 / 65535.))
+                .map(|c| (c * 1023.).round().clamp(0., 1023.) as u16)
+        });
+
+        let result = if has_alpha {
+            let alpha = data.chunks_exact(channels).map(|px| px[3] >> 6);
+
+            self.ravif_encoder().encode_raw_planes_10_bit(
+                width,
+                height,
+                planes,
+                Some(alpha),
+                PixelRange::Full,
+                matrix_coefficients,
+            )
+        } else {
+            self.ravif_encoder().encode_raw_planes_10_bit(
+                width,
+                height,
+                planes,
+                None::<[u16; 0]>,
+                PixelRange::Full,
+                matrix_coefficients,
+            )
+        }
+        .map_err(|e| ImgEncodeErrors::ImageEncodeErrors(e.to_string()))?;
 
-                writer.write(&result.avif_file).map_err(|e| {
-                    ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
-                })?;
+        Ok(result.avif_file)
+    }
+
+    fn encode_libavif(
+        &self,
+        image: &Image,
+        bit_depth: AvifBitDepth,
+        metadata: &MetadataBlocks,
+    ) -> Result<Vec<u8>, ImageErrors> {
+        let mut avif_image = self.avif_image(image, &image.frames_ref()[0], bit_depth.bits())?;
+        avif_image.set_metadata(metadata)?;
+
+        self.libavif_encoder()?.write(&avif_image)
+    }
+
+    fn encode_animated(
+        &self,
+        image: &Image,
+        bit_depth: AvifBitDepth,
+        metadata: &MetadataBlocks,
+    ) -> Result<Vec<u8>, ImageErrors> {
+        let mut encoder = self.libavif_encoder()?;
 
-                Ok(writer.bytes_written())
+        encoder.set_loop_count(self.options.loop_count);
+
+        for (idx, frame) in image.frames_ref().iter().enumerate() {
+            let mut avif_image = self.avif_image(image, frame, bit_depth.bits())?;
+
+            // metadata of the sequence is taken from the first frame
+            if idx == 0 {
+                avif_image.set_metadata(metadata)?;
             }
-            cs => Err(ImageErrors::EncodeErrors(
-                ImgEncodeErrors::UnsupportedColorspace(cs, self.supported_colorspaces()),
-            )),
+
+            encoder.add_frame(&avif_image, frame_duration_ms(frame))?;
         }
+
+        encoder.finish()
     }
 
-    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
-        &[ColorSpace::RGB, ColorSpace::RGBA]
+    fn libavif_encoder(&self) -> Result<sys::AvifEncoder, ImageErrors> {
+        sys::AvifEncoder::new(
+            self.options.quality,
+            self.options.alpha_quality.unwrap_or(self.options.quality),
+            self.options.speed,
+        )
     }
 
-    fn format(&self) -> ImageFormat {
-        ImageFormat::Unknown
+    fn avif_image(
+        &self,
+        image: &Image,
+        frame: &Frame,
+        depth: u32,
+    ) -> Result<sys::AvifImage, ImageErrors> {
+        let (width, height) = image.dimensions();
+
+        let has_alpha = image.colorspace() == ColorSpace::RGBA;
+
+        sys::AvifImage::from_rgb16(
+            &self.flatten_to_u16(image, frame),
+            width,
+            height,
+            has_alpha,
+            depth,
+            matches!(self.options.color_space, ravif::ColorSpace::RGB),
+            has_alpha && self.options.alpha_color_mode == ravif::AlphaColorMode::Premultiplied,
+        )
     }
 
-    fn supported_bit_depth(&self) -> &'static [BitDepth] {
-        &[BitDepth::Eight]
+    /// Flattens `frame` into full range 16-bit samples,
+    /// premultiplying or cleaning them as requested by alpha color mode
+    fn flatten_to_u16(&self, image: &Image, frame: &Frame) -> Vec<u16> {
+        let colorspace = image.colorspace();
+
+        let mut data = match image.depth() {
+            BitDepth::Eight => frame
+                .flatten::<u8>(colorspace)
+                .into_iter()
+                .map(|v| u16::from(v) * 257)
+                .collect(),
+            BitDepth::Float32 => frame
+                .flatten::<f32>(colorspace)
+                .into_iter()
+                .map(|v| (v.clamp(0., 1.) * 65535.).round() as u16)
+                .collect(),
+            _ => frame.flatten::<u16>(colorspace),
+        };
+
+        if colorspace != ColorSpace::RGBA {
+            return data;
+        }
+
+        match self.options.alpha_color_mode {
+            ravif::AlphaColorMode::Premultiplied => data.chunks_exact_mut(4).for_each(|px| {
+                let a = u32::from(px[3]);
+                px[..3]
+                    .iter_mut()
+                    .for_each(|c| *c = (u32::from(*c) * a / 65535) as u16);
+            }),
+            ravif::AlphaColorMode::UnassociatedClean => clean_alpha(&mut data),
+            // colors of transparent pixels are kept as is
+            _ => {}
+        }
+
+        data
     }
+}
+
+/// Replaces colors of fully transparent RGBA pixels with the alpha weighted average color,
+/// so invisible noise doesn't cost bits
+fn clean_alpha(data: &mut [u16]) {
+    let mut sum = [0u64; 3];
+    let mut weight = 0u64;
 
-    fn default_depth(&self, _depth: BitDepth) -> BitDepth {
-        BitDepth::Eight
+    for px in data.chunks_exact(4) {
+        let a = u64::from(px[3]);
+
+        sum.iter_mut()
+            .zip(px)
+            .for_each(|(sum, &c)| *sum += u64::from(c) * a);
+        weight += a;
     }
+
+    let average = sum.map(|sum| sum.checked_div(weight).unwrap_or(0) as u16);
+
+    data.chunks_exact_mut(4)
+        .filter(|px| px[3] == 0)
+        .for_each(|px| px[..3].copy_from_slice(&average));
+}
+
+/// Full range BT.601 conversion
+fn rgb_to_ycbcr([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let y = 0.299 * r + 0.587 * g + 0.114 * b;
+    let cb = (b - y) / 1.772 + 0.5;
+    let cr = (r - y) / 1.402 + 0.5;
+
+    [y, cb, cr]
 }
 
 #[cfg(test)]
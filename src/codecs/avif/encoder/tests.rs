@@ -156,3 +156,183 @@ fn encode_animated() {
 
     assert!(result.is_ok());
 }
+
+#[test]
+fn encode_bit_depths() {
+    for (backend, bit_depth) in [
+        (AvifBackend::Ravif, AvifBitDepth::Eight),
+        (AvifBackend::Ravif, AvifBitDepth::Ten),
+        (AvifBackend::Libavif, AvifBitDepth::Eight),
+        (AvifBackend::Libavif, AvifBitDepth::Ten),
+        (AvifBackend::Libavif, AvifBitDepth::Twelve),
+        (AvifBackend::Auto, AvifBitDepth::Twelve),
+    ] {
+        let image = create_test_image_u16(200, 200, ColorSpace::RGBA);
+        let mut encoder = AvifEncoder::new_with_options(AvifOptions {
+            bit_depth: Some(bit_depth),
+            backend,
+            ..Default::default()
+        });
+
+        let buf = Cursor::new(vec![]);
+
+        let result = encoder.encode(&image, buf);
+        dbg!(&result);
+
+        assert!(result.is_ok());
+    }
+}
+
+#[test]
+fn encode_twelve_bit_ravif() {
+    let image = create_test_image_u16(200, 200, ColorSpace::RGBA);
+    let mut encoder = AvifEncoder::new_with_options(AvifOptions {
+        bit_depth: Some(AvifBitDepth::Twelve),
+        backend: AvifBackend::Ravif,
+        ..Default::default()
+    });
+
+    assert!(encoder.encode(&image, Cursor::new(vec![])).is_err());
+}
+
+#[test]
+fn encode_metadata() {
+    use crate::codecs::avif::AvifDecoder;
+    use zune_image::traits::DecoderTrait;
+
+    let icc = std::fs::read("tests/files/icc/tinysrgb.icc").unwrap();
+
+    let mut image = create_test_image_u8(200, 200, ColorSpace::RGB);
+    image.metadata_mut().set_icc_chunk(icc.clone());
+
+    let mut encoder = AvifEncoder::new_with_options(AvifOptions {
+        backend: AvifBackend::Libavif,
+        xmp: Some(b"<x:xmpmeta/>".to_vec()),
+        ..Default::default()
+    });
+
+    let mut buf = Cursor::new(vec![]);
+    encoder.encode(&image, &mut buf).unwrap();
+    buf.set_position(0);
+
+    let mut decoder = AvifDecoder::try_new(buf).unwrap();
+    let decoded = decoder.decode().unwrap();
+
+    assert_eq!(decoded.metadata().icc_chunk(), Some(&icc));
+    assert_eq!(decoder.xmp(), Some(&b"<x:xmpmeta/>"[..]));
+}
+
+#[test]
+fn encode_metadata_default() {
+    use crate::codecs::avif::AvifDecoder;
+    use zune_image::traits::DecoderTrait;
+
+    let icc = std::fs::read("tests/files/icc/tinysrgb.icc").unwrap();
+
+    let mut image = create_test_image_u8(200, 200, ColorSpace::RGB);
+    image.metadata_mut().set_icc_chunk(icc.clone());
+
+    #[cfg(feature = "metadata")]
+    {
+        use exif::{experimental::Writer, Field, In, Tag, Value};
+
+        let field = Field {
+            tag: Tag::Artist,
+            ifd_num: In::PRIMARY,
+            value: Value::Ascii(vec![b"rimage".to_vec()]),
+        };
+
+        let mut writer = Writer::new();
+        writer.push_field(&field);
+
+        let mut exif = Cursor::new(vec![]);
+        writer.write(&mut exif, false).unwrap();
+
+        image.metadata_mut().parse_raw_exif(&exif.into_inner());
+    }
+
+    let mut encoder = AvifEncoder::new_with_options(AvifOptions {
+        xmp: Some(b"<x:xmpmeta/>".to_vec()),
+        ..Default::default()
+    });
+
+    let mut buf = Cursor::new(vec![]);
+    encoder.encode(&image, &mut buf).unwrap();
+
+    let data = buf.into_inner();
+    let contains = |name: &[u8]| data.windows(name.len()).any(|w| w == name);
+
+    // ICC profile box and item types of EXIF and XMP
+    assert!(contains(b"colr"));
+    assert!(contains(b"mime"));
+    #[cfg(feature = "metadata")]
+    assert!(contains(b"Exif"));
+
+    let mut decoder = AvifDecoder::try_new(Cursor::new(&data)).unwrap();
+    let decoded = decoder.decode().unwrap();
+
+    assert_eq!(decoded.metadata().icc_chunk(), Some(&icc));
+    assert_eq!(decoder.xmp(), Some(&b"<x:xmpmeta/>"[..]));
+}
+
+#[test]
+fn encode_premultiplied() {
+    use crate::codecs::avif::AvifDecoder;
+    use zune_image::traits::DecoderTrait;
+
+    let image = Image::from_fn(16, 16, ColorSpace::RGBA, |_, _, px: &mut [u8; 4]| {
+        *px = [200, 100, 50, 128];
+    });
+
+    let mut encoder = AvifEncoder::new_with_options(AvifOptions {
+        quality: 100.,
+        alpha_color_mode: ravif::AlphaColorMode::Premultiplied,
+        backend: AvifBackend::Libavif,
+        ..Default::default()
+    });
+
+    let mut buf = Cursor::new(vec![]);
+    encoder.encode(&image, &mut buf).unwrap();
+    buf.set_position(0);
+
+    // colors are restored on decoding instead of staying darkened by alpha
+    let decoded = AvifDecoder::try_new(buf).unwrap().decode().unwrap();
+    let data = &decoded.flatten_to_u8()[0];
+
+    assert!(data[0].abs_diff(200) < 8, "{:?}", &data[..4]);
+}
+
+#[test]
+fn encode_clean_alpha_ten_bit() {
+    use crate::codecs::avif::AvifDecoder;
+    use zune_image::traits::DecoderTrait;
+
+    // left half is transparent noise
+    let image = Image::from_fn(16, 16, ColorSpace::RGBA, |x, y, px: &mut [u16; 4]| {
+        *px = match x < 8 {
+            true => [(x * 8000) as u16, (y * 4000) as u16, 65535, 0],
+            false => [40000, 20000, 10000, 65535],
+        };
+    });
+
+    let mut encoder = AvifEncoder::new_with_options(AvifOptions {
+        quality: 100.,
+        alpha_color_mode: ravif::AlphaColorMode::UnassociatedClean,
+        bit_depth: Some(AvifBitDepth::Ten),
+        backend: AvifBackend::Ravif,
+        ..Default::default()
+    });
+
+    let mut buf = Cursor::new(vec![]);
+    encoder.encode(&image, &mut buf).unwrap();
+    buf.set_position(0);
+
+    let decoded = AvifDecoder::try_new(buf).unwrap().decode().unwrap();
+    let data = &decoded.flatten_to_u8()[0];
+
+    // transparent pixels take the color of the visible ones
+    for px in data.chunks_exact(4).step_by(16).take(16) {
+        assert!(px[0].abs_diff(156) < 8, "{px:?}");
+        assert!(px[2].abs_diff(39) < 8, "{px:?}");
+    }
+}
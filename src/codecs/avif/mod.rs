@@ -1,5 +1,6 @@
 mod decoder;
 mod encoder;
+mod sys;
 
 pub use decoder::*;
 pub use encoder::*;
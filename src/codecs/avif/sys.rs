@@ -0,0 +1,463 @@
+//! Thin safe wrappers around `libavif-sys` used where `ravif` and `libavif` fall short.
+
+use std::{
+    io::{Read, Seek, SeekFrom},
+    marker::PhantomData,
+    panic::AssertUnwindSafe,
+    ptr::NonNull,
+};
+
+use libavif_sys as sys;
+use zune_image::errors::{ImageErrors, ImgEncodeErrors};
+
+use crate::codecs::MetadataBlocks;
+
+fn check(result: sys::avifResult) -> Result<(), String> {
+    if result == sys::AVIF_RESULT_OK {
+        Ok(())
+    } else {
+        let msg = unsafe { std::ffi::CStr::from_ptr(sys::avifResultToString(result)) };
+
+        Err(msg.to_string_lossy().into_owned())
+    }
+}
+
+fn encode_error(e: String) -> ImageErrors {
+    ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(e))
+}
+
+fn rw_data(data: &sys::avifRWData) -> Option<Vec<u8>> {
+    if data.data.is_null() || data.size == 0 {
+        None
+    } else {
+        Some(unsafe { std::slice::from_raw_parts(data.data, data.size) }.to_vec())
+    }
+}
+
+/// Owned `avifImage`
+pub(crate) struct AvifImage(NonNull<sys::avifImage>);
+
+impl AvifImage {
+    /// Convert interleaved 16-bit RGB(A) pixels into a YUV 4:4:4 image with `depth` bits per sample
+    ///
+    /// `premultiplied` marks color channels of `data` as already multiplied by alpha.
+    pub(crate) fn from_rgb16(
+        data: &[u16],
+        width: usize,
+        height: usize,
+        has_alpha: bool,
+        depth: u32,
+        identity_matrix: bool,
+        premultiplied: bool,
+    ) -> Result<Self, ImageErrors> {
+        let image = unsafe {
+            sys::avifImageCreate(
+                width as u32,
+                height as u32,
+                depth,
+                sys::AVIF_PIXEL_FORMAT_YUV444,
+            )
+        };
+        let image = AvifImage(
+            NonNull::new(image).ok_or_else(|| encode_error("Unable to allocate image".into()))?,
+        );
+
+        unsafe {
+            let img = &mut *image.0.as_ptr();
+
+            img.yuvRange = sys::AVIF_RANGE_FULL;
+            img.colorPrimaries = sys::AVIF_COLOR_PRIMARIES_BT709 as _;
+            img.transferCharacteristics = sys::AVIF_TRANSFER_CHARACTERISTICS_SRGB as _;
+            img.matrixCoefficients = if identity_matrix {
+                sys::AVIF_MATRIX_COEFFICIENTS_IDENTITY as _
+            } else {
+                sys::AVIF_MATRIX_COEFFICIENTS_BT601 as _
+            };
+            img.alphaPremultiplied = premultiplied as _;
+
+            let mut rgb: sys::avifRGBImage = std::mem::zeroed();
+            sys::avifRGBImageSetDefaults(&mut rgb, img);
+
+            let channels = if has_alpha { 4 } else { 3 };
+
+            rgb.depth = 16;
+            rgb.format = if has_alpha {
+                sys::AVIF_RGB_FORMAT_RGBA
+            } else {
+                sys::AVIF_RGB_FORMAT_RGB
+            };
+            rgb.pixels = data.as_ptr() as *mut u8;
+            rgb.rowBytes = (width * channels * 2) as u32;
+            rgb.alphaPremultiplied = premultiplied as _;
+
+            check(sys::avifImageRGBToYUV(img, &rgb)).map_err(encode_error)?;
+        }
+
+        Ok(image)
+    }
+
+    /// Attach ICC profile, Exif and XMP metadata to the image
+    pub(crate) fn set_metadata(&mut self, metadata: &MetadataBlocks) -> Result<(), ImageErrors> {
+        let img = self.0.as_ptr();
+
+        unsafe {
+            if let Some(icc) = &metadata.icc {
+                check(sys::avifImageSetProfileICC(img, icc.as_ptr(), icc.len()))
+                    .map_err(encode_error)?;
+            }
+
+            if let Some(exif) = &metadata.exif {
+                check(sys::avifImageSetMetadataExif(
+                    img,
+                    exif.as_ptr(),
+                    exif.len(),
+                ))
+                .map_err(encode_error)?;
+            }
+
+            if let Some(xmp) = &metadata.xmp {
+                check(sys::avifImageSetMetadataXMP(img, xmp.as_ptr(), xmp.len()))
+                    .map_err(encode_error)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn as_ptr(&self) -> *const sys::avifImage {
+        self.0.as_ptr()
+    }
+}
+
+impl Drop for AvifImage {
+    fn drop(&mut self) {
+        unsafe { sys::avifImageDestroy(self.0.as_ptr()) }
+    }
+}
+
+/// Owned `avifEncoder`
+pub(crate) struct AvifEncoder(NonNull<sys::avifEncoder>);
+
+impl AvifEncoder {
+    /// Create a new encoder
+    ///
+    /// # Arguments
+    /// - quality: color quality `1..=100`
+    /// - alpha_quality: alpha quality `1..=100`
+    /// - speed: encoder speed `1..=10`
+    pub(crate) fn new(quality: f32, alpha_quality: f32, speed: u8) -> Result<Self, ImageErrors> {
+        let encoder = unsafe { sys::avifEncoderCreate() };
+        let encoder = AvifEncoder(
+            NonNull::new(encoder)
+                .ok_or_else(|| encode_error("Unable to allocate encoder".into()))?,
+        );
+
+        unsafe {
+            let enc = &mut *encoder.0.as_ptr();
+
+            enc.quality = quality.round() as i32;
+            enc.qualityAlpha = alpha_quality.round() as i32;
+            enc.speed = speed as i32;
+            enc.maxThreads = std::thread::available_parallelism()
+                .map(|n| n.get() as i32)
+                .unwrap_or(1);
+        }
+
+        Ok(encoder)
+    }
+
+    /// Set number of times animation is played, `0` means infinite
+    pub(crate) fn set_loop_count(&mut self, loop_count: u32) {
+        unsafe {
+            (*self.0.as_ptr()).repetitionCount = match loop_count {
+                0 => sys::AVIF_REPETITION_COUNT_INFINITE,
+                n => n as i32 - 1,
+            };
+        }
+    }
+
+    /// Add a frame of image sequence that is displayed for `duration_ms` milliseconds
+    pub(crate) fn add_frame(
+        &mut self,
+        image: &AvifImage,
+        duration_ms: u32,
+    ) -> Result<(), ImageErrors> {
+        unsafe {
+            (*self.0.as_ptr()).timescale = 1000;
+
+            check(sys::avifEncoderAddImage(
+                self.0.as_ptr(),
+                image.as_ptr(),
+                u64::from(duration_ms),
+                sys::AVIF_ADD_IMAGE_FLAG_NONE as _,
+            ))
+            .map_err(encode_error)
+        }
+    }
+
+    /// Finish encoding of frames added with [`AvifEncoder::add_frame`]
+    pub(crate) fn finish(&mut self) -> Result<Vec<u8>, ImageErrors> {
+        self.output(|encoder, output| unsafe { sys::avifEncoderFinish(encoder, output) })
+    }
+
+    /// Encode a single still image
+    pub(crate) fn write(&mut self, image: &AvifImage) -> Result<Vec<u8>, ImageErrors> {
+        self.output(|encoder, output| unsafe {
+            sys::avifEncoderWrite(encoder, image.as_ptr(), output)
+        })
+    }
+
+    fn output(
+        &mut self,
+        f: impl FnOnce(*mut sys::avifEncoder, *mut sys::avifRWData) -> sys::avifResult,
+    ) -> Result<Vec<u8>, ImageErrors> {
+        unsafe {
+            let mut output: sys::avifRWData = std::mem::zeroed();
+
+            let result = check(f(self.0.as_ptr(), &mut output));
+
+            let data = if result.is_ok() {
+                std::slice::from_raw_parts(output.data, output.size).to_vec()
+            } else {
+                vec![]
+            };
+
+            sys::avifRWDataFree(&mut output);
+
+            result.map_err(encode_error).map(|_| data)
+        }
+    }
+}
+
+impl Drop for AvifEncoder {
+    fn drop(&mut self) {
+        unsafe { sys::avifEncoderDestroy(self.0.as_ptr()) }
+    }
+}
+
+/// Owned `avifDecoder` reading from borrowed memory or stream
+pub(crate) struct AvifDecoder<'a> {
+    inner: NonNull<sys::avifDecoder>,
+    phantom: PhantomData<&'a [u8]>,
+}
+
+/// Header information of the AVIF file
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct AvifInfo {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) depth: u32,
+    pub(crate) has_alpha: bool,
+    pub(crate) frame_count: usize,
+}
+
+impl<'a> AvifDecoder<'a> {
+    /// Create a decoder and parse headers of the `data`
+    pub(crate) fn parse(data: &'a [u8]) -> Result<Self, ImageErrors> {
+        let decoder = Self::create()?;
+
+        unsafe {
+            check(sys::avifDecoderSetIOMemory(
+                decoder.inner.as_ptr(),
+                data.as_ptr(),
+                data.len(),
+            ))
+            .map_err(ImageErrors::ImageDecodeErrors)?;
+        }
+
+        decoder.parse_headers()
+    }
+
+    /// Create a decoder and parse headers read from the `source`
+    ///
+    /// Only the boxes libavif asks for are read, image data is skipped with seeking.
+    pub(crate) fn parse_stream<R: Read + Seek + 'a>(mut source: R) -> Result<Self, ImageErrors> {
+        let size = source.seek(SeekFrom::End(0))?;
+
+        let decoder = Self::create()?;
+
+        let stream = Box::new(StreamIO {
+            io: sys::avifIO {
+                destroy: Some(stream_destroy::<R>),
+                read: Some(stream_read::<R>),
+                write: None,
+                sizeHint: size,
+                persistent: 0,
+                data: std::ptr::null_mut(),
+            },
+            source,
+            buffer: vec![],
+        });
+
+        // the decoder takes ownership of the stream and destroys it along with itself
+        unsafe {
+            sys::avifDecoderSetIO(
+                decoder.inner.as_ptr(),
+                Box::into_raw(stream) as *mut sys::avifIO,
+            );
+        }
+
+        decoder.parse_headers()
+    }
+
+    fn create() -> Result<Self, ImageErrors> {
+        let decoder = unsafe { sys::avifDecoderCreate() };
+        let decoder = AvifDecoder {
+            inner: NonNull::new(decoder).ok_or_else(|| {
+                ImageErrors::ImageDecodeErrors("Unable to allocate decoder".into())
+            })?,
+            phantom: PhantomData,
+        };
+
+        unsafe {
+            (*decoder.inner.as_ptr()).maxThreads = std::thread::available_parallelism()
+                .map(|n| n.get() as i32)
+                .unwrap_or(1);
+        }
+
+        Ok(decoder)
+    }
+
+    fn parse_headers(self) -> Result<Self, ImageErrors> {
+        unsafe {
+            check(sys::avifDecoderParse(self.inner.as_ptr()))
+                .map_err(ImageErrors::ImageDecodeErrors)?;
+        }
+
+        Ok(self)
+    }
+
+    pub(crate) fn info(&self) -> AvifInfo {
+        unsafe {
+            let dec = &*self.inner.as_ptr();
+            let image = &*dec.image;
+
+            AvifInfo {
+                width: image.width as usize,
+                height: image.height as usize,
+                depth: image.depth,
+                has_alpha: dec.alphaPresent != 0,
+                frame_count: dec.imageCount.max(1) as usize,
+            }
+        }
+    }
+
+    /// Number of times animation is played, `0` means infinite
+    pub(crate) fn loop_count(&self) -> u32 {
+        match unsafe { (*self.inner.as_ptr()).repetitionCount } {
+            n if n < 0 => 0,
+            n => n as u32 + 1,
+        }
+    }
+
+    pub(crate) fn icc(&self) -> Option<Vec<u8>> {
+        unsafe { rw_data(&(*(*self.inner.as_ptr()).image).icc) }
+    }
+
+    pub(crate) fn exif(&self) -> Option<Vec<u8>> {
+        unsafe { rw_data(&(*(*self.inner.as_ptr()).image).exif) }
+    }
+
+    pub(crate) fn xmp(&self) -> Option<Vec<u8>> {
+        unsafe { rw_data(&(*(*self.inner.as_ptr()).image).xmp) }
+    }
+
+    /// Decode next frame into interleaved RGB(A) samples of `T` (`u8` or `u16`)
+    /// along with its duration in milliseconds
+    pub(crate) fn next_frame<T: Copy + Default>(
+        &mut self,
+    ) -> Result<Option<(Vec<T>, u32)>, ImageErrors> {
+        unsafe {
+            let dec = self.inner.as_ptr();
+
+            let result = sys::avifDecoderNextImage(dec);
+
+            if result == sys::AVIF_RESULT_NO_IMAGES_REMAINING {
+                return Ok(None);
+            }
+
+            check(result).map_err(ImageErrors::ImageDecodeErrors)?;
+
+            let AvifInfo {
+                width,
+                height,
+                has_alpha,
+                ..
+            } = self.info();
+            let channels = if has_alpha { 4 } else { 3 };
+
+            let mut data = vec![T::default(); width * height * channels];
+
+            let mut rgb: sys::avifRGBImage = std::mem::zeroed();
+            sys::avifRGBImageSetDefaults(&mut rgb, (*dec).image);
+
+            rgb.depth = (std::mem::size_of::<T>() * 8) as u32;
+            rgb.format = if has_alpha {
+                sys::AVIF_RGB_FORMAT_RGBA
+            } else {
+                sys::AVIF_RGB_FORMAT_RGB
+            };
+            rgb.pixels = data.as_mut_ptr() as *mut u8;
+            rgb.rowBytes = (width * channels * std::mem::size_of::<T>()) as u32;
+
+            check(sys::avifImageYUVToRGB((*dec).image, &mut rgb))
+                .map_err(ImageErrors::ImageDecodeErrors)?;
+
+            let duration = ((*dec).imageTiming.duration * 1000.).round() as u32;
+
+            Ok(Some((data, duration)))
+        }
+    }
+}
+
+impl Drop for AvifDecoder<'_> {
+    fn drop(&mut self) {
+        unsafe { sys::avifDecoderDestroy(self.inner.as_ptr()) }
+    }
+}
+
+/// `avifIO` reading from a seekable stream
+#[repr(C)]
+struct StreamIO<R> {
+    /// Stays the first field, libavif passes a pointer to it back into the callbacks
+    io: sys::avifIO,
+    source: R,
+    /// Data of the last read, libavif keeps using it until the next read
+    buffer: Vec<u8>,
+}
+
+unsafe extern "C" fn stream_read<R: Read + Seek>(
+    io: *mut sys::avifIO,
+    _read_flags: u32,
+    offset: u64,
+    size: usize,
+    out: *mut sys::avifROData,
+) -> sys::avifResult {
+    let stream = &mut *(io as *mut StreamIO<R>);
+
+    let result = std::panic::catch_unwind(AssertUnwindSafe(|| -> std::io::Result<()> {
+        stream.buffer.clear();
+        stream.source.seek(SeekFrom::Start(offset))?;
+        stream
+            .source
+            .by_ref()
+            .take(size as u64)
+            .read_to_end(&mut stream.buffer)?;
+
+        Ok(())
+    }));
+
+    match result {
+        Ok(Ok(())) => {
+            (*out).data = stream.buffer.as_ptr();
+            (*out).size = stream.buffer.len();
+
+            sys::AVIF_RESULT_OK
+        }
+        _ => sys::AVIF_RESULT_IO_ERROR,
+    }
+}
+
+unsafe extern "C" fn stream_destroy<R>(io: *mut sys::avifIO) {
+    drop(Box::from_raw(io as *mut StreamIO<R>));
+}
@@ -0,0 +1,502 @@
+use std::io::Write;
+
+use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
+use zune_image::{errors::ImageErrors, image::Image, traits::EncoderTrait};
+
+use crate::{
+    codecs::IndexedPixels,
+    options::{OptionsError, Validate},
+    Format,
+};
+
+/// Object-safe encoder chosen at runtime
+///
+/// Implemented for every encoder of this crate, encoders of zune-image
+/// can be used through [`ZuneEncoder`].
+pub trait DynEncoder {
+    /// Name of the encoder
+    fn name(&self) -> &'static str;
+
+    /// Format written by the encoder
+    fn format(&self) -> Format;
+
+    /// Preferred file extension of the output
+    fn extension(&self) -> &'static str {
+        self.format().extensions()[0]
+    }
+
+    /// MIME type of the output
+    fn mime_type(&self) -> &'static str {
+        self.format().mime_type()
+    }
+
+    /// Colorspaces accepted without conversion
+    fn supported_colorspaces(&self) -> &'static [ColorSpace];
+
+    /// Bit depths accepted without conversion
+    fn supported_bit_depth(&self) -> &'static [BitDepth];
+
+    /// Bit depth the image of `depth` should be converted to before encoding
+    fn default_depth(&self, depth: BitDepth) -> BitDepth;
+
+    /// Whether all frames of animated images are written
+    fn supports_animated_images(&self) -> bool {
+        false
+    }
+
+    /// Write the image as `indexed` pixels if the encoder supports it
+    ///
+    /// The indexed pixels have to describe the encoded image.
+    fn set_indexed(&mut self, _indexed: IndexedPixels) {}
+
+    /// Whether the file starting with `magic` bytes is re-encoded by [`DynEncoder::transcode`]
+    ///
+    /// Lets callers skip reading the whole file when it will be decoded anyway.
+    fn can_transcode(&self, _magic: &[u8]) -> bool {
+        false
+    }
+
+    /// Re-encode file `data` as is when the encoder works in a lossless mode
+    ///
+    /// Returns `None` if the image has to be decoded and passed to [`DynEncoder::encode`].
+    fn transcode(&self, _data: &[u8]) -> Option<Result<Vec<u8>, ImageErrors>> {
+        None
+    }
+
+    /// Encode the `image` into `sink`, returning the number of bytes written
+    fn encode(&mut self, image: &Image, sink: &mut dyn Write) -> Result<usize, ImageErrors>;
+}
+
+/// Forward [`DynEncoder`] to [`EncoderTrait`] of the `$encoder`
+macro_rules! impl_dyn_encoder {
+    ($encoder:ty, $format:expr $(, { $($extra:tt)* })?) => {
+        impl DynEncoder for $encoder {
+            fn name(&self) -> &'static str {
+                EncoderTrait::name(self)
+            }
+
+            fn format(&self) -> Format {
+                $format
+            }
+
+            fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+                EncoderTrait::supported_colorspaces(self)
+            }
+
+            fn supported_bit_depth(&self) -> &'static [BitDepth] {
+                EncoderTrait::supported_bit_depth(self)
+            }
+
+            fn default_depth(&self, depth: BitDepth) -> BitDepth {
+                EncoderTrait::default_depth(self, depth)
+            }
+
+            fn supports_animated_images(&self) -> bool {
+                EncoderTrait::supports_animated_images(self)
+            }
+
+            fn encode(
+                &mut self,
+                image: &Image,
+                sink: &mut dyn Write,
+            ) -> Result<usize, ImageErrors> {
+                EncoderTrait::encode(self, image, sink)
+            }
+
+            $($($extra)*)?
+        }
+    };
+}
+
+/// Implement [`DynEncoder`] for lossless transcoder, which can't encode decoded images
+#[allow(unused_macros)]
+macro_rules! impl_dyn_transcoder {
+    ($transcoder:ty, $name:expr, $format:expr, $error:expr) => {
+        impl DynEncoder for $transcoder {
+            fn name(&self) -> &'static str {
+                $name
+            }
+
+            fn format(&self) -> Format {
+                $format
+            }
+
+            fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+                &[]
+            }
+
+            fn supported_bit_depth(&self) -> &'static [BitDepth] {
+                &[]
+            }
+
+            fn default_depth(&self, _depth: BitDepth) -> BitDepth {
+                BitDepth::Eight
+            }
+
+            fn can_transcode(&self, magic: &[u8]) -> bool {
+                Format::detect(magic) == Some(Format::Jpeg)
+            }
+
+            fn transcode(&self, data: &[u8]) -> Option<Result<Vec<u8>, ImageErrors>> {
+                Some(<$transcoder>::transcode(self, data))
+            }
+
+            fn encode(
+                &mut self,
+                _image: &Image,
+                _sink: &mut dyn Write,
+            ) -> Result<usize, ImageErrors> {
+                Err(ImageErrors::EncodeErrors(
+                    zune_image::errors::ImgEncodeErrors::GenericStatic($error),
+                ))
+            }
+        }
+    };
+}
+
+#[cfg(feature = "avif")]
+impl_dyn_encoder!(crate::codecs::avif::AvifEncoder, Format::Avif);
+#[cfg(feature = "gif")]
+impl_dyn_encoder!(crate::codecs::gif::GifEncoder, Format::Gif);
+#[cfg(feature = "ico")]
+impl_dyn_encoder!(crate::codecs::ico::IcoEncoder, Format::Ico);
+#[cfg(feature = "jpegxl")]
+impl_dyn_encoder!(crate::codecs::jpegxl::JpegXlEncoder, Format::JpegXl);
+#[cfg(feature = "mozjpeg")]
+impl_dyn_encoder!(crate::codecs::mozjpeg::MozJpegEncoder, Format::Jpeg);
+#[cfg(feature = "oxipng")]
+impl_dyn_encoder!(crate::codecs::oxipng::OxiPngEncoder, Format::Png, {
+    fn set_indexed(&mut self, indexed: IndexedPixels) {
+        crate::codecs::oxipng::OxiPngEncoder::set_indexed(self, indexed)
+    }
+});
+#[cfg(feature = "texture")]
+impl_dyn_encoder!(crate::codecs::texture::DdsEncoder, Format::Dds);
+#[cfg(feature = "texture")]
+impl_dyn_encoder!(crate::codecs::texture::Ktx2Encoder, Format::Ktx2);
+#[cfg(feature = "tiff")]
+impl_dyn_encoder!(crate::codecs::tiff::TiffEncoder, Format::Tiff);
+#[cfg(feature = "webp")]
+impl_dyn_encoder!(crate::codecs::webp::WebPEncoder, Format::WebP);
+
+#[cfg(feature = "jpegxl")]
+impl_dyn_transcoder!(
+    crate::codecs::jpegxl::JpegXlTranscoder,
+    "jpegxl-transcoder",
+    Format::JpegXl,
+    "Lossless JPEG mode accepts only JPEG files"
+);
+#[cfg(feature = "mozjpeg")]
+impl_dyn_transcoder!(
+    crate::codecs::mozjpeg::MozJpegTranscoder,
+    "mozjpeg-transcoder",
+    Format::Jpeg,
+    "Lossless mode accepts only JPEG files"
+);
+
+/// Adapter of any [`EncoderTrait`] implementation to [`DynEncoder`]
+///
+/// Mostly useful for encoders of zune-image, which doesn't know formats of this crate.
+pub struct ZuneEncoder<E: EncoderTrait> {
+    inner: E,
+    format: Format,
+}
+
+impl<E: EncoderTrait> ZuneEncoder<E> {
+    /// Wrap `encoder` writing `format`
+    pub fn new(encoder: E, format: Format) -> ZuneEncoder<E> {
+        ZuneEncoder {
+            inner: encoder,
+            format,
+        }
+    }
+}
+
+impl<E: EncoderTrait> DynEncoder for ZuneEncoder<E> {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn format(&self) -> Format {
+        self.format
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        self.inner.supported_colorspaces()
+    }
+
+    fn supported_bit_depth(&self) -> &'static [BitDepth] {
+        self.inner.supported_bit_depth()
+    }
+
+    fn default_depth(&self, depth: BitDepth) -> BitDepth {
+        self.inner.default_depth(depth)
+    }
+
+    fn supports_animated_images(&self) -> bool {
+        self.inner.supports_animated_images()
+    }
+
+    fn encode(&mut self, image: &Image, sink: &mut dyn Write) -> Result<usize, ImageErrors> {
+        self.inner.encode(image, sink)
+    }
+}
+
+/// Typed options of an encoder, the variant selects the encoder
+///
+/// Serialized with the encoder name and its options, e.g.
+/// `{"encoder": "mozjpeg", "options": {"quality": 80}}`.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "encoder", content = "options", rename_all = "snake_case")
+)]
+pub enum EncoderConfig {
+    /// Options of [`crate::codecs::avif::AvifEncoder`]
+    #[cfg(feature = "avif")]
+    Avif(crate::codecs::avif::AvifOptions),
+    /// Options of [`crate::codecs::texture::DdsEncoder`]
+    #[cfg(feature = "texture")]
+    Dds(crate::codecs::texture::TextureOptions),
+    /// Options of [`crate::codecs::gif::GifEncoder`]
+    #[cfg(feature = "gif")]
+    Gif(crate::codecs::gif::GifOptions),
+    /// Options of [`crate::codecs::ico::IcoEncoder`]
+    #[cfg(feature = "ico")]
+    Ico(crate::codecs::ico::IcoOptions),
+    /// Options of [`crate::codecs::jpegxl::JpegXlEncoder`]
+    #[cfg(feature = "jpegxl")]
+    JpegXl(crate::codecs::jpegxl::JpegXlOptions),
+    /// Options of [`crate::codecs::jpegxl::JpegXlTranscoder`]
+    #[cfg(feature = "jpegxl")]
+    JpegXlLossless(crate::codecs::jpegxl::JpegXlTranscoderOptions),
+    /// Options of [`crate::codecs::texture::Ktx2Encoder`]
+    #[cfg(feature = "texture")]
+    Ktx2(crate::codecs::texture::TextureOptions),
+    /// Options of [`crate::codecs::mozjpeg::MozJpegEncoder`]
+    #[cfg(feature = "mozjpeg")]
+    #[cfg_attr(feature = "serde", serde(rename = "mozjpeg"))]
+    MozJpeg(crate::codecs::mozjpeg::MozJpegOptions),
+    /// Options of [`crate::codecs::mozjpeg::MozJpegTranscoder`]
+    #[cfg(feature = "mozjpeg")]
+    #[cfg_attr(feature = "serde", serde(rename = "mozjpeg_lossless"))]
+    MozJpegLossless(crate::codecs::mozjpeg::MozJpegTranscoderOptions),
+    /// Options of [`crate::codecs::oxipng::OxiPngEncoder`]
+    #[cfg(feature = "oxipng")]
+    #[cfg_attr(feature = "serde", serde(rename = "oxipng"))]
+    OxiPng(crate::codecs::oxipng::OxiPngOptions),
+    /// Options of [`crate::codecs::tiff::TiffEncoder`]
+    #[cfg(feature = "tiff")]
+    Tiff(crate::codecs::tiff::TiffOptions),
+    /// Options of [`crate::codecs::webp::WebPEncoder`]
+    #[cfg(feature = "webp")]
+    #[cfg_attr(feature = "serde", serde(rename = "webp"))]
+    WebP(crate::codecs::webp::WebPOptions),
+}
+
+/// Names accepted by [`EncoderConfig::from_name`], available with enabled features
+pub const ENCODER_NAMES: &[&str] = &[
+    #[cfg(feature = "avif")]
+    "avif",
+    #[cfg(feature = "texture")]
+    "dds",
+    #[cfg(feature = "gif")]
+    "gif",
+    #[cfg(feature = "ico")]
+    "ico",
+    #[cfg(feature = "jpegxl")]
+    "jpeg_xl",
+    #[cfg(feature = "jpegxl")]
+    "jpeg_xl_lossless",
+    #[cfg(feature = "texture")]
+    "ktx2",
+    #[cfg(feature = "mozjpeg")]
+    "mozjpeg",
+    #[cfg(feature = "mozjpeg")]
+    "mozjpeg_lossless",
+    #[cfg(feature = "oxipng")]
+    "oxipng",
+    #[cfg(feature = "tiff")]
+    "tiff",
+    #[cfg(feature = "webp")]
+    "webp",
+];
+
+impl EncoderConfig {
+    /// Default options of the encoder with `name`
+    ///
+    /// Returns `None` if there is no such encoder or its feature is disabled.
+    pub fn from_name(name: &str) -> Option<EncoderConfig> {
+        match name {
+            #[cfg(feature = "avif")]
+            "avif" => Some(EncoderConfig::Avif(Default::default())),
+            #[cfg(feature = "texture")]
+            "dds" => Some(EncoderConfig::Dds(Default::default())),
+            #[cfg(feature = "gif")]
+            "gif" => Some(EncoderConfig::Gif(Default::default())),
+            #[cfg(feature = "ico")]
+            "ico" => Some(EncoderConfig::Ico(Default::default())),
+            #[cfg(feature = "jpegxl")]
+            "jpeg_xl" => Some(EncoderConfig::JpegXl(Default::default())),
+            #[cfg(feature = "jpegxl")]
+            "jpeg_xl_lossless" => Some(EncoderConfig::JpegXlLossless(Default::default())),
+            #[cfg(feature = "texture")]
+            "ktx2" => Some(EncoderConfig::Ktx2(Default::default())),
+            #[cfg(feature = "mozjpeg")]
+            "mozjpeg" => Some(EncoderConfig::MozJpeg(Default::default())),
+            #[cfg(feature = "mozjpeg")]
+            "mozjpeg_lossless" => Some(EncoderConfig::MozJpegLossless(Default::default())),
+            #[cfg(feature = "oxipng")]
+            "oxipng" => Some(EncoderConfig::OxiPng(Default::default())),
+            #[cfg(feature = "tiff")]
+            "tiff" => Some(EncoderConfig::Tiff(Default::default())),
+            #[cfg(feature = "webp")]
+            "webp" => Some(EncoderConfig::WebP(Default::default())),
+            _ => None,
+        }
+    }
+
+    /// Name of the selected encoder, as accepted by [`EncoderConfig::from_name`]
+    pub fn name(&self) -> &'static str {
+        match *self {
+            #[cfg(feature = "avif")]
+            EncoderConfig::Avif(_) => "avif",
+            #[cfg(feature = "texture")]
+            EncoderConfig::Dds(_) => "dds",
+            #[cfg(feature = "gif")]
+            EncoderConfig::Gif(_) => "gif",
+            #[cfg(feature = "ico")]
+            EncoderConfig::Ico(_) => "ico",
+            #[cfg(feature = "jpegxl")]
+            EncoderConfig::JpegXl(_) => "jpeg_xl",
+            #[cfg(feature = "jpegxl")]
+            EncoderConfig::JpegXlLossless(_) => "jpeg_xl_lossless",
+            #[cfg(feature = "texture")]
+            EncoderConfig::Ktx2(_) => "ktx2",
+            #[cfg(feature = "mozjpeg")]
+            EncoderConfig::MozJpeg(_) => "mozjpeg",
+            #[cfg(feature = "mozjpeg")]
+            EncoderConfig::MozJpegLossless(_) => "mozjpeg_lossless",
+            #[cfg(feature = "oxipng")]
+            EncoderConfig::OxiPng(_) => "oxipng",
+            #[cfg(feature = "tiff")]
+            EncoderConfig::Tiff(_) => "tiff",
+            #[cfg(feature = "webp")]
+            EncoderConfig::WebP(_) => "webp",
+        }
+    }
+
+    /// Create the encoder with these options, rejecting out of range values
+    pub fn try_into_encoder(self) -> Result<Box<dyn DynEncoder>, OptionsError> {
+        self.validate()?;
+
+        Ok(self.into_encoder())
+    }
+
+    /// Create the encoder with these options
+    pub fn into_encoder(self) -> Box<dyn DynEncoder> {
+        match self {
+            #[cfg(feature = "avif")]
+            EncoderConfig::Avif(options) => {
+                Box::new(crate::codecs::avif::AvifEncoder::new_with_options(options))
+            }
+            #[cfg(feature = "texture")]
+            EncoderConfig::Dds(options) => Box::new(
+                crate::codecs::texture::DdsEncoder::new_with_options(options),
+            ),
+            #[cfg(feature = "gif")]
+            EncoderConfig::Gif(options) => {
+                Box::new(crate::codecs::gif::GifEncoder::new_with_options(options))
+            }
+            #[cfg(feature = "ico")]
+            EncoderConfig::Ico(options) => {
+                Box::new(crate::codecs::ico::IcoEncoder::new_with_options(options))
+            }
+            #[cfg(feature = "jpegxl")]
+            EncoderConfig::JpegXl(options) => Box::new(
+                crate::codecs::jpegxl::JpegXlEncoder::new_with_options(options),
+            ),
+            #[cfg(feature = "jpegxl")]
+            EncoderConfig::JpegXlLossless(options) => Box::new(
+                crate::codecs::jpegxl::JpegXlTranscoder::new_with_options(options),
+            ),
+            #[cfg(feature = "texture")]
+            EncoderConfig::Ktx2(options) => Box::new(
+                crate::codecs::texture::Ktx2Encoder::new_with_options(options),
+            ),
+            #[cfg(feature = "mozjpeg")]
+            EncoderConfig::MozJpeg(options) => Box::new(
+                crate::codecs::mozjpeg::MozJpegEncoder::new_with_options(options),
+            ),
+            #[cfg(feature = "mozjpeg")]
+            EncoderConfig::MozJpegLossless(options) => Box::new(
+                crate::codecs::mozjpeg::MozJpegTranscoder::new_with_options(options),
+            ),
+            #[cfg(feature = "oxipng")]
+            EncoderConfig::OxiPng(options) => Box::new(
+                crate::codecs::oxipng::OxiPngEncoder::new_with_options(options),
+            ),
+            #[cfg(feature = "tiff")]
+            EncoderConfig::Tiff(options) => {
+                Box::new(crate::codecs::tiff::TiffEncoder::new_with_options(options))
+            }
+            #[cfg(feature = "webp")]
+            EncoderConfig::WebP(options) => {
+                Box::new(crate::codecs::webp::WebPEncoder::new_with_options(options))
+            }
+        }
+    }
+}
+
+impl Validate for EncoderConfig {
+    fn validate(&self) -> Result<(), OptionsError> {
+        match *self {
+            #[cfg(feature = "avif")]
+            EncoderConfig::Avif(ref options) => options.validate(),
+            #[cfg(feature = "texture")]
+            EncoderConfig::Dds(ref options) | EncoderConfig::Ktx2(ref options) => {
+                options.validate()
+            }
+            #[cfg(feature = "gif")]
+            EncoderConfig::Gif(ref options) => options.validate(),
+            #[cfg(feature = "ico")]
+            EncoderConfig::Ico(ref options) => options.validate(),
+            #[cfg(feature = "jpegxl")]
+            EncoderConfig::JpegXl(ref options) => options.validate(),
+            #[cfg(feature = "jpegxl")]
+            EncoderConfig::JpegXlLossless(ref options) => options.validate(),
+            #[cfg(feature = "mozjpeg")]
+            EncoderConfig::MozJpeg(ref options) => options.validate(),
+            #[cfg(feature = "mozjpeg")]
+            EncoderConfig::MozJpegLossless(ref options) => options.validate(),
+            #[cfg(feature = "oxipng")]
+            EncoderConfig::OxiPng(ref options) => options.validate(),
+            #[cfg(feature = "tiff")]
+            EncoderConfig::Tiff(ref options) => options.validate(),
+            #[cfg(feature = "webp")]
+            EncoderConfig::WebP(ref options) => options.validate(),
+        }
+    }
+}
+
+/// Create the encoder with `name` and default options
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(feature = "mozjpeg")]
+/// # {
+/// use rimage::codecs::dynamic::encoder;
+///
+/// let encoder = encoder("mozjpeg").unwrap();
+///
+/// assert_eq!(encoder.extension(), "jpg");
+/// assert_eq!(encoder.mime_type(), "image/jpeg");
+/// # }
+/// ```
+pub fn encoder(name: &str) -> Option<Box<dyn DynEncoder>> {
+    EncoderConfig::from_name(name).map(EncoderConfig::into_encoder)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,102 @@
+use zune_core::colorspace::ColorSpace;
+
+use crate::{codecs::registry::detect, test_utils::*};
+
+use super::*;
+
+#[test]
+fn encoder_by_name() {
+    for &name in ENCODER_NAMES {
+        let config = EncoderConfig::from_name(name).unwrap();
+
+        assert_eq!(config.name(), name);
+        assert!(encoder(name).is_some(), "{name}");
+    }
+
+    assert!(encoder("unknown").is_none());
+}
+
+#[test]
+fn encode_all() {
+    let image = create_test_image_u8(32, 32, ColorSpace::RGBA);
+
+    for name in ENCODER_NAMES
+        .iter()
+        .filter(|name| !name.ends_with("_lossless"))
+    {
+        let mut encoder = encoder(name).unwrap();
+
+        let mut data = vec![];
+        encoder.encode(&image, &mut data).unwrap();
+
+        assert_eq!(detect(&data), Some(encoder.format()), "{name}");
+        assert_eq!(
+            Format::from_extension(encoder.extension()),
+            Some(encoder.format()),
+            "{name}"
+        );
+    }
+}
+
+#[cfg(feature = "webp")]
+#[test]
+fn webp_format() {
+    let encoder = encoder("webp").unwrap();
+
+    assert_eq!(encoder.format(), Format::WebP);
+    assert_eq!(encoder.extension(), "webp");
+    assert_eq!(encoder.mime_type(), "image/webp");
+}
+
+#[cfg(feature = "tiff")]
+#[test]
+fn tiff_format() {
+    let encoder = encoder("tiff").unwrap();
+
+    assert_eq!(encoder.format(), Format::Tiff);
+    assert_eq!(encoder.extension(), "tiff");
+    assert_eq!(encoder.mime_type(), "image/tiff");
+}
+
+#[cfg(feature = "avif")]
+#[test]
+fn avif_format() {
+    let encoder = encoder("avif").unwrap();
+
+    assert_eq!(encoder.format(), Format::Avif);
+    assert_eq!(encoder.mime_type(), "image/avif");
+}
+
+#[cfg(feature = "mozjpeg")]
+#[test]
+fn transcode_lossless() {
+    let data = std::fs::read("tests/files/jpg/f1t.jpg").unwrap();
+    let mut encoder = encoder("mozjpeg_lossless").unwrap();
+
+    assert!(encoder.can_transcode(&data[..64]));
+    assert!(!encoder.can_transcode(b"\x89PNG\r\n\x1a\n"));
+
+    let transcoded = encoder.transcode(&data).unwrap().unwrap();
+
+    assert_eq!(detect(&transcoded), Some(Format::Jpeg));
+
+    let image = create_test_image_u8(32, 32, ColorSpace::RGB);
+    assert!(encoder.encode(&image, &mut Vec::<u8>::new()).is_err());
+}
+
+#[test]
+fn zune_encoder() {
+    let image = create_test_image_u8(32, 32, ColorSpace::RGB);
+
+    let mut encoder: Box<dyn DynEncoder> = Box::new(ZuneEncoder::new(
+        zune_image::codecs::png::PngEncoder::new(),
+        Format::Png,
+    ));
+
+    let mut data = vec![];
+    encoder.encode(&image, &mut data).unwrap();
+
+    assert_eq!(detect(&data), Some(Format::Png));
+    assert!(!encoder.can_transcode(&data));
+    assert!(encoder.transcode(&data).is_none());
+}
@@ -0,0 +1,215 @@
+use std::{io::Read, marker::PhantomData};
+
+use gif::{ColorOutput, DecodeOptions, DisposalMethod, Repeat};
+use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
+use zune_image::{errors::ImageErrors, frame::Frame, image::Image, traits::DecoderTrait};
+
+use crate::{Format, ImageInfo};
+
+/// Properties of the GIF read without decoding frames
+struct GifHeader {
+    width: usize,
+    height: usize,
+    frame_count: usize,
+    loop_count: u32,
+    has_transparency: bool,
+}
+
+/// A GIF decoder
+///
+/// Frames are composed onto the canvas according to their disposal methods,
+/// so every decoded frame is a full image.
+pub struct GifDecoder<R: Read> {
+    inner: Vec<u8>,
+    header: GifHeader,
+    phantom: PhantomData<R>,
+}
+
+impl<R: Read> GifDecoder<R> {
+    /// Create a new gif decoder that reads data from `source`
+    pub fn try_new(mut source: R) -> Result<GifDecoder<R>, ImageErrors> {
+        let mut buf = Vec::new();
+        source.read_to_end(&mut buf)?;
+
+        let header = read_header(&buf).map_err(decode_error)?;
+
+        Ok(GifDecoder {
+            inner: buf,
+            header,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Image information read from the headers
+    pub fn info(&self) -> ImageInfo {
+        ImageInfo {
+            format: Format::Gif,
+            dimensions: (self.header.width, self.header.height),
+            colorspace: ColorSpace::RGBA,
+            depth: BitDepth::Eight,
+            frame_count: self.header.frame_count,
+            has_alpha: self.header.has_transparency,
+        }
+    }
+
+    /// Number of times animation is played, `0` means infinite
+    pub fn loop_count(&self) -> u32 {
+        self.header.loop_count
+    }
+}
+
+fn decode_error(e: gif::DecodingError) -> ImageErrors {
+    ImageErrors::ImageDecodeErrors(e.to_string())
+}
+
+/// Walk through frames without decompressing them
+fn read_header(data: &[u8]) -> Result<GifHeader, gif::DecodingError> {
+    let mut options = DecodeOptions::new();
+    options.skip_frame_decoding(true);
+
+    let mut decoder = options.read_info(data)?;
+
+    let mut frame_count = 0;
+    let mut has_transparency = false;
+
+    while let Some(frame) = decoder.read_next_frame()? {
+        frame_count += 1;
+        has_transparency |= frame.transparent.is_some();
+    }
+
+    Ok(GifHeader {
+        width: decoder.width() as usize,
+        height: decoder.height() as usize,
+        frame_count,
+        // the extension counts repetitions after the first play
+        loop_count: match decoder.repeat() {
+            Repeat::Infinite => 0,
+            Repeat::Finite(n) => u32::from(n) + 1,
+        },
+        has_transparency,
+    })
+}
+
+impl<R> DecoderTrait for GifDecoder<R>
+where
+    R: Read,
+{
+    fn decode(&mut self) -> Result<Image, ImageErrors> {
+        let (width, height) = (self.header.width, self.header.height);
+        let color = <GifDecoder<R> as DecoderTrait>::out_colorspace(self);
+
+        let mut options = DecodeOptions::new();
+        options.set_color_output(ColorOutput::RGBA);
+
+        let mut decoder = options
+            .read_info(self.inner.as_slice())
+            .map_err(decode_error)?;
+
+        // the background color is ignored by browsers, canvas starts transparent
+        let mut canvas = vec![0; width * height * 4];
+        let mut frames = vec![];
+
+        while let Some(frame) = decoder.read_next_frame().map_err(decode_error)? {
+            let previous = (frame.dispose == DisposalMethod::Previous).then(|| canvas.clone());
+
+            let area = Area::new(frame, width, height);
+
+            // transparent pixels keep the canvas beneath
+            area.for_each_row(width, |src, dst| {
+                let src = &frame.buffer[src..][..area.width * 4];
+
+                src.chunks_exact(4)
+                    .zip(canvas[dst..].chunks_exact_mut(4))
+                    .filter(|(px, _)| px[3] != 0)
+                    .for_each(|(px, out)| out.copy_from_slice(px));
+            });
+
+            // delay is in hundredths of a second
+            frames.push(Frame::from_u8(
+                &canvas,
+                color,
+                usize::from(frame.delay) * 10,
+                1000,
+            ));
+
+            match frame.dispose {
+                DisposalMethod::Background => area.for_each_row(width, |_, dst| {
+                    canvas[dst..][..area.width * 4].fill(0);
+                }),
+                DisposalMethod::Previous => canvas = previous.unwrap(),
+                DisposalMethod::Any | DisposalMethod::Keep => {}
+            }
+        }
+
+        if frames.is_empty() {
+            return Err(ImageErrors::ImageDecodeErrors(
+                "GIF contains no frames".to_string(),
+            ));
+        }
+
+        Ok(Image::new_frames(
+            frames,
+            BitDepth::Eight,
+            width,
+            height,
+            color,
+        ))
+    }
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        Some((self.header.width, self.header.height))
+    }
+
+    fn out_colorspace(&self) -> ColorSpace {
+        // frames may be transparent or partially cover the canvas
+        ColorSpace::RGBA
+    }
+
+    fn name(&self) -> &'static str {
+        "gif"
+    }
+}
+
+/// Part of the frame that lies on the canvas
+struct Area {
+    left: usize,
+    top: usize,
+    width: usize,
+    height: usize,
+    /// Width of the frame, which may extend past the canvas
+    stride: usize,
+}
+
+impl Area {
+    fn new(frame: &gif::Frame, canvas_width: usize, canvas_height: usize) -> Self {
+        let (left, top) = (usize::from(frame.left), usize::from(frame.top));
+        let stride = usize::from(frame.width);
+
+        Self {
+            left,
+            top,
+            width: stride.min(canvas_width.saturating_sub(left)),
+            height: usize::from(frame.height).min(canvas_height.saturating_sub(top)),
+            stride,
+        }
+    }
+
+    /// Call `f` with offsets of every row in the frame buffer and on the canvas
+    ///
+    /// Frames placed entirely off the canvas have no rows.
+    fn for_each_row(&self, canvas_width: usize, mut f: impl FnMut(usize, usize)) {
+        if self.width == 0 {
+            return;
+        }
+
+        for y in 0..self.height {
+            f(
+                y * self.stride * 4,
+                ((self.top + y) * canvas_width + self.left) * 4,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,176 @@
+use std::{borrow::Cow, fs::File, io::Cursor};
+
+use gif::{DisposalMethod, Repeat};
+use zune_image::traits::EncoderTrait;
+
+use crate::codecs::{
+    frame_duration_ms,
+    gif::{GifEncoder, GifOptions},
+};
+
+use super::*;
+
+#[test]
+fn decode() {
+    let file_content = File::open("tests/files/gif/f1t.gif").unwrap();
+
+    let decoder = GifDecoder::try_new(file_content).unwrap();
+
+    let img = Image::from_decoder(decoder).unwrap();
+
+    assert_eq!(img.dimensions(), (48, 80));
+    assert_eq!(img.colorspace(), ColorSpace::RGBA);
+}
+
+#[test]
+fn info_without_decoding() {
+    let file_content = File::open("tests/files/gif/f1t.gif").unwrap();
+
+    let decoder = GifDecoder::try_new(file_content).unwrap();
+    let info = decoder.info();
+
+    assert_eq!(decoder.dimensions(), Some((48, 80)));
+    assert_eq!(info.frame_count, 1);
+    assert!(!info.has_alpha);
+}
+
+#[test]
+fn decode_animated_timing() {
+    let frames = (1..=3)
+        .map(|i| {
+            Frame::from_u8(
+                &vec![i as u8 * 60; 48 * 80 * 3],
+                ColorSpace::RGB,
+                i * 100,
+                1000,
+            )
+        })
+        .collect();
+    let image = Image::new_frames(frames, BitDepth::Eight, 48, 80, ColorSpace::RGB);
+
+    let mut encoder = GifEncoder::new_with_options(GifOptions {
+        loop_count: 3,
+        ..Default::default()
+    });
+
+    let mut buf = Cursor::new(vec![]);
+    encoder.encode(&image, &mut buf).unwrap();
+    buf.set_position(0);
+
+    let decoder = GifDecoder::try_new(buf).unwrap();
+
+    assert_eq!(decoder.loop_count(), 3);
+    assert_eq!(decoder.info().frame_count, 3);
+
+    let img = Image::from_decoder(decoder).unwrap();
+
+    let durations = img
+        .frames_ref()
+        .iter()
+        .map(frame_duration_ms)
+        .collect::<Vec<_>>();
+
+    assert_eq!(durations, [100, 200, 300]);
+}
+
+/// Pixel at (`x`, `y`) of the `index` frame
+fn pixel(img: &Image, index: usize, x: usize, y: usize) -> [u8; 4] {
+    let data = &img.flatten_to_u8()[index];
+    let offset = (y * img.dimensions().0 + x) * 4;
+
+    data[offset..offset + 4].try_into().unwrap()
+}
+
+#[test]
+fn decode_disposal() {
+    // black, red and green
+    let palette = [0, 0, 0, 255, 0, 0, 0, 255, 0];
+
+    let mut buf = vec![];
+    {
+        let mut encoder = gif::Encoder::new(&mut buf, 4, 4, &palette).unwrap();
+        encoder.set_repeat(Repeat::Infinite).unwrap();
+
+        let mut frame = |left, top, size, color, dispose| {
+            encoder
+                .write_frame(&gif::Frame {
+                    left,
+                    top,
+                    width: size,
+                    height: size,
+                    dispose,
+                    buffer: Cow::Owned(vec![color; (size * size) as usize]),
+                    ..Default::default()
+                })
+                .unwrap();
+        };
+
+        frame(0, 0, 4, 0, DisposalMethod::Keep);
+        frame(0, 0, 2, 1, DisposalMethod::Previous);
+        frame(2, 2, 2, 2, DisposalMethod::Background);
+        frame(3, 0, 1, 1, DisposalMethod::Keep);
+    }
+
+    let decoder = GifDecoder::try_new(Cursor::new(buf)).unwrap();
+
+    assert_eq!(decoder.loop_count(), 0);
+
+    let img = Image::from_decoder(decoder).unwrap();
+
+    assert_eq!(img.frames_ref().len(), 4);
+
+    const BLACK: [u8; 4] = [0, 0, 0, 255];
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const GREEN: [u8; 4] = [0, 255, 0, 255];
+
+    assert_eq!(pixel(&img, 1, 0, 0), RED);
+    // red square is removed before the third frame
+    assert_eq!(pixel(&img, 2, 0, 0), BLACK);
+    assert_eq!(pixel(&img, 2, 3, 3), GREEN);
+    // green square is cleared to transparent
+    assert_eq!(pixel(&img, 3, 3, 3), [0; 4]);
+    assert_eq!(pixel(&img, 3, 3, 0), RED);
+}
+
+#[test]
+fn decode_off_canvas() {
+    let palette = [0, 0, 0, 255, 0, 0];
+
+    let mut buf = vec![];
+    {
+        let mut encoder = gif::Encoder::new(&mut buf, 4, 4, &palette).unwrap();
+
+        let mut frame = |left, top, color, dispose| {
+            encoder
+                .write_frame(&gif::Frame {
+                    left,
+                    top,
+                    width: 2,
+                    height: 2,
+                    dispose,
+                    buffer: Cow::Owned(vec![color; 4]),
+                    ..Default::default()
+                })
+                .unwrap();
+        };
+
+        frame(0, 0, 0, DisposalMethod::Keep);
+        // right of the canvas on the last row, and below it
+        frame(6, 3, 1, DisposalMethod::Background);
+        frame(1, 9, 1, DisposalMethod::Background);
+    }
+
+    let decoder = GifDecoder::try_new(Cursor::new(buf)).unwrap();
+    let img = Image::from_decoder(decoder).unwrap();
+
+    assert_eq!(img.frames_ref().len(), 3);
+    assert_eq!(pixel(&img, 2, 0, 0), [0, 0, 0, 255]);
+    assert_eq!(pixel(&img, 2, 3, 3), [0; 4]);
+}
+
+#[test]
+fn decode_invalid() {
+    let result = GifDecoder::try_new(Cursor::new(b"GIF89a".to_vec()));
+
+    assert!(result.is_err());
+}
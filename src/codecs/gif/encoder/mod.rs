@@ -0,0 +1,332 @@
+use gif::{DisposalMethod, Repeat};
+use imagequant::{QuantizationResult, RGBA};
+use rgb::FromSlice;
+use zune_core::{
+    bit_depth::BitDepth,
+    bytestream::{ZByteWriterTrait, ZWriter},
+    colorspace::ColorSpace,
+};
+use zune_image::{
+    codecs::ImageFormat,
+    errors::{ImageErrors, ImgEncodeErrors},
+    image::Image,
+    traits::EncoderTrait,
+};
+
+use crate::{
+    codecs::frame_duration_ms,
+    options::{check_range, OptionsError, Validate},
+};
+
+/// Advanced options for GIF encoding
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct GifOptions {
+    /// Quality of the palettes chosen by imagequant. `0..=100`
+    pub quality: u8,
+    /// Dithering level of the quantized frames, `0.0` disables it. `0.0..=1.0`
+    pub dithering: f32,
+    /// Quantize all frames with one palette written as the global color table,
+    /// otherwise every frame gets its own local palette
+    pub global_palette: bool,
+    /// Number of times animation is played, `0` means infinite
+    pub loop_count: u32,
+}
+
+impl Default for GifOptions {
+    fn default() -> Self {
+        Self {
+            quality: 100,
+            dithering: 1.,
+            global_palette: false,
+            loop_count: 0,
+        }
+    }
+}
+
+impl Validate for GifOptions {
+    fn validate(&self) -> Result<(), OptionsError> {
+        check_range("quality", self.quality, 0..=100)?;
+        check_range("dithering", self.dithering, 0.0..=1.0)
+    }
+}
+
+/// A GIF encoder
+///
+/// Frames are reduced to 256 colors with imagequant, alpha is reduced
+/// to a single transparent color.
+#[derive(Default)]
+pub struct GifEncoder {
+    options: GifOptions,
+}
+
+impl GifEncoder {
+    /// Create a new encoder
+    pub fn new() -> GifEncoder {
+        GifEncoder::default()
+    }
+
+    /// Create a new encoder with specified options
+    pub fn new_with_options(options: GifOptions) -> GifEncoder {
+        GifEncoder { options }
+    }
+
+    /// Create a new encoder with specified options, rejecting out of range values
+    pub fn try_new_with_options(options: GifOptions) -> Result<GifEncoder, OptionsError> {
+        options.validate()?;
+
+        Ok(GifEncoder::new_with_options(options))
+    }
+}
+
+/// Frame reduced to a palette
+struct IndexedFrame {
+    palette: Vec<RGBA>,
+    pixels: Vec<u8>,
+}
+
+impl EncoderTrait for GifEncoder {
+    fn name(&self) -> &'static str {
+        "gif"
+    }
+
+    fn encode_inner<T: ZByteWriterTrait>(
+        &mut self,
+        image: &Image,
+        sink: T,
+    ) -> Result<usize, ImageErrors> {
+        let (width, height) = image.dimensions();
+
+        if width > usize::from(u16::MAX) || height > usize::from(u16::MAX) {
+            return Err(ImageErrors::EncodeErrors(ImgEncodeErrors::Generic(
+                format!("GIF dimensions are limited to 65535, got {width}x{height}"),
+            )));
+        }
+
+        let (gif_width, gif_height) = (width as u16, height as u16);
+
+        let frames = self.quantize(image)?;
+
+        // every frame shares the palette of the first one
+        let global_palette = match self.options.global_palette {
+            true => rgb_palette(&frames[0].palette),
+            false => vec![],
+        };
+
+        let mut encoder = gif::Encoder::new(vec![], gif_width, gif_height, &global_palette)
+            .map_err(encode_error)?;
+
+        if image.is_animated() {
+            let repeat = match self.options.loop_count {
+                0 => Some(Repeat::Infinite),
+                1 => None,
+                n => Some(Repeat::Finite(u16::try_from(n - 1).unwrap_or(u16::MAX))),
+            };
+
+            if let Some(repeat) = repeat {
+                encoder.set_repeat(repeat).map_err(encode_error)?;
+            }
+        }
+
+        for (mut indexed, frame) in frames.into_iter().zip(image.frames_ref()) {
+            let transparent = indexed.reduce_alpha();
+            let palette = rgb_palette(&indexed.palette);
+
+            let mut gif_frame = if self.options.global_palette {
+                gif::Frame::from_indexed_pixels(gif_width, gif_height, indexed.pixels, transparent)
+            } else {
+                gif::Frame::from_palette_pixels(
+                    gif_width,
+                    gif_height,
+                    indexed.pixels,
+                    palette,
+                    transparent,
+                )
+            };
+
+            // every frame covers the whole canvas
+            gif_frame.dispose = DisposalMethod::Background;
+            // delay is in hundredths of a second
+            gif_frame.delay =
+                u16::try_from((frame_duration_ms(frame) + 5) / 10).unwrap_or(u16::MAX);
+
+            encoder.write_frame(&gif_frame).map_err(encode_error)?;
+        }
+
+        let data = encoder.into_inner()?;
+
+        let mut writer = ZWriter::new(sink);
+
+        writer.write(&data).map_err(|e| {
+            ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
+        })?;
+
+        Ok(writer.bytes_written())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[ColorSpace::RGBA]
+    }
+
+    /// zune-image has no GIF format, [`DynEncoder::format`](crate::codecs::DynEncoder::format)
+    /// reports [`Format::Gif`](crate::Format::Gif)
+    fn format(&self) -> ImageFormat {
+        ImageFormat::Unknown
+    }
+
+    fn supported_bit_depth(&self) -> &'static [BitDepth] {
+        &[BitDepth::Eight]
+    }
+
+    fn default_depth(&self, _depth: BitDepth) -> BitDepth {
+        BitDepth::Eight
+    }
+
+    fn supports_animated_images(&self) -> bool {
+        true
+    }
+}
+
+impl GifEncoder {
+    /// Quantize every frame of the `image`
+    fn quantize(&self, image: &Image) -> Result<Vec<IndexedFrame>, ImageErrors> {
+        let (width, height) = image.dimensions();
+
+        let mut liq = imagequant::new();
+
+        liq.set_quality(0, self.options.quality)
+            .map_err(quantize_error)?;
+
+        let mut frames = image
+            .flatten_to_u8()
+            .into_iter()
+            .map(|data| {
+                liq.new_image(data.as_rgba().to_vec(), width, height, 0.0)
+                    .map_err(quantize_error)
+            })
+            .collect::<Result<Vec<_>, ImageErrors>>()?;
+
+        if !self.options.global_palette {
+            return frames
+                .iter_mut()
+                .map(|img| {
+                    let mut res = liq.quantize(img).map_err(quantize_error)?;
+                    self.remap(&mut res, img)
+                })
+                .collect();
+        }
+
+        let mut histogram = imagequant::Histogram::new(&liq);
+
+        for img in &mut frames {
+            histogram.add_image(&liq, img).map_err(quantize_error)?;
+        }
+
+        let mut res = histogram.quantize(&liq).map_err(quantize_error)?;
+
+        let mut indexed = frames
+            .iter_mut()
+            .map(|img| self.remap(&mut res, img))
+            .collect::<Result<Vec<_>, ImageErrors>>()?;
+
+        // remapping may refine the palette, move later frames onto the first one
+        let (first, rest) = indexed.split_first_mut().unwrap();
+
+        for frame in rest
+            .iter_mut()
+            .filter(|frame| frame.palette != first.palette)
+        {
+            let lookup = frame
+                .palette
+                .iter()
+                .map(|color| nearest(&first.palette, *color))
+                .collect::<Vec<_>>();
+
+            frame
+                .pixels
+                .iter_mut()
+                .for_each(|px| *px = lookup[*px as usize]);
+            frame.palette.clone_from(&first.palette);
+        }
+
+        Ok(indexed)
+    }
+
+    fn remap(
+        &self,
+        res: &mut QuantizationResult,
+        img: &mut imagequant::Image,
+    ) -> Result<IndexedFrame, ImageErrors> {
+        res.set_dithering_level(self.options.dithering)
+            .map_err(quantize_error)?;
+
+        let (palette, pixels) = res.remapped(img).map_err(quantize_error)?;
+
+        Ok(IndexedFrame { palette, pixels })
+    }
+}
+
+impl IndexedFrame {
+    /// Move pixels of mostly transparent colors onto a single transparent color
+    ///
+    /// GIF supports only one fully transparent color, its index is returned.
+    fn reduce_alpha(&mut self) -> Option<u8> {
+        let transparent = self.palette.iter().position(|color| color.a < 128)? as u8;
+
+        let is_transparent = self
+            .palette
+            .iter()
+            .map(|color| color.a < 128)
+            .collect::<Vec<_>>();
+
+        self.pixels
+            .iter_mut()
+            .filter(|px| is_transparent[**px as usize])
+            .for_each(|px| *px = transparent);
+
+        Some(transparent)
+    }
+}
+
+/// Colors of the `palette` without alpha
+fn rgb_palette(palette: &[RGBA]) -> Vec<u8> {
+    palette
+        .iter()
+        .flat_map(|color| [color.r, color.g, color.b])
+        .collect()
+}
+
+/// Index of the color closest to `color` in the `palette`
+fn nearest(palette: &[RGBA], color: RGBA) -> u8 {
+    let distance = |other: &RGBA| -> u32 {
+        [
+            (color.r, other.r),
+            (color.g, other.g),
+            (color.b, other.b),
+            (color.a, other.a),
+        ]
+        .iter()
+        .map(|(a, b)| u32::from(a.abs_diff(*b)).pow(2))
+        .sum()
+    };
+
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, other)| distance(other))
+        .map_or(0, |(index, _)| index as u8)
+}
+
+fn quantize_error(e: imagequant::Error) -> ImageErrors {
+    ImageErrors::EncodeErrors(ImgEncodeErrors::Generic(e.to_string()))
+}
+
+fn encode_error(e: gif::EncodingError) -> ImageErrors {
+    ImageErrors::EncodeErrors(ImgEncodeErrors::Generic(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,91 @@
+use std::io::Cursor;
+
+use zune_core::colorspace::ColorSpace;
+use zune_image::frame::Frame;
+
+use crate::{codecs::gif::GifDecoder, test_utils::*};
+
+use super::*;
+
+fn decode(data: Vec<u8>) -> Image {
+    let decoder = GifDecoder::try_new(Cursor::new(data)).unwrap();
+
+    Image::from_decoder(decoder).unwrap()
+}
+
+#[test]
+fn encode_colorspaces() {
+    for colorspace in [ColorSpace::RGB, ColorSpace::RGBA, ColorSpace::Luma] {
+        let image = create_test_image_u8(200, 200, colorspace);
+
+        let mut buf = Cursor::new(vec![]);
+        let result = GifEncoder::new().encode(&image, &mut buf);
+
+        assert!(result.is_ok(), "{colorspace:?}");
+        assert_eq!(decode(buf.into_inner()).dimensions(), (200, 200));
+    }
+}
+
+#[test]
+fn encode_u16() {
+    let image = create_test_image_u16(200, 200, ColorSpace::RGB);
+
+    let result = GifEncoder::new().encode(&image, Cursor::new(vec![]));
+
+    assert!(result.is_ok());
+}
+
+#[test]
+fn encode_animated() {
+    for global_palette in [false, true] {
+        let image = create_test_image_animated(200, 200, ColorSpace::RGB);
+
+        let mut encoder = GifEncoder::new_with_options(GifOptions {
+            global_palette,
+            ..Default::default()
+        });
+
+        let mut buf = Cursor::new(vec![]);
+        encoder.encode(&image, &mut buf).unwrap();
+        let data = buf.into_inner();
+
+        // global color table flag of the logical screen descriptor
+        assert_eq!(data[10] & 0x80 != 0, global_palette);
+
+        let decoder = GifDecoder::try_new(Cursor::new(data.clone())).unwrap();
+
+        assert_eq!(decoder.loop_count(), 0);
+        assert_eq!(decode(data).frames_ref().len(), image.frames_ref().len());
+    }
+}
+
+#[test]
+fn encode_transparency() {
+    // left half is transparent, right half is opaque red
+    let image = Image::from_fn(8, 8, ColorSpace::RGBA, |x, _, px: &mut [u8; 4]| {
+        *px = if x < 4 {
+            [0, 0, 0, 0]
+        } else {
+            [255, 0, 0, 255]
+        };
+    });
+
+    let mut buf = Cursor::new(vec![]);
+    GifEncoder::new().encode(&image, &mut buf).unwrap();
+
+    let decoded = decode(buf.into_inner());
+    let data = &decoded.flatten_to_u8()[0];
+
+    assert_eq!(data[3], 0);
+    assert_eq!(&data[4 * 4..4 * 4 + 4], &[255, 0, 0, 255]);
+}
+
+#[test]
+fn encode_too_large() {
+    let frame = Frame::from_u8(&vec![0; 70000 * 4], ColorSpace::RGBA, 0, 0);
+    let image = Image::new_frames(vec![frame], BitDepth::Eight, 70000, 1, ColorSpace::RGBA);
+
+    let result = GifEncoder::new().encode(&image, Cursor::new(vec![]));
+
+    assert!(result.is_err());
+}
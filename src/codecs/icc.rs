@@ -0,0 +1,141 @@
+/// Colorants of sRGB adapted to the D50 illuminant, as stored in ICC profiles
+const SRGB_COLORANTS: [(&[u8; 4], [f64; 3]); 3] = [
+    (b"rXYZ", [0.4361, 0.2225, 0.0139]),
+    (b"gXYZ", [0.3851, 0.7169, 0.0971]),
+    (b"bXYZ", [0.1431, 0.0606, 0.7141]),
+];
+
+/// Rendering intent stored in the ICC profile header
+pub(crate) fn rendering_intent(profile: &[u8]) -> u8 {
+    profile.get(64..68).map_or(0, |intent| intent[3].min(3))
+}
+
+/// Check whether the ICC `profile` describes plain sRGB
+///
+/// Profiles are compared by their colorants and tone curves rather than by
+/// the description, so re-encoded or minimized sRGB profiles are recognized too.
+pub(crate) fn is_srgb(profile: &[u8]) -> bool {
+    // color space of the data and profile connection space
+    if profile.get(16..20) != Some(b"RGB ") || profile.get(20..24) != Some(b"XYZ ") {
+        return false;
+    }
+
+    let colorants = SRGB_COLORANTS.iter().all(|(signature, expected)| {
+        tag(profile, signature)
+            .and_then(xyz)
+            .is_some_and(|xyz| xyz.iter().zip(expected).all(|(a, b)| (a - b).abs() < 0.003))
+    });
+
+    colorants
+        && [b"rTRC", b"gTRC", b"bTRC"].iter().all(|signature| {
+            tag(profile, signature).is_some_and(|curve| {
+                // compare with sRGB transfer function on a few points
+                [0.02, 0.1, 0.25, 0.5, 0.75, 0.9].iter().all(|&x| {
+                    eval_curve(curve, x).is_some_and(|y| (y - srgb_to_linear(x)).abs() < 0.002)
+                })
+            })
+        })
+}
+
+fn srgb_to_linear(x: f64) -> f64 {
+    if x <= 0.04045 {
+        x / 12.92
+    } else {
+        ((x + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn be_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn be_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+/// Signed 15.16 fixed point number
+fn s15_fixed16(data: &[u8], offset: usize) -> Option<f64> {
+    be_u32(data, offset).map(|v| v as i32 as f64 / 65536.)
+}
+
+/// Data of the tag with `signature` from the tag table
+fn tag<'a>(profile: &'a [u8], signature: &[u8; 4]) -> Option<&'a [u8]> {
+    let count = be_u32(profile, 128)? as usize;
+
+    (0..count).find_map(|i| {
+        let entry = 132 + i * 12;
+
+        if profile.get(entry..entry + 4)? != signature {
+            return None;
+        }
+
+        let offset = be_u32(profile, entry + 4)? as usize;
+        let size = be_u32(profile, entry + 8)? as usize;
+
+        profile.get(offset..offset.checked_add(size)?)
+    })
+}
+
+fn xyz(data: &[u8]) -> Option<[f64; 3]> {
+    if data.get(..4)? != b"XYZ " {
+        return None;
+    }
+
+    Some([
+        s15_fixed16(data, 8)?,
+        s15_fixed16(data, 12)?,
+        s15_fixed16(data, 16)?,
+    ])
+}
+
+/// Evaluate `curv` or `para` tone curve at `x` in `0..=1`
+fn eval_curve(data: &[u8], x: f64) -> Option<f64> {
+    match data.get(..4)? {
+        b"curv" => {
+            let count = be_u32(data, 8)? as usize;
+
+            match count {
+                0 => Some(x),
+                // gamma as unsigned 8.8 fixed point number
+                1 => Some(x.powf(be_u16(data, 12)? as f64 / 256.)),
+                _ => {
+                    let position = x * (count - 1) as f64;
+                    let index = (position as usize).min(count - 2);
+                    let fraction = position - index as f64;
+
+                    let a = be_u16(data, 12 + index * 2)? as f64 / 65535.;
+                    let b = be_u16(data, 12 + (index + 1) * 2)? as f64 / 65535.;
+
+                    Some(a + (b - a) * fraction)
+                }
+            }
+        }
+        b"para" => {
+            let function = be_u16(data, 8)?;
+            let count = *[1, 3, 4, 5, 7].get(function as usize)?;
+
+            let mut p = [0.; 7];
+            for (i, value) in p.iter_mut().enumerate().take(count) {
+                *value = s15_fixed16(data, 12 + i * 4)?;
+            }
+            let [g, a, b, c, d, e, f] = p;
+
+            Some(match function {
+                0 => x.powf(g),
+                1 if x >= -b / a => (a * x + b).powf(g),
+                1 => 0.,
+                2 if x >= -b / a => (a * x + b).powf(g) + c,
+                2 => c,
+                3 if x >= d => (a * x + b).powf(g),
+                3 => c * x,
+                _ if x >= d => (a * x + b).powf(g) + e,
+                _ => c * x + f,
+            })
+        }
+        _ => None,
+    }
+}
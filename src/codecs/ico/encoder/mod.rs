@@ -0,0 +1,204 @@
+use std::io::Cursor;
+
+use ico::{IconDir, IconDirEntry, IconImage, ResourceType};
+use zune_core::{
+    bit_depth::BitDepth,
+    bytestream::{ZByteWriterTrait, ZWriter},
+    colorspace::ColorSpace,
+};
+use zune_image::{
+    codecs::ImageFormat,
+    errors::{ImageErrors, ImgEncodeErrors},
+    image::Image,
+    traits::{EncoderTrait, OperationsTrait},
+};
+
+use crate::{
+    operations::resize::{Resize, ResizeAlg},
+    options::{check_range, OptionsError, Validate},
+};
+
+/// Advanced options for ICO encoding
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct IcoOptions {
+    /// Width and height of every icon in the file. `1..=256`
+    pub sizes: Vec<u32>,
+    /// Algorithm used to resize the source to every size
+    #[cfg_attr(feature = "serde", serde(with = "crate::options::remote::resize_alg"))]
+    pub algorithm: ResizeAlg,
+    /// Icons larger than this size are stored as PNG, smaller ones as BMP
+    ///
+    /// BMP entries are understood by every ICO reader, PNG entries are much smaller.
+    pub png_threshold: u32,
+}
+
+impl Default for IcoOptions {
+    fn default() -> Self {
+        Self {
+            sizes: vec![16, 32, 48, 64, 256],
+            algorithm: ResizeAlg::default(),
+            png_threshold: 64,
+        }
+    }
+}
+
+impl Validate for IcoOptions {
+    fn validate(&self) -> Result<(), OptionsError> {
+        if self.sizes.is_empty() {
+            return Err(OptionsError::Invalid {
+                option: "sizes",
+                reason: "must not be empty",
+            });
+        }
+
+        self.sizes
+            .iter()
+            .try_for_each(|&size| check_range("size", size, 1..=256))
+    }
+}
+
+/// An ICO encoder
+///
+/// Packs the first frame of the image resized to every size of [`IcoOptions::sizes`]
+/// into one icon. Non-square images are fitted into the square and padded with
+/// transparent pixels.
+#[derive(Default)]
+pub struct IcoEncoder {
+    options: IcoOptions,
+}
+
+impl IcoEncoder {
+    /// Create a new encoder
+    pub fn new() -> IcoEncoder {
+        IcoEncoder::default()
+    }
+
+    /// Create a new encoder with specified options
+    pub fn new_with_options(options: IcoOptions) -> IcoEncoder {
+        IcoEncoder { options }
+    }
+
+    /// Create a new encoder with specified options, rejecting sizes out of range
+    pub fn try_new_with_options(options: IcoOptions) -> Result<IcoEncoder, OptionsError> {
+        options.validate()?;
+
+        Ok(IcoEncoder::new_with_options(options))
+    }
+}
+
+impl EncoderTrait for IcoEncoder {
+    fn name(&self) -> &'static str {
+        "ico"
+    }
+
+    fn encode_inner<T: ZByteWriterTrait>(
+        &mut self,
+        image: &Image,
+        sink: T,
+    ) -> Result<usize, ImageErrors> {
+        if self.options.sizes.is_empty() {
+            return Err(ImageErrors::EncodeErrors(ImgEncodeErrors::GenericStatic(
+                "At least one icon size is required",
+            )));
+        }
+
+        let (width, height) = image.dimensions();
+        let source = Image::from_u8(&image.flatten_to_u8()[0], width, height, ColorSpace::RGBA);
+
+        let mut icon_dir = IconDir::new(ResourceType::Icon);
+
+        for &size in &self.options.sizes {
+            if !(1..=256).contains(&size) {
+                return Err(ImageErrors::EncodeErrors(ImgEncodeErrors::Generic(
+                    format!("Icon size must be in 1..=256 range, got {size}"),
+                )));
+            }
+
+            let icon = IconImage::from_rgba_data(size, size, self.resize(&source, size)?);
+
+            let entry = if size > self.options.png_threshold {
+                IconDirEntry::encode_as_png(&icon)
+            } else {
+                IconDirEntry::encode_as_bmp(&icon)
+            }?;
+
+            icon_dir.add_entry(entry);
+        }
+
+        let mut buf = Cursor::new(vec![]);
+        icon_dir.write(&mut buf)?;
+
+        let mut writer = ZWriter::new(sink);
+
+        writer.write(buf.get_ref()).map_err(|e| {
+            ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
+        })?;
+
+        Ok(writer.bytes_written())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[ColorSpace::RGBA]
+    }
+
+    /// zune-image has no ICO format, [`DynEncoder::format`](crate::codecs::DynEncoder::format)
+    /// reports [`Format::Ico`](crate::Format::Ico)
+    fn format(&self) -> ImageFormat {
+        ImageFormat::Unknown
+    }
+
+    fn supported_bit_depth(&self) -> &'static [BitDepth] {
+        &[BitDepth::Eight]
+    }
+
+    fn default_depth(&self, _depth: BitDepth) -> BitDepth {
+        BitDepth::Eight
+    }
+}
+
+impl IcoEncoder {
+    /// Fit `source` into a square of `size` and return its RGBA pixels
+    fn resize(&self, source: &Image, size: u32) -> Result<Vec<u8>, ImageErrors> {
+        let size = size as usize;
+        let (width, height) = source.dimensions();
+
+        // longer side matches the size, aspect ratio is kept
+        let (fit_width, fit_height) = if width >= height {
+            (size, (height * size / width).max(1))
+        } else {
+            ((width * size / height).max(1), size)
+        };
+
+        let mut image = source.clone();
+
+        if (fit_width, fit_height) != (width, height) {
+            Resize::new(fit_width, fit_height, self.options.algorithm).execute(&mut image)?;
+        }
+
+        let pixels = &image.flatten_to_u8()[0];
+
+        if (fit_width, fit_height) == (size, size) {
+            return Ok(pixels.clone());
+        }
+
+        // center on transparent canvas
+        let (left, top) = ((size - fit_width) / 2, (size - fit_height) / 2);
+        let mut canvas = vec![0; size * size * 4];
+
+        for (y, row) in pixels.chunks_exact(fit_width * 4).enumerate() {
+            let offset = ((top + y) * size + left) * 4;
+
+            canvas[offset..offset + row.len()].copy_from_slice(row);
+        }
+
+        Ok(canvas)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,68 @@
+use zune_core::colorspace::ColorSpace;
+
+use crate::test_utils::*;
+
+use super::*;
+
+fn encode_with(image: &Image, options: IcoOptions) -> Result<IconDir, ImageErrors> {
+    let mut buf = Cursor::new(vec![]);
+    IcoEncoder::new_with_options(options).encode(image, &mut buf)?;
+    buf.set_position(0);
+
+    Ok(IconDir::read(buf)?)
+}
+
+#[test]
+fn encode_sizes() {
+    let image = create_test_image_u8(300, 300, ColorSpace::RGB);
+
+    let icon_dir = encode_with(&image, IcoOptions::default()).unwrap();
+    let entries = icon_dir.entries();
+
+    assert_eq!(
+        entries.iter().map(IconDirEntry::width).collect::<Vec<_>>(),
+        [16, 32, 48, 64, 256]
+    );
+    assert!(!entries[0].is_png());
+    assert!(entries[4].is_png());
+    assert_eq!(entries[4].decode().unwrap().height(), 256);
+}
+
+#[test]
+fn encode_non_square() {
+    let image = create_test_image_u8(64, 32, ColorSpace::RGB);
+
+    let icon_dir = encode_with(
+        &image,
+        IcoOptions {
+            sizes: vec![32],
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let icon = icon_dir.entries()[0].decode().unwrap();
+    let data = icon.rgba_data();
+
+    assert_eq!((icon.width(), icon.height()), (32, 32));
+    // top row is padding, the middle row is the image
+    assert_eq!(data[3], 0);
+    assert_eq!(data[(16 * 32) * 4 + 3], 255);
+}
+
+#[test]
+fn encode_invalid_size() {
+    let image = create_test_image_u8(64, 64, ColorSpace::RGB);
+
+    for sizes in [vec![], vec![512]] {
+        let result = encode_with(
+            &image,
+            IcoOptions {
+                sizes,
+                ..Default::default()
+            },
+        );
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,3 @@
+mod encoder;
+
+pub use encoder::*;
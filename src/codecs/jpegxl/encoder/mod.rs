@@ -0,0 +1,184 @@
+use jpegxl_rs::encode::{EncoderFrame, EncoderResult};
+use zune_core::{
+    bit_depth::BitDepth,
+    bytestream::{ZByteWriterTrait, ZWriter},
+    colorspace::ColorSpace,
+};
+use zune_image::{
+    codecs::ImageFormat,
+    errors::{ImageErrors, ImgEncodeErrors},
+    image::Image,
+    traits::EncoderTrait,
+};
+
+use super::{encode_error, speed};
+use crate::options::{check_range, OptionsError, Validate};
+
+/// Advanced options for JPEG XL encoding
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct JpegXlOptions {
+    /// Encode pixels without loss with the modular mode, `distance` is ignored
+    pub lossless: bool,
+    /// Butteraugli distance of the lossy VarDCT mode, lower is better.
+    /// `1.0` is visually lossless, `0.0` enables the lossless mode. `0.0..=25.0`
+    pub distance: f32,
+    /// Encoder effort, higher is slower and produces smaller files. `1..=10`
+    pub effort: u8,
+}
+
+impl Default for JpegXlOptions {
+    fn default() -> Self {
+        Self {
+            lossless: false,
+            distance: 1.0,
+            effort: 7,
+        }
+    }
+}
+
+impl Validate for JpegXlOptions {
+    fn validate(&self) -> Result<(), OptionsError> {
+        check_range("distance", self.distance, 0.0..=25.0)?;
+        check_range("effort", self.effort, 1..=10)
+    }
+}
+
+impl JpegXlOptions {
+    /// Butteraugli distance equivalent to JPEG-like `quality`, same mapping as `cjxl` uses
+    ///
+    /// Quality `100` is lossless, `90` corresponds to distance `1.0`.
+    pub fn distance_from_quality(quality: f32) -> f32 {
+        if quality >= 100. {
+            0.
+        } else if quality >= 30. {
+            0.1 + (100. - quality) * 0.09
+        } else {
+            53. / 3000. * quality * quality - 23. / 20. * quality + 25.
+        }
+    }
+}
+
+/// A JPEG XL encoder
+///
+/// Uses libjxl, images are encoded with the lossy VarDCT mode
+/// unless [`JpegXlOptions::lossless`] is set.
+#[derive(Default)]
+pub struct JpegXlEncoder {
+    options: JpegXlOptions,
+}
+
+impl JpegXlEncoder {
+    /// Create a new encoder
+    pub fn new() -> JpegXlEncoder {
+        JpegXlEncoder::default()
+    }
+
+    /// Create a new encoder with specified options
+    pub fn new_with_options(options: JpegXlOptions) -> JpegXlEncoder {
+        JpegXlEncoder { options }
+    }
+
+    /// Create a new encoder with specified options, rejecting out of range values
+    pub fn try_new_with_options(options: JpegXlOptions) -> Result<JpegXlEncoder, OptionsError> {
+        options.validate()?;
+
+        Ok(JpegXlEncoder::new_with_options(options))
+    }
+}
+
+impl EncoderTrait for JpegXlEncoder {
+    fn name(&self) -> &'static str {
+        "jpegxl"
+    }
+
+    fn encode_inner<T: ZByteWriterTrait>(
+        &mut self,
+        image: &Image,
+        sink: T,
+    ) -> Result<usize, ImageErrors> {
+        let (width, height) = image.dimensions();
+        let colorspace = image.colorspace();
+
+        #[cfg(feature = "threads")]
+        let runner = jpegxl_rs::ThreadsRunner::default();
+
+        let distance = self.options.distance.clamp(0., 25.);
+        let lossless = self.options.lossless || distance == 0.;
+
+        let mut builder = jpegxl_rs::encoder_builder();
+
+        builder
+            .has_alpha(colorspace.has_alpha())
+            .lossless(lossless)
+            .uses_original_profile(lossless)
+            .quality(if lossless { 0. } else { distance })
+            .speed(speed(self.options.effort));
+
+        #[cfg(feature = "threads")]
+        builder.parallel_runner(&runner);
+
+        let mut encoder = builder.build().map_err(encode_error)?;
+
+        let frame = &image.frames_ref()[0];
+        let channels = colorspace.num_components() as u32;
+
+        macro_rules! encode {
+            ($sample:ty) => {{
+                let data = frame.flatten::<$sample>(colorspace);
+                let frame = EncoderFrame::new(&data).num_channels(channels);
+
+                encoder
+                    .encode_frame::<$sample, $sample>(&frame, width as u32, height as u32)
+                    .map(|result: EncoderResult<$sample>| result.data)
+            }};
+        }
+
+        let data = match image.depth() {
+            BitDepth::Sixteen => encode!(u16),
+            BitDepth::Float32 => encode!(f32),
+            _ => encode!(u8),
+        }
+        .map_err(encode_error)?;
+
+        let mut writer = ZWriter::new(sink);
+
+        writer.write(&data).map_err(|e| {
+            ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
+        })?;
+
+        Ok(writer.bytes_written())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[
+            ColorSpace::Luma,
+            ColorSpace::LumaA,
+            ColorSpace::RGB,
+            ColorSpace::RGBA,
+        ]
+    }
+
+    fn format(&self) -> ImageFormat {
+        ImageFormat::JPEG_XL
+    }
+
+    fn supported_bit_depth(&self) -> &'static [BitDepth] {
+        &[BitDepth::Eight, BitDepth::Sixteen, BitDepth::Float32]
+    }
+
+    fn default_depth(&self, depth: BitDepth) -> BitDepth {
+        match depth {
+            BitDepth::Sixteen => BitDepth::Sixteen,
+            BitDepth::Float32 => BitDepth::Float32,
+            _ => BitDepth::Eight,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
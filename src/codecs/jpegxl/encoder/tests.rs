@@ -0,0 +1,101 @@
+use std::io::Cursor;
+
+use zune_core::colorspace::ColorSpace;
+
+use crate::test_utils::*;
+
+use super::*;
+
+fn encode_with(image: &Image, options: JpegXlOptions) -> Vec<u8> {
+    let mut buf = Cursor::new(vec![]);
+    JpegXlEncoder::new_with_options(options)
+        .encode(image, &mut buf)
+        .unwrap();
+
+    buf.into_inner()
+}
+
+/// Decode JPEG XL `data` into 8-bit samples
+fn decode(data: &[u8]) -> (jpegxl_rs::decode::Metadata, Vec<u8>) {
+    let decoder = jpegxl_rs::decoder_builder().build().unwrap();
+
+    decoder.decode_with::<u8>(data).unwrap()
+}
+
+#[test]
+fn encode_colorspaces() {
+    for colorspace in [
+        ColorSpace::Luma,
+        ColorSpace::LumaA,
+        ColorSpace::RGB,
+        ColorSpace::RGBA,
+    ] {
+        let image = create_test_image_u8(200, 200, colorspace);
+
+        let data = encode_with(&image, JpegXlOptions::default());
+        let (metadata, _) = decode(&data);
+
+        assert_eq!(
+            (metadata.width, metadata.height),
+            (200, 200),
+            "{colorspace:?}"
+        );
+        assert_eq!(
+            metadata.has_alpha_channel,
+            colorspace.has_alpha(),
+            "{colorspace:?}"
+        );
+    }
+}
+
+#[test]
+fn encode_high_depth() {
+    for image in [
+        create_test_image_u16(200, 200, ColorSpace::RGB),
+        create_test_image_f32(200, 200, ColorSpace::RGB),
+    ] {
+        let result = JpegXlEncoder::new().encode(&image, Cursor::new(vec![]));
+
+        assert!(result.is_ok());
+    }
+}
+
+#[test]
+fn encode_lossless() {
+    let image = create_test_image_u8(64, 64, ColorSpace::RGB);
+
+    let data = encode_with(
+        &image,
+        JpegXlOptions {
+            lossless: true,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(decode(&data).1, image.flatten_to_u8()[0]);
+}
+
+#[test]
+fn encode_distance() {
+    let image = create_test_image_u8(200, 200, ColorSpace::RGB);
+
+    let encode = |distance| {
+        encode_with(
+            &image,
+            JpegXlOptions {
+                distance,
+                ..Default::default()
+            },
+        )
+        .len()
+    };
+
+    assert!(encode(4.0) < encode(0.5));
+}
+
+#[test]
+fn distance_from_quality() {
+    assert_eq!(JpegXlOptions::distance_from_quality(100.), 0.);
+    assert!((JpegXlOptions::distance_from_quality(90.) - 1.0).abs() < 1e-4);
+    assert!(JpegXlOptions::distance_from_quality(10.) > JpegXlOptions::distance_from_quality(50.));
+}
@@ -0,0 +1,28 @@
+mod encoder;
+mod transcoder;
+
+pub use encoder::*;
+pub use transcoder::*;
+
+use jpegxl_rs::encode::EncoderSpeed;
+use zune_image::errors::{ImageErrors, ImgEncodeErrors};
+
+/// Encoder speed matching the `effort` level, which is clamped to `1..=10`
+fn speed(effort: u8) -> EncoderSpeed {
+    match effort {
+        0 | 1 => EncoderSpeed::Lightning,
+        2 => EncoderSpeed::Thunder,
+        3 => EncoderSpeed::Falcon,
+        4 => EncoderSpeed::Cheetah,
+        5 => EncoderSpeed::Hare,
+        6 => EncoderSpeed::Wombat,
+        7 => EncoderSpeed::Squirrel,
+        8 => EncoderSpeed::Kitten,
+        9 => EncoderSpeed::Tortoise,
+        _ => EncoderSpeed::Glacier,
+    }
+}
+
+fn encode_error(e: jpegxl_rs::EncodeError) -> ImageErrors {
+    ImageErrors::EncodeErrors(ImgEncodeErrors::Generic(e.to_string()))
+}
@@ -0,0 +1,83 @@
+use zune_image::errors::ImageErrors;
+
+use super::{encode_error, speed};
+use crate::options::{check_range, OptionsError, Validate};
+
+/// Options for lossless JPEG recompression
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct JpegXlTranscoderOptions {
+    /// Encoder effort, higher is slower and produces smaller files. `1..=10`
+    pub effort: u8,
+}
+
+impl Default for JpegXlTranscoderOptions {
+    fn default() -> Self {
+        Self { effort: 7 }
+    }
+}
+
+impl Validate for JpegXlTranscoderOptions {
+    fn validate(&self) -> Result<(), OptionsError> {
+        check_range("effort", self.effort, 1..=10)
+    }
+}
+
+/// Lossless JPEG to JPEG XL recompressor
+///
+/// DCT coefficients of the source are stored as is along with the reconstruction data,
+/// so the original JPEG file can be restored bit-exact from the output.
+#[derive(Default)]
+pub struct JpegXlTranscoder {
+    options: JpegXlTranscoderOptions,
+}
+
+impl JpegXlTranscoder {
+    /// Create a new transcoder
+    pub fn new() -> JpegXlTranscoder {
+        JpegXlTranscoder::default()
+    }
+
+    /// Create a new transcoder with specified options
+    pub fn new_with_options(options: JpegXlTranscoderOptions) -> JpegXlTranscoder {
+        JpegXlTranscoder { options }
+    }
+
+    /// Create a new transcoder with specified options, rejecting out of range values
+    pub fn try_new_with_options(
+        options: JpegXlTranscoderOptions,
+    ) -> Result<JpegXlTranscoder, OptionsError> {
+        options.validate()?;
+
+        Ok(JpegXlTranscoder::new_with_options(options))
+    }
+
+    /// Losslessly recompress JPEG file `data` into JPEG XL
+    pub fn transcode(&self, data: &[u8]) -> Result<Vec<u8>, ImageErrors> {
+        #[cfg(feature = "threads")]
+        let runner = jpegxl_rs::ThreadsRunner::default();
+
+        let mut builder = jpegxl_rs::encoder_builder();
+
+        // reconstruction data is stored in a box of the container
+        builder
+            .use_container(true)
+            .speed(speed(self.options.effort));
+
+        #[cfg(feature = "threads")]
+        builder.parallel_runner(&runner);
+
+        let mut encoder = builder.build().map_err(encode_error)?;
+
+        let result = encoder.encode_jpeg(data).map_err(encode_error)?;
+
+        Ok(result.data)
+    }
+}
+
+#[cfg(test)]
+mod tests;
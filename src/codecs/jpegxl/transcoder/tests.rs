@@ -0,0 +1,29 @@
+use jpegxl_rs::decode::Data;
+
+use super::*;
+
+#[test]
+fn transcode_reconstruct() {
+    let data = std::fs::read("tests/files/jpg/f1t.jpg").unwrap();
+
+    let transcoded = JpegXlTranscoder::new().transcode(&data).unwrap();
+
+    assert!(transcoded.len() < data.len());
+
+    let decoder = jpegxl_rs::decoder_builder().build().unwrap();
+    let (metadata, reconstructed) = decoder.reconstruct(&transcoded).unwrap();
+
+    assert_eq!((metadata.width, metadata.height), (48, 80));
+
+    match reconstructed {
+        Data::Jpeg(jpeg) => assert_eq!(jpeg, data),
+        Data::Pixels(_) => panic!("JPEG reconstruction data is missing"),
+    }
+}
+
+#[test]
+fn transcode_invalid() {
+    let result = JpegXlTranscoder::new().transcode(b"not a jpeg");
+
+    assert!(result.is_err());
+}
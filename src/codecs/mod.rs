@@ -2,7 +2,19 @@
 #[cfg(feature = "avif")]
 pub mod avif;
 
-/// MozJpeg encoding support
+/// GIF encoding and decoding support
+#[cfg(feature = "gif")]
+pub mod gif;
+
+/// ICO encoding support
+#[cfg(feature = "ico")]
+pub mod ico;
+
+/// JPEG XL encoding and lossless JPEG recompression support
+#[cfg(feature = "jpegxl")]
+pub mod jpegxl;
+
+/// MozJpeg encoding and decoding support
 #[cfg(feature = "mozjpeg")]
 pub mod mozjpeg;
 
@@ -10,6 +22,22 @@ pub mod mozjpeg;
 #[cfg(feature = "oxipng")]
 pub mod oxipng;
 
+/// Camera RAW and DNG decoding support
+#[cfg(feature = "raw")]
+pub mod raw;
+
+/// Encoders chosen at runtime behind a common object-safe trait
+pub mod dynamic;
+
+pub use self::dynamic::{DynEncoder, EncoderConfig};
+
+/// Format detection and decoding of any supported format
+pub mod registry;
+
+/// DDS and KTX2 GPU texture encoding support
+#[cfg(feature = "texture")]
+pub mod texture;
+
 /// Tiff encoding support
 #[cfg(feature = "tiff")]
 pub mod tiff;
@@ -17,3 +45,85 @@ pub mod tiff;
 /// WebP encoding support
 #[cfg(feature = "webp")]
 pub mod webp;
+
+#[cfg(any(feature = "mozjpeg", feature = "oxipng"))]
+mod icc;
+
+/// 8-bit image stored as indices into a palette of RGBA colors
+///
+/// Produced by `Quantize::quantize` and written as is by encoders supporting indexed output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedPixels {
+    /// RGBA colors shared by all frames
+    pub palette: Vec<[u8; 4]>,
+    /// Palette index of every pixel, one vector per frame
+    pub frames: Vec<Vec<u8>>,
+}
+
+/// Display time of the `frame` in milliseconds
+///
+/// Frame durations are stored as `numerator / denominator` seconds,
+/// frames without duration are displayed for 100 ms.
+#[cfg(any(
+    feature = "avif",
+    feature = "gif",
+    feature = "oxipng",
+    feature = "webp"
+))]
+pub(crate) fn frame_duration_ms(frame: &zune_image::frame::Frame) -> u32 {
+    let (numerator, denominator) = (frame.numerator(), frame.denominator());
+
+    if numerator == 0 || denominator == 0 {
+        return 100;
+    }
+
+    (numerator * 1000 / denominator) as u32
+}
+
+/// ICC profile, EXIF and XMP blocks written by encoders
+#[cfg(any(feature = "avif", feature = "webp"))]
+#[derive(Default)]
+pub(crate) struct MetadataBlocks {
+    pub(crate) icc: Option<Vec<u8>>,
+    /// EXIF fields serialized into a TIFF structure
+    pub(crate) exif: Option<Vec<u8>>,
+    pub(crate) xmp: Option<Vec<u8>>,
+}
+
+#[cfg(any(feature = "avif", feature = "webp"))]
+impl MetadataBlocks {
+    /// Collect metadata of the `image` along with the `xmp` packet
+    ///
+    /// EXIF is written only with `metadata` feature enabled.
+    pub(crate) fn from_image(image: &zune_image::image::Image, xmp: Option<&[u8]>) -> Self {
+        Self {
+            icc: image.metadata().icc_chunk().cloned(),
+            #[cfg(feature = "metadata")]
+            exif: image.metadata().exif().and_then(|fields| {
+                use exif::experimental::Writer;
+
+                let mut writer = Writer::new();
+                let mut buf = std::io::Cursor::new(vec![]);
+
+                for field in fields {
+                    writer.push_field(field);
+                }
+
+                let result = writer.write(&mut buf, false);
+                if result.is_ok() {
+                    Some(buf.into_inner())
+                } else {
+                    log::warn!("Writing exif failed {:?}", result);
+                    None
+                }
+            }),
+            #[cfg(not(feature = "metadata"))]
+            exif: None,
+            xmp: xmp.map(<[u8]>::to_vec),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.icc.is_none() && self.exif.is_none() && self.xmp.is_none()
+    }
+}
@@ -0,0 +1,238 @@
+use std::{io::Read, marker::PhantomData, mem, panic::AssertUnwindSafe};
+
+use mozjpeg::{Decompress, Marker};
+use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
+use zune_image::{errors::ImageErrors, image::Image, traits::DecoderTrait};
+
+use super::{EXIF_MARKER, ICC_MARKER};
+use crate::{Format, ImageInfo};
+
+/// DCT-domain scaling applied while decoding
+///
+/// libjpeg can skip the inverse DCT for high frequency coefficients, which makes
+/// decoding at a reduced size considerably faster than decoding at full size and
+/// resizing afterwards.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeScale {
+    /// Decode at full resolution
+    #[default]
+    Full,
+    /// Decode at 1/2 of the resolution
+    Half,
+    /// Decode at 1/4 of the resolution
+    Quarter,
+    /// Decode at 1/8 of the resolution
+    Eighth,
+}
+
+impl DecodeScale {
+    /// Pick the smallest scale that still produces an image at least `width`x`height` large
+    /// from the image with `src_width`x`src_height` dimensions
+    pub fn for_target(src_width: usize, src_height: usize, width: usize, height: usize) -> Self {
+        [Self::Eighth, Self::Quarter, Self::Half]
+            .into_iter()
+            .find(|scale| {
+                let (w, h) = scale.scaled(src_width, src_height);
+                w >= width && h >= height
+            })
+            .unwrap_or(Self::Full)
+    }
+
+    /// Dimensions of the image with `width`x`height` dimensions after scaling
+    pub fn scaled(self, width: usize, height: usize) -> (usize, usize) {
+        let denom = 8 / self.numerator() as usize;
+
+        (width.div_ceil(denom), height.div_ceil(denom))
+    }
+
+    fn numerator(self) -> u8 {
+        match self {
+            Self::Full => 8,
+            Self::Half => 4,
+            Self::Quarter => 2,
+            Self::Eighth => 1,
+        }
+    }
+}
+
+/// Advanced options for MozJpeg decoding
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MozJpegDecoderOptions {
+    /// Scale applied during decoding
+    pub scale: DecodeScale,
+}
+
+/// A MozJpeg decoder
+pub struct MozJpegDecoder<R: Read> {
+    inner: Vec<u8>,
+    options: MozJpegDecoderOptions,
+    dimensions: (usize, usize),
+    colorspace: ColorSpace,
+    phantom: PhantomData<R>,
+}
+
+const ADOBE_MARKER: &[u8] = b"Adobe";
+
+impl<R: Read> MozJpegDecoder<R> {
+    /// Create a new jpeg decoder that reads data from `source`
+    pub fn try_new(source: R) -> Result<MozJpegDecoder<R>, ImageErrors> {
+        Self::try_new_with_options(source, MozJpegDecoderOptions::default())
+    }
+
+    /// Create a new jpeg decoder with specified options that reads data from `source`
+    pub fn try_new_with_options(
+        mut source: R,
+        options: MozJpegDecoderOptions,
+    ) -> Result<MozJpegDecoder<R>, ImageErrors> {
+        let mut buf = Vec::new();
+        source.read_to_end(&mut buf)?;
+
+        let (dimensions, colorspace) = catch_decode_panic(|| {
+            let dinfo = Decompress::new_mem(&buf)?;
+
+            Ok((
+                options.scale.scaled(dinfo.width(), dinfo.height()),
+                out_colorspace(dinfo.color_space()),
+            ))
+        })?;
+
+        Ok(MozJpegDecoder {
+            inner: buf,
+            options,
+            dimensions,
+            colorspace,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Image information read from the headers
+    ///
+    /// Dimensions account for the decoding scale.
+    pub fn info(&self) -> ImageInfo {
+        ImageInfo {
+            format: Format::Jpeg,
+            dimensions: self.dimensions,
+            colorspace: self.colorspace,
+            depth: BitDepth::Eight,
+            frame_count: 1,
+            has_alpha: false,
+        }
+    }
+}
+
+impl<R> DecoderTrait for MozJpegDecoder<R>
+where
+    R: Read,
+{
+    fn decode(&mut self) -> Result<Image, ImageErrors> {
+        let colorspace = self.colorspace;
+        let scale = self.options.scale;
+
+        let (image, dimensions) = catch_decode_panic(|| {
+            let mut dinfo =
+                Decompress::with_markers(&[Marker::APP(1), Marker::APP(2), Marker::APP(14)])
+                    .from_mem(&self.inner)?;
+
+            dinfo.scale(scale.numerator());
+
+            let mut exif = None;
+            let mut icc_chunks = vec![];
+            let mut adobe = false;
+
+            for marker in dinfo.markers() {
+                match marker.marker {
+                    Marker::APP(1) if marker.data.starts_with(EXIF_MARKER) => {
+                        exif = Some(marker.data[EXIF_MARKER.len()..].to_vec());
+                    }
+                    Marker::APP(2) if marker.data.starts_with(ICC_MARKER) => {
+                        let data = &marker.data[ICC_MARKER.len()..];
+
+                        if data.len() > 2 {
+                            icc_chunks.push((data[0], data[2..].to_vec()));
+                        }
+                    }
+                    Marker::APP(14) if marker.data.starts_with(ADOBE_MARKER) => {
+                        adobe = true;
+                    }
+                    _ => {}
+                }
+            }
+
+            let mut dinfo = dinfo.to_colorspace(match colorspace {
+                ColorSpace::Luma => mozjpeg::ColorSpace::JCS_GRAYSCALE,
+                ColorSpace::CMYK => mozjpeg::ColorSpace::JCS_CMYK,
+                _ => mozjpeg::ColorSpace::JCS_RGB,
+            })?;
+
+            let (width, height) = (dinfo.width(), dinfo.height());
+            let mut data = dinfo.read_scanlines::<u8>()?;
+            dinfo.finish()?;
+
+            // Adobe writes CMYK and YCCK JPEGs with inverted values
+            if adobe && colorspace == ColorSpace::CMYK {
+                data.iter_mut().for_each(|v| *v = 255 - *v);
+            }
+
+            let mut image = Image::from_u8(&data, width, height, colorspace);
+
+            if !icc_chunks.is_empty() {
+                icc_chunks.sort_by_key(|(seq, _)| *seq);
+
+                image.metadata_mut().set_icc_chunk(
+                    icc_chunks
+                        .into_iter()
+                        .flat_map(|(_, chunk)| chunk)
+                        .collect(),
+                );
+            }
+
+            #[cfg(feature = "metadata")]
+            if let Some(exif) = exif {
+                image.metadata_mut().parse_raw_exif(&exif);
+            }
+            #[cfg(not(feature = "metadata"))]
+            let _ = exif;
+
+            Ok((image, (width, height)))
+        })?;
+
+        self.dimensions = dimensions;
+
+        Ok(image)
+    }
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        Some(self.dimensions)
+    }
+
+    fn out_colorspace(&self) -> ColorSpace {
+        self.colorspace
+    }
+
+    fn name(&self) -> &'static str {
+        "mozjpeg-decoder"
+    }
+}
+
+fn out_colorspace(colorspace: mozjpeg::ColorSpace) -> ColorSpace {
+    match colorspace {
+        mozjpeg::ColorSpace::JCS_GRAYSCALE => ColorSpace::Luma,
+        mozjpeg::ColorSpace::JCS_CMYK | mozjpeg::ColorSpace::JCS_YCCK => ColorSpace::CMYK,
+        _ => ColorSpace::RGB,
+    }
+}
+
+fn catch_decode_panic<T>(f: impl FnOnce() -> Result<T, std::io::Error>) -> Result<T, ImageErrors> {
+    std::panic::catch_unwind(AssertUnwindSafe(f))
+        .map_err(|err| {
+            if let Ok(mut err) = err.downcast::<String>() {
+                ImageErrors::ImageDecodeErrors(mem::take(&mut *err))
+            } else {
+                ImageErrors::ImageDecodeErrors("Unknown error occurred during decoding".to_string())
+            }
+        })?
+        .map_err(|e| ImageErrors::ImageDecodeErrors(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests;
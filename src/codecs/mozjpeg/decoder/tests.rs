@@ -0,0 +1,60 @@
+use std::fs::File;
+
+use super::*;
+
+#[test]
+fn decode() {
+    let file_content = File::open("tests/files/jpg/f1t.jpg").unwrap();
+
+    let decoder = MozJpegDecoder::try_new(file_content).unwrap();
+
+    let img = Image::from_decoder(decoder).unwrap();
+
+    assert_eq!(img.dimensions(), (48, 80));
+    assert_eq!(img.colorspace(), ColorSpace::RGB);
+}
+
+#[test]
+fn decode_scaled() {
+    let file_content = File::open("tests/files/jpg/f1t.jpg").unwrap();
+
+    let decoder = MozJpegDecoder::try_new_with_options(
+        file_content,
+        MozJpegDecoderOptions {
+            scale: DecodeScale::Quarter,
+        },
+    )
+    .unwrap();
+
+    let img = Image::from_decoder(decoder).unwrap();
+
+    assert_eq!(img.dimensions(), (12, 20));
+}
+
+#[cfg(feature = "metadata")]
+#[test]
+fn decode_exif() {
+    let file_content = File::open("tests/files/exif/f6t.jpg").unwrap();
+
+    let decoder = MozJpegDecoder::try_new(file_content).unwrap();
+
+    let img = Image::from_decoder(decoder).unwrap();
+
+    assert!(img.metadata().exif().is_some());
+}
+
+#[test]
+fn scale_for_target() {
+    assert_eq!(
+        DecodeScale::for_target(800, 600, 100, 75),
+        DecodeScale::Eighth
+    );
+    assert_eq!(
+        DecodeScale::for_target(800, 600, 200, 100),
+        DecodeScale::Quarter
+    );
+    assert_eq!(
+        DecodeScale::for_target(800, 600, 500, 300),
+        DecodeScale::Full
+    );
+}
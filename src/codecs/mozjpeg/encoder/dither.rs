@@ -0,0 +1,125 @@
+use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
+use zune_image::{frame::Frame, image::Image};
+
+use super::Dithering;
+
+/// 8x8 Bayer threshold matrix
+const BAYER: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+/// Flatten `frame` into 8-bit samples, reducing high bit depth with `dithering`
+///
+/// Alpha is only rounded, JPEG doesn't store it.
+pub(super) fn to_u8(image: &Image, frame: &Frame, dithering: Dithering) -> Vec<u8> {
+    let colorspace = image.colorspace();
+
+    // samples scaled to 0..=255 range
+    let data: Vec<f32> = match image.depth() {
+        BitDepth::Sixteen => frame
+            .flatten::<u16>(colorspace)
+            .into_iter()
+            .map(|v| f32::from(v) / 257.)
+            .collect(),
+        BitDepth::Float32 => frame
+            .flatten::<f32>(colorspace)
+            .into_iter()
+            .map(|v| v.clamp(0., 1.) * 255.)
+            .collect(),
+        _ => return frame.flatten::<u8>(colorspace),
+    };
+
+    let width = image.dimensions().0;
+    let channels = colorspace.num_components();
+
+    let alpha = match colorspace {
+        ColorSpace::ARGB => Some(0),
+        cs if cs.has_alpha() => Some(channels - 1),
+        _ => None,
+    };
+
+    match dithering {
+        Dithering::None => data.into_iter().map(|v| v.round() as u8).collect(),
+        Dithering::Ordered => ordered(&data, width, channels, alpha),
+        Dithering::ErrorDiffusion => error_diffusion(data, width, channels, alpha),
+    }
+}
+
+/// Offset every pixel by its Bayer threshold, all color channels of a pixel share it
+fn ordered(data: &[f32], width: usize, channels: usize, alpha: Option<usize>) -> Vec<u8> {
+    data.chunks_exact(channels)
+        .enumerate()
+        .flat_map(|(index, pixel)| {
+            let (x, y) = (index % width, index / width);
+            let threshold = (f32::from(BAYER[y % 8][x % 8]) + 0.5) / 64.;
+
+            pixel
+                .iter()
+                .enumerate()
+                .map(move |(c, v)| match Some(c) == alpha {
+                    true => v.round() as u8,
+                    false => (v + threshold).floor().clamp(0., 255.) as u8,
+                })
+        })
+        .collect()
+}
+
+/// Floyd-Steinberg dithering, rows are scanned in alternating directions
+fn error_diffusion(
+    mut data: Vec<f32>,
+    width: usize,
+    channels: usize,
+    alpha: Option<usize>,
+) -> Vec<u8> {
+    let stride = width * channels;
+    let height = data.len() / stride.max(1);
+
+    let mut out = vec![0; data.len()];
+
+    for y in 0..height {
+        let reverse = y % 2 == 1;
+
+        for i in 0..width {
+            let x = if reverse { width - 1 - i } else { i };
+            // neighbours ahead and behind in the scan direction
+            let (ahead, behind) = if reverse {
+                (x.checked_sub(1), Some(x + 1))
+            } else {
+                (Some(x + 1), x.checked_sub(1))
+            };
+
+            for c in 0..channels {
+                let index = y * stride + x * channels + c;
+
+                if Some(c) == alpha {
+                    out[index] = data[index].round() as u8;
+                    continue;
+                }
+
+                let value = data[index].clamp(0., 255.).round();
+                let error = data[index] - value;
+                out[index] = value as u8;
+
+                let mut spread = |x: Option<usize>, y: usize, weight: f32| {
+                    if let Some(x) = x.filter(|&x| x < width && y < height) {
+                        data[y * stride + x * channels + c] += error * weight;
+                    }
+                };
+
+                spread(ahead, y, 7. / 16.);
+                spread(behind, y + 1, 3. / 16.);
+                spread(Some(x), y + 1, 5. / 16.);
+                spread(ahead, y + 1, 1. / 16.);
+            }
+        }
+    }
+
+    out
+}
@@ -1,10 +1,92 @@
-use std::{io, mem, panic::AssertUnwindSafe};
+use std::{
+    mem,
+    os::raw::{c_int, c_uint},
+    panic::AssertUnwindSafe,
+    ptr, slice,
+    str::FromStr,
+};
+
+use mozjpeg::{qtable::QTable, ColorSpaceExt};
+use mozjpeg_sys::*;
+use zune_core::{
+    bit_depth::BitDepth,
+    bytestream::{ZByteWriterTrait, ZWriter},
+    colorspace::ColorSpace,
+};
+use zune_image::{
+    codecs::ImageFormat,
+    errors::{ImageErrors, ImgEncodeErrors},
+    image::Image,
+    traits::EncoderTrait,
+};
+
+use super::{ffi::Compress, ICC_MARKER};
+use crate::{
+    codecs::icc,
+    options::{check_range, OptionsError, Validate},
+};
+
+mod dither;
+
+/// Largest ICC profile part fitting into one APP2 marker along with its header
+const ICC_CHUNK_SIZE: usize = 65519;
+
+/// Interval between restart markers
+///
+/// Restart markers let decoders resynchronize after corrupted data,
+/// at the cost of a slightly larger file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum RestartInterval {
+    /// Every given number of MCU rows
+    Rows(u16),
+    /// Every given number of MCU blocks
+    Blocks(u16),
+}
+
+impl FromStr for RestartInterval {
+    type Err = String;
+
+    /// Parse `cjpeg` restart interval, in MCU rows or in blocks with `B` suffix
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |_| format!("Invalid restart interval '{s}', expected ROWS or BLOCKSB");
+
+        match s.strip_suffix(['b', 'B']) {
+            Some(blocks) => blocks.parse().map(RestartInterval::Blocks).map_err(error),
+            None => s.parse().map(RestartInterval::Rows).map_err(error),
+        }
+    }
+}
 
-use mozjpeg::qtable::QTable;
-use zune_core::{bit_depth::BitDepth, bytestream::ZByteWriterTrait, colorspace::ColorSpace};
-use zune_image::{codecs::ImageFormat, errors::ImageErrors, image::Image, traits::EncoderTrait};
+/// Dithering used to reduce high bit depth input to 8 bits
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Dithering {
+    /// Round samples to the nearest value
+    #[default]
+    None,
+    /// Add an 8x8 Bayer pattern, fast and stable between frames
+    Ordered,
+    /// Floyd-Steinberg error diffusion, smoothest gradients
+    ErrorDiffusion,
+}
 
 /// Advanced options for MozJpeg encoding
+///
+/// Explicit quantization tables are not serialized, use `qtable_index` in configs.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct MozJpegOptions {
     /// Quality, values 60-80 are recommended. `1..=100`
     pub quality: f32,
@@ -15,14 +97,45 @@ pub struct MozJpegOptions {
     /// If `1..=100` (non-zero), it will use MozJPEG's smoothing.
     pub smoothing: u8,
     /// Set color space of JPEG being written, different from input color space
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::options::remote::jpeg_color_space")
+    )]
     pub color_space: mozjpeg::ColorSpace,
     /// Specifies whether multiple scans should be considered during trellis quantization.
     pub trellis_multipass: bool,
-    /// Sets chroma subsampling, leave as `None` to use auto subsampling
-    pub chroma_subsample: Option<u8>,
+    /// Sets chroma subsampling as horizontal and vertical factors in `1..=4`,
+    /// leave as `None` to use auto subsampling
+    ///
+    /// `(2, 1)` is 4:2:2, `(1, 2)` is 4:4:0 and `(2, 2)` is 4:2:0.
+    pub chroma_subsample: Option<(u8, u8)>,
+    /// Write restart markers, leave as `None` to write none
+    pub restart_interval: Option<RestartInterval>,
+    /// How DC coefficients are split into scans of progressive image
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::options::remote::jpeg_scan_mode")
+    )]
+    pub dc_scan_mode: mozjpeg::ScanMode,
+    /// Use trellis quantization of AC coefficients
+    pub trellis: bool,
+    /// Use trellis quantization of DC coefficients
+    pub trellis_dc: bool,
+    /// Reduce ringing artifacts on black text over white background
+    pub overshoot_deringing: bool,
+    /// Base quantization tables built into MozJpeg, scaled by quality. `0..=8`
+    ///
+    /// 0 - JPEG Annex K, 1 - Flat, 2 - MSSIM tuned, 3 - ImageMagick (default),
+    /// 4 - PSNR-HVS-M tuned, 5 - Klein, Silverstein and Carney, 6 - Watson, Taylor
+    /// and Borthwick, 7 - Ahumada, Watson and Peterson, 8 - Peterson, Ahumada and Watson.
+    pub qtable_index: Option<u8>,
+    /// Dithering of 16-bit and floating point input, which is reduced to 8 bits
+    pub dithering: Dithering,
     /// Instead of quality setting, use a specific quantization table.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub luma_qtable: Option<QTable>,
     /// Instead of quality setting, use a specific quantization table for color.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub chroma_qtable: Option<QTable>,
 }
 
@@ -32,37 +145,6 @@ pub struct MozJpegEncoder {
     options: MozJpegOptions,
 }
 
-struct TempVt<T: ZByteWriterTrait> {
-    inner: T,
-    bytes_written: usize,
-}
-impl<T: ZByteWriterTrait> io::Write for TempVt<T> {
-    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        let bytes_written = self.inner.write_bytes(buf).map_err(|e| match e {
-            zune_core::bytestream::ZByteIoError::StdIoError(e) => e,
-            e => io::Error::other(format!("{e:?}")),
-        })?;
-        self.bytes_written += bytes_written;
-        Ok(bytes_written)
-    }
-
-    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
-        self.inner.write_all_bytes(buf).map_err(|e| match e {
-            zune_core::bytestream::ZByteIoError::StdIoError(e) => e,
-            e => io::Error::other(format!("{e:?}")),
-        })?;
-        self.bytes_written += buf.len();
-        Ok(())
-    }
-
-    fn flush(&mut self) -> io::Result<()> {
-        self.inner.flush_bytes().map_err(|e| match e {
-            zune_core::bytestream::ZByteIoError::StdIoError(e) => e,
-            e => io::Error::other(format!("{e:?}")),
-        })
-    }
-}
-
 impl Default for MozJpegOptions {
     fn default() -> Self {
         Self {
@@ -73,12 +155,52 @@ impl Default for MozJpegOptions {
             color_space: mozjpeg::ColorSpace::JCS_YCbCr,
             trellis_multipass: false,
             chroma_subsample: None,
+            restart_interval: None,
+            dc_scan_mode: mozjpeg::ScanMode::AllComponentsTogether,
+            trellis: true,
+            trellis_dc: true,
+            overshoot_deringing: true,
+            qtable_index: None,
+            dithering: Dithering::None,
             luma_qtable: None,
             chroma_qtable: None,
         }
     }
 }
 
+impl Validate for MozJpegOptions {
+    fn validate(&self) -> Result<(), OptionsError> {
+        check_range("quality", self.quality, 1.0..=100.0)?;
+        check_range("smoothing", self.smoothing, 0..=100)?;
+
+        if let Some((horizontal, vertical)) = self.chroma_subsample {
+            check_range("horizontal subsampling", horizontal, 1..=4)?;
+            check_range("vertical subsampling", vertical, 1..=4)?;
+        }
+
+        if let Some(index) = self.qtable_index {
+            check_range("qtable_index", index, 0..=8)?;
+        }
+
+        // extended RGB layouts describe input pixels and can't be written
+        if !matches!(
+            self.color_space,
+            mozjpeg::ColorSpace::JCS_YCbCr
+                | mozjpeg::ColorSpace::JCS_RGB
+                | mozjpeg::ColorSpace::JCS_GRAYSCALE
+                | mozjpeg::ColorSpace::JCS_CMYK
+                | mozjpeg::ColorSpace::JCS_YCCK
+        ) {
+            return Err(OptionsError::Invalid {
+                option: "color_space",
+                reason: "must be YCbCr, RGB, grayscale, CMYK or YCCK",
+            });
+        }
+
+        Ok(())
+    }
+}
+
 impl MozJpegEncoder {
     /// Create a new encoder
     pub fn new() -> MozJpegEncoder {
@@ -89,51 +211,58 @@ impl MozJpegEncoder {
     pub fn new_with_options(options: MozJpegOptions) -> MozJpegEncoder {
         MozJpegEncoder { options }
     }
-}
 
-impl EncoderTrait for MozJpegEncoder {
-    fn name(&self) -> &'static str {
-        "mozjpeg-encoder"
+    /// Create a new encoder with specified options, rejecting out of range values
+    pub fn try_new_with_options(options: MozJpegOptions) -> Result<MozJpegEncoder, OptionsError> {
+        options.validate()?;
+
+        Ok(MozJpegEncoder::new_with_options(options))
     }
 
-    fn encode_inner<T: ZByteWriterTrait>(
-        &mut self,
-        image: &Image,
-        sink: T,
-    ) -> Result<usize, ImageErrors> {
+    unsafe fn compress(&self, image: &Image) -> Vec<u8> {
         let (width, height) = image.dimensions();
-        let data = &image.flatten_to_u8()[0];
-
-        let luma_qtable = self.options.luma_qtable.as_ref();
-        let chroma_qtable = self.options.chroma_qtable.as_ref();
-
-        std::panic::catch_unwind(AssertUnwindSafe(|| -> Result<usize, ImageErrors> {
-            let format = match image.colorspace() {
-                ColorSpace::RGB => mozjpeg::ColorSpace::JCS_RGB,
-                ColorSpace::RGBA => mozjpeg::ColorSpace::JCS_EXT_RGBA,
-                ColorSpace::YCbCr => mozjpeg::ColorSpace::JCS_YCbCr,
-                ColorSpace::Luma => mozjpeg::ColorSpace::JCS_GRAYSCALE,
-                ColorSpace::YCCK => mozjpeg::ColorSpace::JCS_YCCK,
-                ColorSpace::CMYK => mozjpeg::ColorSpace::JCS_CMYK,
-                ColorSpace::BGR => mozjpeg::ColorSpace::JCS_EXT_BGR,
-                ColorSpace::BGRA => mozjpeg::ColorSpace::JCS_EXT_BGRA,
-                ColorSpace::ARGB => mozjpeg::ColorSpace::JCS_EXT_ARGB,
-                ColorSpace::Unknown => mozjpeg::ColorSpace::JCS_UNKNOWN,
-                _ => mozjpeg::ColorSpace::JCS_UNKNOWN,
-            };
-
-            let mut comp = mozjpeg::Compress::new(format);
-
-            comp.set_size(width, height);
-            comp.set_quality(self.options.quality);
-
-            if self.options.progressive {
-                comp.set_progressive_mode();
-            }
+        let data = &dither::to_u8(image, &image.frames_ref()[0], self.options.dithering);
+
+        let format = match image.colorspace() {
+            ColorSpace::RGB => mozjpeg::ColorSpace::JCS_RGB,
+            ColorSpace::RGBA => mozjpeg::ColorSpace::JCS_EXT_RGBA,
+            ColorSpace::YCbCr => mozjpeg::ColorSpace::JCS_YCbCr,
+            ColorSpace::Luma => mozjpeg::ColorSpace::JCS_GRAYSCALE,
+            ColorSpace::YCCK => mozjpeg::ColorSpace::JCS_YCCK,
+            ColorSpace::CMYK => mozjpeg::ColorSpace::JCS_CMYK,
+            ColorSpace::BGR => mozjpeg::ColorSpace::JCS_EXT_BGR,
+            ColorSpace::BGRA => mozjpeg::ColorSpace::JCS_EXT_BGRA,
+            ColorSpace::ARGB => mozjpeg::ColorSpace::JCS_EXT_ARGB,
+            ColorSpace::Unknown => mozjpeg::ColorSpace::JCS_UNKNOWN,
+            _ => mozjpeg::ColorSpace::JCS_UNKNOWN,
+        };
+
+        let mut comp = Compress::new();
+        let info = &mut *comp.info;
+
+        info.in_color_space = format;
+        info.input_components = format.num_components() as c_int;
+        jpeg_set_defaults(info);
+
+        info.image_width = width as JDIMENSION;
+        info.image_height = height as JDIMENSION;
+
+        // both are reset by jpeg_set_defaults, base table is read by
+        // jpeg_set_quality and scan mode by jpeg_simple_progression
+        if let Some(index) = self.options.qtable_index {
+            jpeg_c_set_int_param(info, JINT_BASE_QUANT_TBL_IDX, c_int::from(index));
+        }
+        jpeg_c_set_int_param(
+            info,
+            JINT_DC_SCAN_OPT_MODE,
+            self.options.dc_scan_mode as c_int,
+        );
+
+        jpeg_set_quality(info, self.options.quality as c_int, 0);
 
-            comp.set_optimize_coding(self.options.optimize_coding);
-            comp.set_smoothing_factor(self.options.smoothing);
-            comp.set_color_space(match format {
+        jpeg_set_colorspace(
+            info,
+            match format {
                 mozjpeg::ColorSpace::JCS_GRAYSCALE => {
                     log::warn!("Input colorspace is GRAYSCALE, using GRAYSCALE as output");
 
@@ -151,67 +280,177 @@ impl EncoderTrait for MozJpegEncoder {
                 }
 
                 _ => self.options.color_space,
-            });
-            comp.set_use_scans_in_trellis(self.options.trellis_multipass);
+            },
+        );
+
+        // scans depend on the number of components of output color space
+        if self.options.progressive {
+            jpeg_simple_progression(info);
+        } else {
+            jpeg_c_set_bool_param(info, JBOOLEAN_OPTIMIZE_SCANS, 0);
+            info.num_scans = 0;
+            info.scan_info = ptr::null();
+        }
 
-            if let Some(sb) = self.options.chroma_subsample {
-                comp.set_chroma_sampling_pixel_sizes((sb, sb), (sb, sb))
-            }
+        info.optimize_coding = self.options.optimize_coding as boolean;
+        info.smoothing_factor = c_int::from(self.options.smoothing);
+
+        jpeg_c_set_bool_param(
+            info,
+            JBOOLEAN_USE_SCANS_IN_TRELLIS,
+            self.options.trellis_multipass as boolean,
+        );
+        jpeg_c_set_bool_param(
+            info,
+            JBOOLEAN_TRELLIS_QUANT,
+            self.options.trellis as boolean,
+        );
+        jpeg_c_set_bool_param(
+            info,
+            JBOOLEAN_TRELLIS_QUANT_DC,
+            self.options.trellis_dc as boolean,
+        );
+        jpeg_c_set_bool_param(
+            info,
+            JBOOLEAN_OVERSHOOT_DERINGING,
+            self.options.overshoot_deringing as boolean,
+        );
+
+        match self.options.restart_interval {
+            Some(RestartInterval::Rows(rows)) => info.restart_in_rows = c_int::from(rows),
+            Some(RestartInterval::Blocks(blocks)) => info.restart_interval = c_uint::from(blocks),
+            None => {}
+        }
+
+        if let Some((h, v)) = self.options.chroma_subsample {
+            let components =
+                slice::from_raw_parts_mut(info.comp_info, info.num_components as usize);
 
-            if let Some(qtable) = luma_qtable {
-                comp.set_luma_qtable(qtable)
+            // luma is sampled at full resolution, chroma once per h x v pixels
+            let factors = [(h, v), (1, 1), (1, 1)];
+
+            for (component, (h_samp, v_samp)) in components.iter_mut().zip(factors) {
+                component.h_samp_factor = c_int::from(h_samp);
+                component.v_samp_factor = c_int::from(v_samp);
             }
+        }
+
+        if let Some(qtable) = &self.options.luma_qtable {
+            jpeg_add_quant_table(info, 0, qtable.as_ptr(), 100, 1);
+        }
+
+        if let Some(qtable) = &self.options.chroma_qtable {
+            jpeg_add_quant_table(info, 1, qtable.as_ptr(), 100, 1);
+        }
+
+        comp.mem_dest();
+        jpeg_start_compress(&mut comp.info, 1);
+
+        #[cfg(feature = "metadata")]
+        {
+            use exif::experimental::Writer;
+
+            if let Some(metadata) = &image.metadata().exif() {
+                let mut writer = Writer::new();
+                // write first tags for exif
+                let mut buf = std::io::Cursor::new(b"Exif\x00\x00".to_vec());
+                // set buffer position to be bytes written, to ensure we don't overwrite anything
+                buf.set_position(6);
 
-            if let Some(qtable) = chroma_qtable {
-                comp.set_chroma_qtable(qtable)
+                for metadatum in *metadata {
+                    writer.push_field(metadatum);
+                }
+                let result = writer.write(&mut buf, false);
+                if result.is_ok() {
+                    // add the exif tag to APP1 segment
+                    write_marker(&mut comp, JPEG_APP0 + 1, buf.get_ref());
+                } else {
+                    log::warn!("Writing exif failed {:?}", result);
+                }
             }
+        }
+
+        // JPEG without a profile is treated as sRGB by decoders
+        if let Some(profile) = image.metadata().icc_chunk().filter(|p| !icc::is_srgb(p)) {
+            let chunks = profile.chunks(ICC_CHUNK_SIZE);
+            let count = chunks.len();
+
+            if count > 255 {
+                log::warn!("ICC profile is too large for JPEG, skipping it");
+            } else {
+                for (index, chunk) in chunks.enumerate() {
+                    // marker, sequence number starting from 1 and count of chunks
+                    let mut marker = ICC_MARKER.to_vec();
+                    marker.extend_from_slice(&[index as u8 + 1, count as u8]);
+                    marker.extend_from_slice(chunk);
 
-            let writer = TempVt {
-                inner: sink,
-                bytes_written: 0,
-            };
-
-            let mut comp = comp.start_compress(writer)?;
-
-            #[cfg(feature = "metadata")]
-            {
-                use exif::experimental::Writer;
-
-                if let Some(metadata) = &image.metadata().exif() {
-                    let mut writer = Writer::new();
-                    // write first tags for exif
-                    let mut buf = std::io::Cursor::new(b"Exif\x00\x00".to_vec());
-                    // set buffer position to be bytes written, to ensure we don't overwrite anything
-                    buf.set_position(6);
-
-                    for metadatum in *metadata {
-                        writer.push_field(metadatum);
-                    }
-                    let result = writer.write(&mut buf, false);
-                    if result.is_ok() {
-                        // add the exif tag to APP1 segment
-                        comp.write_marker(mozjpeg::Marker::APP(1), buf.get_ref());
-                    } else {
-                        log::warn!("Writing exif failed {:?}", result);
-                    }
+                    write_marker(&mut comp, JPEG_APP0 + 2, &marker);
                 }
             }
+        }
+
+        let stride = width * format.num_components();
+        let rows: Vec<JSAMPROW> = data.chunks_exact(stride).map(|row| row.as_ptr()).collect();
+
+        while (comp.info.next_scanline as usize) < rows.len() {
+            let written = comp.info.next_scanline as usize;
+
+            jpeg_write_scanlines(
+                &mut comp.info,
+                rows[written..].as_ptr(),
+                (rows.len() - written) as JDIMENSION,
+            );
+        }
 
-            comp.write_scanlines(data)?;
+        jpeg_finish_compress(&mut comp.info);
 
-            Ok(comp.finish()?.bytes_written)
-        }))
-        .map_err(|err| {
+        comp.data()
+    }
+}
+
+const JPEG_APP0: c_int = 0xE0;
+
+unsafe fn write_marker(comp: &mut Compress, marker: c_int, data: &[u8]) {
+    jpeg_write_marker(&mut comp.info, marker, data.as_ptr(), data.len() as c_uint);
+}
+
+impl EncoderTrait for MozJpegEncoder {
+    fn name(&self) -> &'static str {
+        "mozjpeg-encoder"
+    }
+
+    fn encode_inner<T: ZByteWriterTrait>(
+        &mut self,
+        image: &Image,
+        sink: T,
+    ) -> Result<usize, ImageErrors> {
+        // libjpeg silently ignores an unknown table
+        if let Some(index) = self.options.qtable_index.filter(|&index| index > 8) {
+            return Err(ImageErrors::EncodeErrors(
+                ImgEncodeErrors::ImageEncodeErrors(format!(
+                    "Quantization table index {index} is out of range 0..=8"
+                )),
+            ));
+        }
+
+        let data = std::panic::catch_unwind(AssertUnwindSafe(|| unsafe { self.compress(image) }))
+            .map_err(|err| {
             if let Ok(mut err) = err.downcast::<String>() {
-                ImageErrors::EncodeErrors(zune_image::errors::ImgEncodeErrors::Generic(mem::take(
-                    &mut *err,
-                )))
+                ImageErrors::EncodeErrors(ImgEncodeErrors::Generic(mem::take(&mut *err)))
             } else {
-                ImageErrors::EncodeErrors(zune_image::errors::ImgEncodeErrors::GenericStatic(
+                ImageErrors::EncodeErrors(ImgEncodeErrors::GenericStatic(
                     "Unknown error occurred during encoding",
                 ))
             }
-        })?
+        })?;
+
+        let mut writer = ZWriter::new(sink);
+
+        writer.write(&data).map_err(|e| {
+            ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
+        })?;
+
+        Ok(writer.bytes_written())
     }
 
     fn supported_colorspaces(&self) -> &'static [ColorSpace] {
@@ -232,13 +471,16 @@ impl EncoderTrait for MozJpegEncoder {
         ImageFormat::JPEG
     }
 
+    /// JPEG is always written with 8-bit samples, higher bit depths
+    /// are reduced according to [`MozJpegOptions::dithering`]
     fn supported_bit_depth(&self) -> &'static [BitDepth] {
-        &[BitDepth::Eight, BitDepth::Sixteen]
+        &[BitDepth::Eight, BitDepth::Sixteen, BitDepth::Float32]
     }
 
+    /// High bit depths are kept to be reduced with dithering
     fn default_depth(&self, depth: BitDepth) -> BitDepth {
         match depth {
-            BitDepth::Sixteen | BitDepth::Float32 => BitDepth::Sixteen,
+            BitDepth::Sixteen | BitDepth::Float32 => depth,
             _ => BitDepth::Eight,
         }
     }
@@ -2,7 +2,7 @@ use std::io::Cursor;
 
 use zune_core::colorspace::ColorSpace;
 
-use crate::test_utils::*;
+use crate::{codecs::mozjpeg::MozJpegDecoder, test_utils::*};
 
 use super::*;
 
@@ -156,3 +156,194 @@ fn encode_animated() {
 
     assert!(result.is_ok());
 }
+
+#[test]
+fn encode_icc() {
+    let encode = |icc: Vec<u8>| {
+        let mut image = create_test_image_u8(200, 200, ColorSpace::RGB);
+        image.metadata_mut().set_icc_chunk(icc);
+
+        let mut buf = Cursor::new(vec![]);
+        MozJpegEncoder::new().encode(&image, &mut buf).unwrap();
+
+        let decoder = MozJpegDecoder::try_new(Cursor::new(buf.into_inner())).unwrap();
+        Image::from_decoder(decoder).unwrap()
+    };
+
+    let icc = create_wide_gamut_profile();
+    assert_eq!(encode(icc.clone()).metadata().icc_chunk(), Some(&icc));
+
+    // sRGB is implied for JPEG without a profile
+    let srgb = std::fs::read("tests/files/icc/tinysrgb.icc").unwrap();
+    assert_eq!(encode(srgb).metadata().icc_chunk(), None);
+}
+
+/// Encode RGB test image with `options`
+fn encode_with(options: MozJpegOptions) -> Vec<u8> {
+    let image = create_test_image_u8(64, 64, ColorSpace::RGB);
+
+    let mut buf = Cursor::new(vec![]);
+    MozJpegEncoder::new_with_options(options)
+        .encode(&image, &mut buf)
+        .unwrap();
+
+    buf.into_inner()
+}
+
+/// Payload of the first `marker` segment
+fn segment(data: &[u8], marker: u8) -> Option<&[u8]> {
+    let offset = data.windows(2).position(|w| w == [0xFF, marker])?;
+    let len = u16::from_be_bytes([data[offset + 2], data[offset + 3]]) as usize;
+
+    data.get(offset + 4..offset + 2 + len)
+}
+
+/// Sampling factors of components from the start of frame
+fn sampling_factors(data: &[u8]) -> Vec<u8> {
+    let frame = segment(data, 0xC2).or_else(|| segment(data, 0xC0)).unwrap();
+
+    frame[6..].chunks(3).map(|component| component[1]).collect()
+}
+
+#[test]
+fn encode_subsampling() {
+    for (subsample, luma) in [((2, 1), 0x21), ((1, 2), 0x12), ((2, 2), 0x22)] {
+        let data = encode_with(MozJpegOptions {
+            chroma_subsample: Some(subsample),
+            ..Default::default()
+        });
+
+        assert_eq!(sampling_factors(&data), [luma, 0x11, 0x11]);
+    }
+}
+
+#[test]
+fn encode_baseline() {
+    let data = encode_with(MozJpegOptions {
+        progressive: false,
+        ..Default::default()
+    });
+
+    assert!(segment(&data, 0xC2).is_none());
+    assert!(segment(&data, 0xC0).is_some());
+}
+
+#[test]
+fn encode_restart_interval() {
+    let data = encode_with(MozJpegOptions {
+        restart_interval: Some(RestartInterval::Blocks(4)),
+        ..Default::default()
+    });
+
+    assert_eq!(segment(&data, 0xDD), Some(&[0, 4][..]));
+
+    let data = encode_with(MozJpegOptions::default());
+
+    assert_eq!(segment(&data, 0xDD), None);
+}
+
+#[test]
+fn encode_qtable_index() {
+    let data = encode_with(MozJpegOptions {
+        quality: 50.,
+        qtable_index: Some(1),
+        ..Default::default()
+    });
+
+    // precision and table id followed by values of the flat table
+    let table = segment(&data, 0xDB).unwrap();
+    assert!(table[1..65].iter().all(|&v| v == 16));
+
+    let result = MozJpegEncoder::new_with_options(MozJpegOptions {
+        qtable_index: Some(9),
+        ..Default::default()
+    })
+    .encode(
+        &create_test_image_u8(64, 64, ColorSpace::RGB),
+        Cursor::new(vec![]),
+    );
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn parse_restart_interval() {
+    assert_eq!("8".parse(), Ok(RestartInterval::Rows(8)));
+    assert_eq!("16b".parse(), Ok(RestartInterval::Blocks(16)));
+    assert_eq!("16B".parse(), Ok(RestartInterval::Blocks(16)));
+    assert!("b".parse::<RestartInterval>().is_err());
+    assert!("100000".parse::<RestartInterval>().is_err());
+}
+
+#[test]
+fn dither_u16() {
+    // halfway between 100 and 101 in 8 bits
+    let value = 100 * 257 + 128;
+    let image = Image::from_fn(64, 64, ColorSpace::Luma, |_, _, px: &mut [u16; 4]| {
+        px[0] = value;
+    });
+
+    let mean = |data: &[u8]| data.iter().map(|&v| f64::from(v)).sum::<f64>() / data.len() as f64;
+    let frame = &image.frames_ref()[0];
+
+    let rounded = dither::to_u8(&image, frame, Dithering::None);
+    assert!(rounded.iter().all(|&v| v == 100));
+
+    for dithering in [Dithering::Ordered, Dithering::ErrorDiffusion] {
+        let dithered = dither::to_u8(&image, frame, dithering);
+
+        assert!(dithered.iter().all(|&v| v == 100 || v == 101));
+        assert!((mean(&dithered) - f64::from(value) / 257.).abs() < 0.02);
+    }
+}
+
+#[test]
+fn dither_skips_alpha() {
+    let value = 100 * 257 + 128;
+    let image = Image::from_fn(16, 16, ColorSpace::RGBA, |_, _, px: &mut [u16; 4]| {
+        *px = [value; 4];
+    });
+    let frame = &image.frames_ref()[0];
+
+    for dithering in [Dithering::Ordered, Dithering::ErrorDiffusion] {
+        let dithered = dither::to_u8(&image, frame, dithering);
+
+        // alpha is rounded down, while some color samples are dithered up
+        assert!(dithered.chunks_exact(4).all(|px| px[3] == 100));
+        assert!(dithered.chunks_exact(4).any(|px| px[0] == 101));
+    }
+}
+
+#[test]
+fn keep_high_depth() {
+    let encoder = MozJpegEncoder::new();
+
+    assert_eq!(encoder.default_depth(BitDepth::Sixteen), BitDepth::Sixteen);
+    assert_eq!(encoder.default_depth(BitDepth::Float32), BitDepth::Float32);
+    assert_eq!(encoder.default_depth(BitDepth::Eight), BitDepth::Eight);
+}
+
+#[test]
+fn encode_u16_dithering() {
+    let image = create_test_image_u16(200, 200, ColorSpace::RGB);
+
+    for dithering in [
+        Dithering::None,
+        Dithering::Ordered,
+        Dithering::ErrorDiffusion,
+    ] {
+        let mut encoder = MozJpegEncoder::new_with_options(MozJpegOptions {
+            dithering,
+            ..Default::default()
+        });
+
+        let mut buf = Cursor::new(vec![]);
+        encoder.encode(&image, &mut buf).unwrap();
+
+        let decoder = MozJpegDecoder::try_new(Cursor::new(buf.into_inner())).unwrap();
+        let decoded = Image::from_decoder(decoder).unwrap();
+
+        assert_eq!(decoded.depth(), BitDepth::Eight);
+        assert_eq!(decoded.dimensions(), (200, 200));
+    }
+}
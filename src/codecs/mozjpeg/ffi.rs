@@ -0,0 +1,132 @@
+use std::{
+    mem,
+    os::raw::{c_ulong, c_void},
+    ptr, slice,
+};
+
+use mozjpeg_sys::*;
+
+/// Decompression object with an error manager that unwinds on errors
+pub(super) struct Decompress {
+    pub(super) info: Box<jpeg_decompress_struct>,
+    _err: Box<jpeg_error_mgr>,
+}
+
+impl Decompress {
+    pub(super) unsafe fn new() -> Self {
+        let mut err = error_mgr();
+        let mut info: Box<jpeg_decompress_struct> = Box::new(mem::zeroed());
+
+        info.common.err = &mut *err;
+        jpeg_create_decompress(&mut *info);
+
+        Self { info, _err: err }
+    }
+}
+
+impl Drop for Decompress {
+    fn drop(&mut self) {
+        unsafe { jpeg_destroy_decompress(&mut self.info) }
+    }
+}
+
+/// Buffer allocated by libjpeg for the compressed file
+struct Output {
+    buffer: *mut u8,
+    size: c_ulong,
+}
+
+/// Compression object writing into [`Output`]
+pub(super) struct Compress {
+    pub(super) info: Box<jpeg_compress_struct>,
+    output: Box<Output>,
+    _err: Box<jpeg_error_mgr>,
+}
+
+impl Compress {
+    pub(super) unsafe fn new() -> Self {
+        let mut err = error_mgr();
+        let mut info: Box<jpeg_compress_struct> = Box::new(mem::zeroed());
+
+        info.common.err = &mut *err;
+        jpeg_create_compress(&mut *info);
+
+        Self {
+            info,
+            output: Box::new(Output {
+                buffer: ptr::null_mut(),
+                size: 0,
+            }),
+            _err: err,
+        }
+    }
+
+    /// Write compressed file into [`Output`]
+    pub(super) unsafe fn mem_dest(&mut self) {
+        jpeg_mem_dest(
+            &mut *self.info,
+            &mut self.output.buffer,
+            &mut self.output.size,
+        );
+    }
+
+    /// Compressed file, complete after `jpeg_finish_compress`
+    pub(super) unsafe fn data(&self) -> Vec<u8> {
+        slice::from_raw_parts(self.output.buffer, self.output.size as usize).to_vec()
+    }
+}
+
+impl Drop for Compress {
+    fn drop(&mut self) {
+        unsafe {
+            jpeg_destroy_compress(&mut self.info);
+
+            if !self.output.buffer.is_null() {
+                libc::free(self.output.buffer as *mut c_void);
+            }
+        }
+    }
+}
+
+unsafe fn error_mgr() -> Box<jpeg_error_mgr> {
+    let mut err = Box::new(mem::zeroed());
+
+    jpeg_std_error(&mut err);
+    err.error_exit = Some(error_exit);
+    err.emit_message = Some(emit_message);
+
+    err
+}
+
+extern "C-unwind" fn emit_message(_cinfo: &mut jpeg_common_struct, _level: i32) {}
+
+extern "C-unwind" fn error_exit(cinfo: &mut jpeg_common_struct) {
+    let message = unsafe {
+        let mut buffer = [0u8; JMSG_LENGTH_MAX];
+
+        match (*cinfo.err).format_message {
+            Some(format_message) => {
+                // the binding declares the output buffer as shared
+                let format_message = mem::transmute::<
+                    unsafe extern "C-unwind" fn(&mut jpeg_common_struct, &[u8; JMSG_LENGTH_MAX]),
+                    unsafe extern "C-unwind" fn(
+                        &mut jpeg_common_struct,
+                        &mut [u8; JMSG_LENGTH_MAX],
+                    ),
+                >(format_message);
+                format_message(cinfo, &mut buffer);
+
+                let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+                String::from_utf8_lossy(&buffer[..len]).into_owned()
+            }
+            None => format!("code {}", (*cinfo.err).msg_code),
+        }
+    };
+
+    // avoids calling panic handler, errors are caught with `catch_unwind` so
+    // binaries have to be built with `panic = "unwind"`
+    std::panic::resume_unwind(Box::new(format!("libjpeg fatal error: {message}")));
+}
+
+/// Size of the message buffer of libjpeg error manager
+const JMSG_LENGTH_MAX: usize = 80;
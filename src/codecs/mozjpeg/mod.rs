@@ -1,3 +1,11 @@
+mod decoder;
 mod encoder;
+mod ffi;
+mod transcoder;
 
+pub use decoder::*;
 pub use encoder::*;
+pub use transcoder::*;
+
+const ICC_MARKER: &[u8] = b"ICC_PROFILE\0";
+const EXIF_MARKER: &[u8] = b"Exif\0\0";
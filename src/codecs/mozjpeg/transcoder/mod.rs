@@ -0,0 +1,445 @@
+use std::{
+    mem,
+    os::raw::{c_int, c_ulong},
+    panic::AssertUnwindSafe,
+    ptr, slice,
+    str::FromStr,
+};
+
+use mozjpeg_sys::*;
+use zune_image::errors::{ImageErrors, ImgEncodeErrors};
+
+use super::ffi::{Compress, Decompress};
+use crate::options::{OptionsError, Validate};
+
+/// Markers of the source JPEG written into the output
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum CopyMarkers {
+    /// Strip all markers
+    None,
+    /// Keep only comments
+    Comments,
+    /// Keep all markers, including EXIF and ICC profile
+    #[default]
+    All,
+}
+
+impl CopyMarkers {
+    fn option(self) -> JCOPY_OPTION {
+        match self {
+            CopyMarkers::None => JCOPY_OPTION_JCOPYOPT_NONE,
+            CopyMarkers::Comments => JCOPY_OPTION_JCOPYOPT_COMMENTS,
+            CopyMarkers::All => JCOPY_OPTION_JCOPYOPT_ALL,
+        }
+    }
+}
+
+/// Lossless transformation of DCT coefficients
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum JpegTransform {
+    /// Keep the image as is
+    #[default]
+    None,
+    /// Mirror image horizontally
+    FlipHorizontal,
+    /// Mirror image vertically
+    FlipVertical,
+    /// Flip image across the upper-left to lower-right diagonal
+    Transpose,
+    /// Flip image across the upper-right to lower-left diagonal
+    Transverse,
+    /// Rotate image 90 degrees clockwise
+    Rotate90,
+    /// Rotate image 180 degrees
+    Rotate180,
+    /// Rotate image 270 degrees clockwise
+    Rotate270,
+    /// Apply the EXIF orientation of the source and reset the Orientation tag
+    AutoOrient,
+}
+
+impl JpegTransform {
+    /// Transform which turns an image with EXIF `orientation` upright
+    pub fn from_orientation(orientation: u16) -> JpegTransform {
+        match orientation {
+            2 => JpegTransform::FlipHorizontal,
+            3 => JpegTransform::Rotate180,
+            4 => JpegTransform::FlipVertical,
+            5 => JpegTransform::Transpose,
+            6 => JpegTransform::Rotate90,
+            7 => JpegTransform::Transverse,
+            8 => JpegTransform::Rotate270,
+            _ => JpegTransform::None,
+        }
+    }
+
+    fn code(self) -> JXFORM_CODE {
+        match self {
+            JpegTransform::None | JpegTransform::AutoOrient => JXFORM_CODE_JXFORM_NONE,
+            JpegTransform::FlipHorizontal => JXFORM_CODE_JXFORM_FLIP_H,
+            JpegTransform::FlipVertical => JXFORM_CODE_JXFORM_FLIP_V,
+            JpegTransform::Transpose => JXFORM_CODE_JXFORM_TRANSPOSE,
+            JpegTransform::Transverse => JXFORM_CODE_JXFORM_TRANSVERSE,
+            JpegTransform::Rotate90 => JXFORM_CODE_JXFORM_ROT_90,
+            JpegTransform::Rotate180 => JXFORM_CODE_JXFORM_ROT_180,
+            JpegTransform::Rotate270 => JXFORM_CODE_JXFORM_ROT_270,
+        }
+    }
+}
+
+/// Region kept by lossless cropping
+///
+/// The region is in coordinates of the transformed image. Its top left corner is moved
+/// to the nearest MCU boundary, so the output may be slightly larger than requested.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JpegCrop {
+    /// Left offset in pixels
+    pub x: u32,
+    /// Top offset in pixels
+    pub y: u32,
+    /// Width of the region in pixels
+    pub width: u32,
+    /// Height of the region in pixels
+    pub height: u32,
+}
+
+impl FromStr for JpegCrop {
+    type Err = String;
+
+    /// Parse `jpegtran` geometry `WIDTHxHEIGHT+X+Y`, offsets are optional
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || format!("Invalid crop region '{s}', expected WIDTHxHEIGHT+X+Y");
+
+        let mut parts = s.split('+');
+        let (width, height) = parts
+            .next()
+            .and_then(|size| size.split_once('x'))
+            .ok_or_else(error)?;
+
+        let number = |value: Option<&str>| value.unwrap_or("0").parse::<u32>().map_err(|_| error());
+
+        let crop = JpegCrop {
+            width: number(Some(width))?,
+            height: number(Some(height))?,
+            x: number(parts.next())?,
+            y: number(parts.next())?,
+        };
+
+        if parts.next().is_some() || crop.width == 0 || crop.height == 0 {
+            return Err(error());
+        }
+
+        Ok(crop)
+    }
+}
+
+/// Options for lossless JPEG transcoding
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct MozJpegTranscoderOptions {
+    /// Write progressive JPEG with scans optimized by MozJpeg, otherwise sequential
+    pub progressive: bool,
+    /// Markers copied from the source
+    pub copy_markers: CopyMarkers,
+    /// Transformation applied to the image
+    pub transform: JpegTransform,
+    /// Crop the image after transformation
+    pub crop: Option<JpegCrop>,
+    /// Drop partial MCUs on the edges which can't be transformed, enabled by default
+    ///
+    /// Otherwise they are kept in place untransformed.
+    pub trim: bool,
+    /// Fail when the image has partial MCUs on the edges which can't be transformed
+    pub perfect: bool,
+}
+
+impl Default for MozJpegTranscoderOptions {
+    fn default() -> Self {
+        Self {
+            progressive: true,
+            copy_markers: CopyMarkers::All,
+            transform: JpegTransform::None,
+            crop: None,
+            trim: true,
+            perfect: false,
+        }
+    }
+}
+
+impl Validate for MozJpegTranscoderOptions {
+    fn validate(&self) -> Result<(), OptionsError> {
+        match self.crop {
+            Some(crop) if crop.width == 0 || crop.height == 0 => Err(OptionsError::Invalid {
+                option: "crop",
+                reason: "must not be empty",
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Lossless JPEG optimizer
+///
+/// Works on DCT coefficients of the source like `jpegtran`, so the image is never
+/// requantized. Huffman tables are always optimized.
+#[derive(Default)]
+pub struct MozJpegTranscoder {
+    options: MozJpegTranscoderOptions,
+}
+
+impl MozJpegTranscoder {
+    /// Create a new transcoder
+    pub fn new() -> MozJpegTranscoder {
+        MozJpegTranscoder::default()
+    }
+
+    /// Create a new transcoder with specified options
+    pub fn new_with_options(options: MozJpegTranscoderOptions) -> MozJpegTranscoder {
+        MozJpegTranscoder { options }
+    }
+
+    /// Create a new transcoder with specified options, rejecting an empty crop region
+    pub fn try_new_with_options(
+        options: MozJpegTranscoderOptions,
+    ) -> Result<MozJpegTranscoder, OptionsError> {
+        options.validate()?;
+
+        Ok(MozJpegTranscoder::new_with_options(options))
+    }
+
+    /// Losslessly optimize JPEG file `data`
+    pub fn transcode(&self, data: &[u8]) -> Result<Vec<u8>, ImageErrors> {
+        std::panic::catch_unwind(AssertUnwindSafe(|| unsafe { self.transcode_inner(data) }))
+            .map_err(|err| {
+                if let Ok(mut err) = err.downcast::<String>() {
+                    ImageErrors::EncodeErrors(ImgEncodeErrors::Generic(mem::take(&mut *err)))
+                } else {
+                    ImageErrors::EncodeErrors(ImgEncodeErrors::GenericStatic(
+                        "Unknown error occurred during transcoding",
+                    ))
+                }
+            })?
+    }
+
+    unsafe fn transcode_inner(&self, data: &[u8]) -> Result<Vec<u8>, ImageErrors> {
+        let copy_markers = self.options.copy_markers.option();
+        let auto_orient = self.options.transform == JpegTransform::AutoOrient;
+
+        let mut src = Decompress::new();
+        jpeg_mem_src(&mut src.info, data.as_ptr(), data.len() as c_ulong);
+        // markers have to be saved before reading the header
+        jcopy_markers_setup(&mut *src.info, copy_markers);
+        if auto_orient && self.options.copy_markers != CopyMarkers::All {
+            jpeg_save_markers(&mut src.info, JPEG_APP1, 0xFFFF);
+        }
+        jpeg_read_header(&mut src.info, 1);
+
+        let transform = if auto_orient {
+            let orientation = reset_orientation(&mut src.info);
+
+            if self.options.copy_markers != CopyMarkers::All {
+                // EXIF was saved only to read the orientation
+                remove_markers(&mut src.info, JPEG_APP1);
+            }
+
+            JpegTransform::from_orientation(orientation)
+        } else {
+            self.options.transform
+        };
+
+        let mut info: jpeg_transform_info = mem::zeroed();
+        info.transform = transform.code();
+        info.trim = self.options.trim as boolean;
+        info.perfect = self.options.perfect as boolean;
+
+        if let Some(crop) = self.options.crop {
+            // crop region is in coordinates of the transformed image
+            let (width, height) = match transform {
+                JpegTransform::Transpose
+                | JpegTransform::Transverse
+                | JpegTransform::Rotate90
+                | JpegTransform::Rotate270 => (src.info.image_height, src.info.image_width),
+                _ => (src.info.image_width, src.info.image_height),
+            };
+
+            if crop.width == 0
+                || crop.height == 0
+                || crop
+                    .x
+                    .checked_add(crop.width)
+                    .map_or(true, |right| right > width)
+                || crop
+                    .y
+                    .checked_add(crop.height)
+                    .map_or(true, |bottom| bottom > height)
+            {
+                return Err(ImageErrors::EncodeErrors(ImgEncodeErrors::GenericStatic(
+                    "Crop region is outside the image",
+                )));
+            }
+
+            info.crop = 1;
+            info.crop_width = crop.width;
+            info.crop_width_set = JCROP_CODE_JCROP_POS;
+            info.crop_height = crop.height;
+            info.crop_height_set = JCROP_CODE_JCROP_POS;
+            info.crop_xoffset = crop.x;
+            info.crop_xoffset_set = JCROP_CODE_JCROP_POS;
+            info.crop_yoffset = crop.y;
+            info.crop_yoffset_set = JCROP_CODE_JCROP_POS;
+        }
+
+        // fails only when perfect transformation is requested
+        if jtransform_request_workspace(&mut *src.info, &mut info) == 0 {
+            return Err(ImageErrors::EncodeErrors(ImgEncodeErrors::GenericStatic(
+                "Image size is not a multiple of MCU size, transformation is not perfect",
+            )));
+        }
+
+        let src_coefficients = jpeg_read_coefficients(&mut src.info);
+
+        let mut dst = Compress::new();
+        jpeg_copy_critical_parameters(&src.info, &mut dst.info);
+
+        let coefficients = jtransform_adjust_parameters(
+            &mut *src.info,
+            &mut *dst.info,
+            src_coefficients,
+            &mut info,
+        );
+
+        dst.info.optimize_coding = 1;
+
+        if self.options.progressive {
+            jpeg_c_set_bool_param(&mut dst.info, JBOOLEAN_OPTIMIZE_SCANS, 1);
+            jpeg_simple_progression(&mut dst.info);
+        } else {
+            jpeg_c_set_bool_param(&mut dst.info, JBOOLEAN_OPTIMIZE_SCANS, 0);
+            dst.info.num_scans = 0;
+            dst.info.scan_info = ptr::null();
+        }
+
+        dst.mem_dest();
+        jpeg_write_coefficients(&mut dst.info, coefficients);
+        jcopy_markers_execute(&mut *src.info, &mut *dst.info, copy_markers);
+        jtransform_execute_transformation(
+            &mut *src.info,
+            &mut *dst.info,
+            src_coefficients,
+            &mut info,
+        );
+
+        jpeg_finish_compress(&mut dst.info);
+        jpeg_finish_decompress(&mut src.info);
+
+        Ok(dst.data())
+    }
+}
+
+const JPEG_APP1: c_int = 0xE1;
+
+/// Read EXIF orientation of the image from saved APP1 markers and reset it to normal
+///
+/// Returns `1` when there is no orientation.
+unsafe fn reset_orientation(info: &mut jpeg_decompress_struct) -> u16 {
+    let mut marker = info.marker_list;
+
+    while let Some(current) = marker.as_mut() {
+        if c_int::from(current.marker) == JPEG_APP1 && !current.data.is_null() {
+            let data = slice::from_raw_parts_mut(current.data, current.data_length as usize);
+
+            if let Some((orientation, offset, big_endian)) = exif_orientation(data) {
+                let normal = if big_endian {
+                    1u16.to_be_bytes()
+                } else {
+                    1u16.to_le_bytes()
+                };
+                data[offset..offset + 2].copy_from_slice(&normal);
+
+                return orientation;
+            }
+        }
+
+        marker = current.next;
+    }
+
+    1
+}
+
+/// Unlink saved markers with `code` so they aren't copied
+unsafe fn remove_markers(info: &mut jpeg_decompress_struct, code: c_int) {
+    let mut link = &mut info.marker_list;
+
+    while let Some(current) = link.as_mut() {
+        if c_int::from(current.marker) == code {
+            *link = current.next;
+        } else {
+            link = &mut current.next;
+        }
+    }
+}
+
+/// Orientation tag of IFD0 in APP1 marker `data` as its value,
+/// offset of the value and byte order
+fn exif_orientation(data: &[u8]) -> Option<(u16, usize, bool)> {
+    const EXIF_HEADER: &[u8] = b"Exif\0\0";
+
+    let tiff = data.strip_prefix(EXIF_HEADER)?;
+    let big_endian = match tiff.get(..2)? {
+        b"MM" => true,
+        b"II" => false,
+        _ => return None,
+    };
+
+    let u16_at = |offset: usize| -> Option<u16> {
+        let bytes = [*tiff.get(offset)?, *tiff.get(offset + 1)?];
+        Some(if big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    };
+    let u32_at = |offset: usize| -> Option<u32> {
+        let bytes = tiff.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    };
+
+    let ifd = u32_at(4)? as usize;
+    let count = u16_at(ifd)? as usize;
+
+    (0..count).find_map(|i| {
+        let entry = ifd + 2 + i * 12;
+
+        if u16_at(entry)? != 0x0112 {
+            return None;
+        }
+
+        Some((
+            u16_at(entry + 8)?,
+            EXIF_HEADER.len() + entry + 8,
+            big_endian,
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,302 @@
+use std::io::Cursor;
+
+use zune_core::colorspace::ColorSpace;
+use zune_image::{image::Image, traits::EncoderTrait};
+
+use crate::{
+    codecs::mozjpeg::{MozJpegDecoder, MozJpegEncoder, MozJpegOptions},
+    test_utils::*,
+};
+
+use super::*;
+
+fn decode(data: &[u8]) -> Image {
+    let decoder = MozJpegDecoder::try_new(Cursor::new(data.to_vec())).unwrap();
+
+    Image::from_decoder(decoder).unwrap()
+}
+
+/// Check whether JPEG `data` has start of frame `marker`
+fn has_marker(data: &[u8], marker: u8) -> bool {
+    data.windows(2).any(|w| w == [0xFF, marker])
+}
+
+#[test]
+fn transcode_lossless() {
+    let data = std::fs::read("tests/files/jpg/f1t.jpg").unwrap();
+
+    let transcoded = MozJpegTranscoder::new().transcode(&data).unwrap();
+
+    // progressive DCT frame
+    assert!(has_marker(&transcoded, 0xC2));
+    assert_eq!(
+        decode(&transcoded).flatten_to_u8(),
+        decode(&data).flatten_to_u8()
+    );
+}
+
+#[test]
+fn transcode_baseline() {
+    let mut image = create_test_image_u8(200, 200, ColorSpace::RGB);
+    image
+        .metadata_mut()
+        .set_icc_chunk(create_wide_gamut_profile());
+
+    let mut encoder = MozJpegEncoder::new_with_options(MozJpegOptions {
+        optimize_coding: false,
+        ..Default::default()
+    });
+
+    let mut buf = Cursor::new(vec![]);
+    encoder.encode(&image, &mut buf).unwrap();
+    let data = buf.into_inner();
+
+    let transcoded = MozJpegTranscoder::new_with_options(MozJpegTranscoderOptions {
+        progressive: false,
+        copy_markers: CopyMarkers::None,
+        ..Default::default()
+    })
+    .transcode(&data)
+    .unwrap();
+
+    assert!(!has_marker(&transcoded, 0xC2));
+
+    let decoded = decode(&transcoded);
+
+    assert_eq!(decoded.flatten_to_u8(), decode(&data).flatten_to_u8());
+    assert_eq!(decoded.metadata().icc_chunk(), None);
+}
+
+#[test]
+fn transcode_invalid() {
+    let result = MozJpegTranscoder::new().transcode(b"not a jpeg");
+
+    assert!(result.is_err());
+}
+
+/// Encode test image of `width` and `height` without chroma subsampling
+fn encode(width: usize, height: usize) -> Vec<u8> {
+    let image = create_test_image_u8(width, height, ColorSpace::RGB);
+
+    let mut encoder = MozJpegEncoder::new_with_options(MozJpegOptions {
+        chroma_subsample: Some((1, 1)),
+        ..Default::default()
+    });
+
+    let mut buf = Cursor::new(vec![]);
+    encoder.encode(&image, &mut buf).unwrap();
+
+    buf.into_inner()
+}
+
+/// Insert APP1 marker with EXIF `orientation` after SOI
+fn with_orientation(data: &[u8], orientation: u16) -> Vec<u8> {
+    let mut exif = b"Exif\0\0MM\0\x2a\0\0\0\x08\0\x01".to_vec();
+    // orientation entry of SHORT type
+    exif.extend_from_slice(&[0x01, 0x12, 0, 3, 0, 0, 0, 1]);
+    exif.extend_from_slice(&orientation.to_be_bytes());
+    exif.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+
+    let mut out = data[..2].to_vec();
+    out.extend_from_slice(&[0xFF, 0xE1]);
+    out.extend_from_slice(&(exif.len() as u16 + 2).to_be_bytes());
+    out.extend_from_slice(&exif);
+    out.extend_from_slice(&data[2..]);
+
+    out
+}
+
+#[test]
+fn transcode_rotate() {
+    let data = encode(64, 32);
+
+    let transcoded = MozJpegTranscoder::new_with_options(MozJpegTranscoderOptions {
+        transform: JpegTransform::Rotate90,
+        ..Default::default()
+    })
+    .transcode(&data)
+    .unwrap();
+
+    let source = decode(&data);
+    let rotated = decode(&transcoded);
+
+    assert_eq!(rotated.dimensions(), (32, 64));
+
+    let source = &source.flatten_to_u8()[0];
+    let rotated = &rotated.flatten_to_u8()[0];
+
+    // pixel (x, y) moves to (height - 1 - y, x)
+    for y in 0..32 {
+        for x in 0..64 {
+            let a = &source[(y * 64 + x) * 3..][..3];
+            let b = &rotated[(x * 32 + 31 - y) * 3..][..3];
+
+            assert!(a.iter().zip(b).all(|(a, b)| a.abs_diff(*b) <= 2));
+        }
+    }
+}
+
+#[test]
+fn transcode_partial_mcu() {
+    // 4:2:0 subsampling has 16x16 MCU
+    let image = create_test_image_u8(33, 17, ColorSpace::RGB);
+    let mut encoder = MozJpegEncoder::new_with_options(MozJpegOptions {
+        chroma_subsample: Some((2, 2)),
+        ..Default::default()
+    });
+    let mut buf = Cursor::new(vec![]);
+    encoder.encode(&image, &mut buf).unwrap();
+    let data = buf.into_inner();
+
+    let transcode = |trim, perfect| {
+        MozJpegTranscoder::new_with_options(MozJpegTranscoderOptions {
+            transform: JpegTransform::Rotate90,
+            trim,
+            perfect,
+            ..Default::default()
+        })
+        .transcode(&data)
+    };
+
+    // partial MCUs which can't be transformed are dropped by default
+    let trimmed = transcode(true, false).unwrap();
+    assert_eq!(decode(&trimmed).dimensions(), (16, 33));
+
+    // or kept untransformed in place
+    let kept = transcode(false, false).unwrap();
+    assert_eq!(decode(&kept).dimensions(), (17, 33));
+
+    assert!(transcode(true, true).is_err());
+    assert!(transcode(false, true).is_err());
+
+    // flipping vertically keeps the full width, edge blocks of the bottom are trimmed
+    let flipped = MozJpegTranscoder::new_with_options(MozJpegTranscoderOptions {
+        transform: JpegTransform::FlipVertical,
+        ..Default::default()
+    })
+    .transcode(&data)
+    .unwrap();
+    assert_eq!(decode(&flipped).dimensions(), (33, 16));
+}
+
+#[test]
+fn transcode_aligned_perfect() {
+    let data = encode(64, 32);
+
+    let transcoded = MozJpegTranscoder::new_with_options(MozJpegTranscoderOptions {
+        transform: JpegTransform::Rotate270,
+        perfect: true,
+        ..Default::default()
+    })
+    .transcode(&data)
+    .unwrap();
+
+    assert_eq!(decode(&transcoded).dimensions(), (32, 64));
+}
+
+#[test]
+fn transcode_crop() {
+    let data = encode(64, 64);
+
+    let transcoded = MozJpegTranscoder::new_with_options(MozJpegTranscoderOptions {
+        crop: Some("16x24+4+16".parse().unwrap()),
+        ..Default::default()
+    })
+    .transcode(&data)
+    .unwrap();
+
+    // left offset is aligned to MCU, which extends the width
+    assert_eq!(decode(&transcoded).dimensions(), (20, 24));
+}
+
+#[test]
+fn transcode_crop_outside() {
+    let data = encode(64, 32);
+
+    let transcode = |crop: JpegCrop, transform| {
+        MozJpegTranscoder::new_with_options(MozJpegTranscoderOptions {
+            crop: Some(crop),
+            transform,
+            perfect: true,
+            ..Default::default()
+        })
+        .transcode(&data)
+    };
+
+    let empty = JpegCrop {
+        x: 0,
+        y: 0,
+        width: 0,
+        height: 16,
+    };
+
+    for crop in [
+        "16x16+64+0".parse().unwrap(),
+        "16x16+56+0".parse().unwrap(),
+        empty,
+    ] {
+        let Err(ImageErrors::EncodeErrors(ImgEncodeErrors::GenericStatic(message))) =
+            transcode(crop, JpegTransform::None)
+        else {
+            panic!("{crop:?} has to be rejected");
+        };
+
+        assert_eq!(message, "Crop region is outside the image", "{crop:?}");
+    }
+
+    // crop region is checked against the rotated image
+    let portrait = "32x64".parse().unwrap();
+    assert!(transcode(portrait, JpegTransform::Rotate90).is_ok());
+    assert!(transcode(portrait, JpegTransform::None).is_err());
+}
+
+#[test]
+fn transcode_auto_orient() {
+    let data = with_orientation(&encode(64, 32), 6);
+
+    for copy_markers in [CopyMarkers::All, CopyMarkers::None] {
+        let transcoded = MozJpegTranscoder::new_with_options(MozJpegTranscoderOptions {
+            transform: JpegTransform::AutoOrient,
+            copy_markers,
+            ..Default::default()
+        })
+        .transcode(&data)
+        .unwrap();
+
+        assert_eq!(decode(&transcoded).dimensions(), (32, 64));
+
+        let exif = transcoded
+            .windows(6)
+            .position(|w| w == b"Exif\0\0")
+            .map(|offset| exif_orientation(&transcoded[offset..]).unwrap().0);
+
+        match copy_markers {
+            CopyMarkers::All => assert_eq!(exif, Some(1)),
+            _ => assert_eq!(exif, None),
+        }
+    }
+}
+
+#[test]
+fn parse_crop() {
+    assert_eq!(
+        "100x50+8+16".parse(),
+        Ok(JpegCrop {
+            x: 8,
+            y: 16,
+            width: 100,
+            height: 50
+        })
+    );
+    assert_eq!(
+        "100x50".parse(),
+        Ok(JpegCrop {
+            x: 0,
+            y: 0,
+            width: 100,
+            height: 50
+        })
+    );
+    assert!("100+8+16".parse::<JpegCrop>().is_err());
+    assert!("0x50".parse::<JpegCrop>().is_err());
+}
@@ -0,0 +1,159 @@
+use zune_image::errors::{ImageErrors, ImgEncodeErrors};
+
+const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Frame area is left as is before rendering the next frame
+const DISPOSE_OP_NONE: u8 = 0;
+/// Frame replaces the contents of its area, including alpha
+const BLEND_OP_SOURCE: u8 = 0;
+
+/// A chunk of the PNG file
+pub(crate) struct Chunk<'a> {
+    pub(crate) name: [u8; 4],
+    pub(crate) data: &'a [u8],
+}
+
+/// Split a PNG file into its chunks
+pub(crate) fn chunks(data: &[u8]) -> Result<Vec<Chunk<'_>>, ImageErrors> {
+    if !data.starts_with(SIGNATURE) {
+        return Err(ImageErrors::EncodeErrors(
+            ImgEncodeErrors::ImageEncodeErrors("Not a PNG file".to_string()),
+        ));
+    }
+
+    let mut chunks = vec![];
+    let mut offset = SIGNATURE.len();
+
+    while let Some(header) = data.get(offset..offset + 8) {
+        let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+
+        let chunk_data = data.get(offset + 8..offset + 8 + len).ok_or_else(|| {
+            ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(
+                "Truncated PNG chunk".to_string(),
+            ))
+        })?;
+
+        chunks.push(Chunk {
+            name: [header[4], header[5], header[6], header[7]],
+            data: chunk_data,
+        });
+
+        // chunk length, name, data and crc
+        offset += 12 + len;
+    }
+
+    Ok(chunks)
+}
+
+/// Animation frame compressed by oxipng into a standalone PNG
+pub(crate) struct Frame {
+    pub(crate) png: Vec<u8>,
+    /// Position of the frame on the canvas
+    pub(crate) offset: (u32, u32),
+    /// Display time in milliseconds
+    pub(crate) duration: u32,
+}
+
+/// Assemble compressed `frames` into an APNG file
+///
+/// The first frame covers the whole canvas and is also the default image,
+/// its ancillary chunks are carried over into the output. Each frame is drawn
+/// over the previous ones, so the following frames may cover only the changed area.
+pub(crate) fn assemble(frames: &[Frame], loop_count: u32) -> Result<Vec<u8>, ImageErrors> {
+    let first = chunks(&frames[0].png)?;
+    let ihdr = header(&first)?;
+
+    let mut out = SIGNATURE.to_vec();
+    let mut sequence = 0;
+
+    write_chunk(&mut out, b"IHDR", ihdr);
+
+    let mut actl = (frames.len() as u32).to_be_bytes().to_vec();
+    actl.extend_from_slice(&loop_count.to_be_bytes());
+    write_chunk(&mut out, b"acTL", &actl);
+
+    first
+        .iter()
+        .filter(|chunk| !matches!(&chunk.name, b"IHDR" | b"IDAT" | b"IEND"))
+        .for_each(|chunk| write_chunk(&mut out, &chunk.name, chunk.data));
+
+    for (index, frame) in frames.iter().enumerate() {
+        let frame_chunks = chunks(&frame.png)?;
+        let frame_ihdr = header(&frame_chunks)?;
+
+        // every frame has to share color type, bit depth and interlacing of the canvas
+        if frame_ihdr[8..] != ihdr[8..] {
+            return Err(ImageErrors::EncodeErrors(
+                ImgEncodeErrors::ImageEncodeErrors(
+                    "APNG frames have different pixel formats".to_string(),
+                ),
+            ));
+        }
+
+        write_chunk(&mut out, b"fcTL", &fctl(sequence, frame, &frame_ihdr[..8]));
+        sequence += 1;
+
+        let idat = frame_chunks.iter().filter(|chunk| &chunk.name == b"IDAT");
+
+        if index == 0 {
+            idat.for_each(|chunk| write_chunk(&mut out, b"IDAT", chunk.data));
+        } else {
+            let mut fdat = sequence.to_be_bytes().to_vec();
+            idat.for_each(|chunk| fdat.extend_from_slice(chunk.data));
+
+            write_chunk(&mut out, b"fdAT", &fdat);
+            sequence += 1;
+        }
+    }
+
+    write_chunk(&mut out, b"IEND", &[]);
+
+    Ok(out)
+}
+
+fn header<'a>(chunks: &[Chunk<'a>]) -> Result<&'a [u8], ImageErrors> {
+    chunks
+        .iter()
+        .find(|chunk| &chunk.name == b"IHDR" && chunk.data.len() == 13)
+        .map(|chunk| chunk.data)
+        .ok_or_else(|| {
+            ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(
+                "PNG header is missing".to_string(),
+            ))
+        })
+}
+
+/// Frame control data, `dimensions` are the width and height from the frame header
+fn fctl(sequence: u32, frame: &Frame, dimensions: &[u8]) -> Vec<u8> {
+    let mut data = sequence.to_be_bytes().to_vec();
+
+    data.extend_from_slice(dimensions);
+    data.extend_from_slice(&frame.offset.0.to_be_bytes());
+    data.extend_from_slice(&frame.offset.1.to_be_bytes());
+    // delay is a fraction of a second
+    data.extend_from_slice(&(frame.duration.min(u16::MAX as u32) as u16).to_be_bytes());
+    data.extend_from_slice(&1000u16.to_be_bytes());
+    data.push(DISPOSE_OP_NONE);
+    data.push(BLEND_OP_SOURCE);
+
+    data
+}
+
+fn write_chunk(out: &mut Vec<u8>, name: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend_from_slice(name);
+    out.extend_from_slice(data);
+    out.extend_from_slice(&crc32(name.iter().chain(data)).to_be_bytes());
+}
+
+fn crc32<'a>(bytes: impl Iterator<Item = &'a u8>) -> u32 {
+    !bytes.fold(!0, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| {
+            if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
@@ -10,13 +10,96 @@ use zune_image::{
     traits::EncoderTrait,
 };
 
-/// Alias to [`oxipng::Options`]
-pub type OxiPngOptions = oxipng::Options;
+use super::apng;
+use crate::{
+    codecs::{frame_duration_ms, icc, IndexedPixels},
+    options::{check_range, OptionsError, Validate},
+};
+
+/// Advanced options for OxiPNG encoding
+///
+/// Every option of oxipng is available through [`OxiPngOptions::advanced`],
+/// which can be created from [`oxipng::Options`] with `into()`.
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct OxiPngOptions {
+    /// Optimization preset of oxipng, higher is slower and produces smaller files. `0..=6`
+    pub preset: u8,
+    /// Write interlaced image with the Adam7 method
+    ///
+    /// Ignored for animated images.
+    pub interlace: bool,
+    /// Alter colors of fully transparent pixels to improve compression
+    pub optimize_alpha: bool,
+    /// Number of times animation is played, `0` means infinite
+    pub loop_count: u32,
+    /// Options passed to oxipng as is, `preset`, `interlace` and `optimize_alpha` are ignored when set
+    ///
+    /// Not serialized, configs are limited to the fields above.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub advanced: Option<oxipng::Options>,
+}
+
+impl Default for OxiPngOptions {
+    fn default() -> Self {
+        Self {
+            preset: 2,
+            interlace: false,
+            optimize_alpha: false,
+            loop_count: 0,
+            advanced: None,
+        }
+    }
+}
+
+impl From<oxipng::Options> for OxiPngOptions {
+    fn from(options: oxipng::Options) -> Self {
+        Self {
+            advanced: Some(options),
+            ..Default::default()
+        }
+    }
+}
+
+impl OxiPngOptions {
+    /// Default options with optimization `preset`, same as `-o` of oxipng. `0..=6`
+    pub fn from_preset(preset: u8) -> Self {
+        Self {
+            preset,
+            ..Default::default()
+        }
+    }
+
+    /// Options passed to oxipng
+    fn to_oxipng(&self) -> oxipng::Options {
+        if let Some(options) = &self.advanced {
+            return options.clone();
+        }
+
+        let mut options = oxipng::Options::from_preset(self.preset);
+
+        options.interlace = self.interlace.then_some(oxipng::Interlacing::Adam7);
+        options.optimize_alpha = self.optimize_alpha;
+
+        options
+    }
+}
+
+impl Validate for OxiPngOptions {
+    fn validate(&self) -> Result<(), OptionsError> {
+        check_range("preset", self.preset, 0..=6)
+    }
+}
 
 /// A OxiPNG encoder
 #[derive(Default)]
 pub struct OxiPngEncoder {
     options: OxiPngOptions,
+    indexed: Option<IndexedPixels>,
 }
 
 impl OxiPngEncoder {
@@ -26,7 +109,23 @@ impl OxiPngEncoder {
     }
     /// Create a new encoder with specified options
     pub fn new_with_options(options: OxiPngOptions) -> OxiPngEncoder {
-        OxiPngEncoder { options }
+        OxiPngEncoder {
+            options,
+            ..Default::default()
+        }
+    }
+    /// Create a new encoder with specified options, rejecting out of range values
+    pub fn try_new_with_options(options: OxiPngOptions) -> Result<OxiPngEncoder, OptionsError> {
+        options.validate()?;
+
+        Ok(OxiPngEncoder::new_with_options(options))
+    }
+    /// Write an indexed PNG with the palette in the given order
+    ///
+    /// `indexed` pixels usually come from `Quantize::quantize` of the encoded image.
+    /// If they don't match the image size, the image is written in its own colorspace.
+    pub fn set_indexed(&mut self, indexed: IndexedPixels) {
+        self.indexed = Some(indexed);
     }
 }
 
@@ -40,85 +139,43 @@ impl EncoderTrait for OxiPngEncoder {
         image: &Image,
         sink: T,
     ) -> Result<usize, ImageErrors> {
-        let (width, height) = image.dimensions();
-
-        // inlined `to_u8` method because its private
-        let colorspace = image.colorspace();
-        let data = if image.depth() == BitDepth::Eight {
-            image.flatten_frames::<u8>()
-        } else if image.depth() == BitDepth::Sixteen {
-            image
-                .frames_ref()
-                .iter()
-                .map(|z| z.u16_to_native_endian(colorspace))
-                .collect()
-        } else {
-            unreachable!()
-        }
-        .into_iter()
-        .next()
-        .unwrap();
-
-        #[allow(unused_mut)]
-        let mut img = oxipng::RawImage::new(
-            width as u32,
-            height as u32,
-            match image.colorspace() {
-                ColorSpace::Luma => oxipng::ColorType::Grayscale {
-                    transparent_shade: None,
-                },
-                ColorSpace::RGB => oxipng::ColorType::RGB {
-                    transparent_color: None,
-                },
-
-                ColorSpace::LumaA => oxipng::ColorType::GrayscaleAlpha,
-                ColorSpace::RGBA => oxipng::ColorType::RGBA,
-
-                cs => {
-                    return Err(ImageErrors::EncodeErrors(
-                        ImgEncodeErrors::UnsupportedColorspace(cs, self.supported_colorspaces()),
-                    ))
-                }
-            },
-            match image.depth() {
-                BitDepth::Eight => oxipng::BitDepth::Eight,
-                BitDepth::Sixteen => oxipng::BitDepth::Sixteen,
-                d => {
-                    return Err(ImageErrors::EncodeErrors(ImgEncodeErrors::Generic(
-                        format!("{d:?} is not supported"),
-                    )))
-                }
-            },
-            data,
-        )
-        .map_err(|e| ImgEncodeErrors::ImageEncodeErrors(e.to_string()))?;
-
-        #[cfg(feature = "metadata")]
+        let pixels = match self
+            .indexed
+            .as_ref()
+            .and_then(|indexed| indexed_pixels(image, indexed))
         {
-            use exif::experimental::Writer;
+            Some(pixels) => pixels,
+            None => {
+                if self.indexed.is_some() {
+                    log::warn!("Indexed pixels don't match the image, writing it as is");
+                }
 
-            let mut buf = std::io::Cursor::new(vec![]);
+                self.pixels(image)?
+            }
+        };
 
-            if let Some(fields) = &image.metadata().exif() {
-                let mut writer = Writer::new();
+        let mut options = self.options.to_oxipng();
 
-                for metadatum in *fields {
-                    writer.push_field(metadatum);
-                }
-                let result = writer.write(&mut buf, false);
-                if result.is_ok() {
-                    img.add_png_chunk(*b"eXIf", buf.into_inner());
-                } else {
-                    log::warn!("Writing exif failed {:?}", result);
-                }
-            }
+        if matches!(pixels.color_type, oxipng::ColorType::Indexed { .. }) {
+            // keep the palette order and size as given
+            options.bit_depth_reduction = false;
+            options.color_type_reduction = false;
+            options.palette_reduction = false;
+            options.grayscale_reduction = false;
         }
 
         let mut writer = ZWriter::new(sink);
 
-        let result = img
-            .create_optimized_png(&self.options)
-            .map_err(|e| ImgEncodeErrors::ImageEncodeErrors(e.to_string()))?;
+        let result = if image.is_animated() {
+            self.encode_animated(image, &pixels, options)?
+        } else {
+            let (width, height) = image.dimensions();
+            let data = pixels.frames[0].clone();
+
+            raw_image(image, &pixels, (width, height), data, true)?
+                .create_optimized_png(&options)
+                .map_err(|e| ImgEncodeErrors::ImageEncodeErrors(e.to_string()))?
+        };
 
         writer.write(&result).map_err(|e| {
             ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
@@ -150,6 +207,278 @@ impl EncoderTrait for OxiPngEncoder {
             _ => BitDepth::Eight,
         }
     }
+
+    fn supports_animated_images(&self) -> bool {
+        true
+    }
+}
+
+/// Frames of the image flattened into a PNG pixel format
+struct Pixels {
+    /// One byte or sample per pixel for indexed images, samples are packed on write
+    frames: Vec<Vec<u8>>,
+    color_type: oxipng::ColorType,
+    bit_depth: oxipng::BitDepth,
+    /// Size of an unpacked pixel in bytes
+    pixel_size: usize,
+}
+
+impl OxiPngEncoder {
+    /// Frames in the colorspace and depth of the `image`
+    fn pixels(&self, image: &Image) -> Result<Pixels, ImageErrors> {
+        let colorspace = image.colorspace();
+
+        let color_type = match colorspace {
+            ColorSpace::Luma => oxipng::ColorType::Grayscale {
+                transparent_shade: None,
+            },
+            ColorSpace::RGB => oxipng::ColorType::RGB {
+                transparent_color: None,
+            },
+
+            ColorSpace::LumaA => oxipng::ColorType::GrayscaleAlpha,
+            ColorSpace::RGBA => oxipng::ColorType::RGBA,
+
+            cs => {
+                return Err(ImageErrors::EncodeErrors(
+                    ImgEncodeErrors::UnsupportedColorspace(cs, self.supported_colorspaces()),
+                ))
+            }
+        };
+
+        // inlined `to_u8` method because its private
+        let (frames, bit_depth) = match image.depth() {
+            BitDepth::Eight => (image.flatten_frames::<u8>(), oxipng::BitDepth::Eight),
+            BitDepth::Sixteen => (
+                image
+                    .frames_ref()
+                    .iter()
+                    // PNG stores samples in big endian
+                    .map(|z| {
+                        z.u16_to_native_endian(colorspace)
+                            .chunks_exact(2)
+                            .flat_map(|b| u16::from_ne_bytes([b[0], b[1]]).to_be_bytes())
+                            .collect()
+                    })
+                    .collect(),
+                oxipng::BitDepth::Sixteen,
+            ),
+            d => {
+                return Err(ImageErrors::EncodeErrors(ImgEncodeErrors::Generic(
+                    format!("{d:?} is not supported"),
+                )))
+            }
+        };
+
+        Ok(Pixels {
+            frames,
+            pixel_size: colorspace.num_components() * bit_depth as usize / 8,
+            color_type,
+            bit_depth,
+        })
+    }
+
+    /// Compress every frame with oxipng and assemble them into APNG
+    ///
+    /// Following frames store only the area changed since the previous frame.
+    fn encode_animated(
+        &self,
+        image: &Image,
+        pixels: &Pixels,
+        mut options: oxipng::Options,
+    ) -> Result<Vec<u8>, ImageErrors> {
+        let (width, height) = image.dimensions();
+        let pixel_size = pixels.pixel_size;
+        let frames = &pixels.frames;
+
+        // oxipng does the same for APNG input, all frames have to share one pixel format
+        options.bit_depth_reduction = false;
+        options.color_type_reduction = false;
+        options.palette_reduction = false;
+        options.grayscale_reduction = false;
+        options.interlace = None;
+
+        let mut encoded = Vec::with_capacity(frames.len());
+
+        for (index, (data, frame)) in frames.iter().zip(image.frames_ref()).enumerate() {
+            let (x, y, w, h) = match index {
+                0 => (0, 0, width, height),
+                _ => changed_area(&frames[index - 1], data, width, pixel_size)
+                    // frame is still needed to keep its duration
+                    .unwrap_or((0, 0, 1, 1)),
+            };
+
+            let data = data
+                .chunks_exact(width * pixel_size)
+                .skip(y)
+                .take(h)
+                .flat_map(|row| &row[x * pixel_size..(x + w) * pixel_size])
+                .copied()
+                .collect();
+
+            let png = raw_image(image, pixels, (w, h), data, index == 0)?
+                .create_optimized_png(&options)
+                .map_err(|e| ImgEncodeErrors::ImageEncodeErrors(e.to_string()))?;
+
+            encoded.push(apng::Frame {
+                png,
+                offset: (x as u32, y as u32),
+                duration: frame_duration_ms(frame),
+            });
+        }
+
+        apng::assemble(&encoded, self.options.loop_count)
+    }
+}
+
+/// Pixels of the `image` written from `indexed` data
+///
+/// Returns `None` if the indexed data doesn't match frames of the image.
+fn indexed_pixels(image: &Image, indexed: &IndexedPixels) -> Option<Pixels> {
+    let (width, height) = image.dimensions();
+    let palette = &indexed.palette;
+
+    if palette.is_empty()
+        || palette.len() > 256
+        || indexed.frames.len() != image.frames_ref().len()
+        || indexed
+            .frames
+            .iter()
+            .any(|frame| frame.len() != width * height)
+        || indexed
+            .frames
+            .iter()
+            .flatten()
+            .any(|&index| index as usize >= palette.len())
+    {
+        return None;
+    }
+
+    let bit_depth = match palette.len() {
+        0..=2 => oxipng::BitDepth::One,
+        3..=4 => oxipng::BitDepth::Two,
+        5..=16 => oxipng::BitDepth::Four,
+        _ => oxipng::BitDepth::Eight,
+    };
+
+    Some(Pixels {
+        frames: indexed.frames.clone(),
+        color_type: oxipng::ColorType::Indexed {
+            palette: palette
+                .iter()
+                .map(|&[r, g, b, a]| oxipng::RGBA8::new(r, g, b, a))
+                .collect(),
+        },
+        bit_depth,
+        pixel_size: 1,
+    })
+}
+
+/// Create oxipng image of `dimensions` from unpacked `data` in the format of `pixels`
+fn raw_image(
+    image: &Image,
+    pixels: &Pixels,
+    dimensions: (usize, usize),
+    data: Vec<u8>,
+    with_metadata: bool,
+) -> Result<oxipng::RawImage, ImageErrors> {
+    let bits = pixels.bit_depth as usize;
+
+    let data = if bits < 8 {
+        pack_rows(&data, dimensions.0, bits)
+    } else {
+        data
+    };
+
+    let mut img = oxipng::RawImage::new(
+        dimensions.0 as u32,
+        dimensions.1 as u32,
+        pixels.color_type.clone(),
+        pixels.bit_depth,
+        data,
+    )
+    .map_err(|e| ImgEncodeErrors::ImageEncodeErrors(e.to_string()))?;
+
+    #[cfg(feature = "metadata")]
+    if with_metadata {
+        use exif::experimental::Writer;
+
+        let mut buf = std::io::Cursor::new(vec![]);
+
+        if let Some(fields) = &image.metadata().exif() {
+            let mut writer = Writer::new();
+
+            for metadatum in *fields {
+                writer.push_field(metadatum);
+            }
+            let result = writer.write(&mut buf, false);
+            if result.is_ok() {
+                img.add_png_chunk(*b"eXIf", buf.into_inner());
+            } else {
+                log::warn!("Writing exif failed {:?}", result);
+            }
+        }
+    }
+
+    if let Some(profile) = image.metadata().icc_chunk().filter(|_| with_metadata) {
+        if icc::is_srgb(profile) {
+            img.add_png_chunk(*b"sRGB", vec![icc::rendering_intent(profile)]);
+            // BT.709 primaries, sRGB transfer, RGB and full range
+            img.add_png_chunk(*b"cICP", vec![1, 13, 0, 1]);
+        } else {
+            img.add_icc_profile(profile);
+        }
+    }
+
+    Ok(img)
+}
+
+/// Pack rows of one byte per sample into `bits` per sample, most significant bits first
+fn pack_rows(data: &[u8], width: usize, bits: usize) -> Vec<u8> {
+    let per_byte = 8 / bits;
+
+    data.chunks_exact(width)
+        .flat_map(|row| {
+            row.chunks(per_byte).map(|samples| {
+                samples
+                    .iter()
+                    .enumerate()
+                    .fold(0u8, |byte, (i, &s)| byte | s << (8 - bits * (i + 1)))
+            })
+        })
+        .collect()
+}
+
+/// Bounding box of pixels that differ between `previous` and `current` frames
+/// as `(x, y, width, height)`, `None` if frames are identical
+fn changed_area(
+    previous: &[u8],
+    current: &[u8],
+    width: usize,
+    pixel_size: usize,
+) -> Option<(usize, usize, usize, usize)> {
+    let mut area: Option<(usize, usize, usize, usize)> = None;
+
+    let rows = previous
+        .chunks_exact(width * pixel_size)
+        .zip(current.chunks_exact(width * pixel_size));
+
+    for (y, (a, b)) in rows.enumerate() {
+        if a == b {
+            continue;
+        }
+
+        let pixels = || a.chunks_exact(pixel_size).zip(b.chunks_exact(pixel_size));
+        let left = pixels().position(|(a, b)| a != b).unwrap_or(0);
+        let right = width - 1 - pixels().rev().position(|(a, b)| a != b).unwrap_or(0);
+
+        area = Some(match area {
+            None => (left, y, right, y),
+            Some((x0, y0, x1, _)) => (x0.min(left), y0, x1.max(right), y),
+        });
+    }
+
+    area.map(|(x0, y0, x1, y1)| (x0, y0, x1 - x0 + 1, y1 - y0 + 1))
 }
 
 #[cfg(test)]
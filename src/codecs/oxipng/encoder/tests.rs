@@ -131,6 +131,27 @@ fn encode_u16() {
     assert!(result.is_ok());
 }
 
+#[test]
+fn encode_u16_round_trip() {
+    let image = Image::from_fn(16, 16, ColorSpace::RGB, |x, y, px: &mut [u16; 4]| {
+        px[0] = (x * 4000 + 1) as u16;
+        px[1] = (y * 3000 + 2) as u16;
+        px[2] = 0x1234;
+    });
+    let mut encoder = OxiPngEncoder::new();
+
+    let mut buf = Cursor::new(vec![]);
+    encoder.encode(&image, &mut buf).unwrap();
+
+    let decoded = crate::decode(Cursor::new(buf.into_inner())).unwrap();
+
+    assert_eq!(decoded.depth(), BitDepth::Sixteen);
+    assert_eq!(
+        decoded.frames_ref()[0].u16_to_native_endian(ColorSpace::RGB),
+        image.frames_ref()[0].u16_to_native_endian(ColorSpace::RGB)
+    );
+}
+
 #[test]
 fn encode_f32() {
     let image = create_test_image_f32(200, 200, ColorSpace::RGB);
@@ -156,3 +177,171 @@ fn encode_animated() {
 
     assert!(result.is_ok());
 }
+
+#[test]
+fn encode_apng() {
+    use zune_image::frame::Frame;
+
+    let first = vec![0; 10 * 10 * 3];
+    let mut second = first.clone();
+    second[(4 * 10 + 3) * 3] = 255;
+
+    let frames = vec![
+        Frame::from_u8(&first, ColorSpace::RGB, 50, 1000),
+        Frame::from_u8(&second, ColorSpace::RGB, 120, 1000),
+        Frame::from_u8(&second, ColorSpace::RGB, 30, 1000),
+    ];
+    let image = Image::new_frames(frames, BitDepth::Eight, 10, 10, ColorSpace::RGB);
+
+    let mut encoder = OxiPngEncoder::new_with_options(OxiPngOptions {
+        loop_count: 3,
+        ..Default::default()
+    });
+
+    let mut buf = Cursor::new(vec![]);
+    encoder.encode(&image, &mut buf).unwrap();
+
+    let data = buf.into_inner();
+
+    let info = crate::probe(Cursor::new(&data)).unwrap();
+    assert_eq!(info.dimensions, (10, 10));
+    assert_eq!(info.frame_count, 3);
+
+    let chunks = super::apng::chunks(&data).unwrap();
+
+    // frame count and loop count
+    let actl = chunks.iter().find(|chunk| &chunk.name == b"acTL").unwrap();
+    assert_eq!(actl.data, &[0, 0, 0, 3, 0, 0, 0, 3]);
+
+    let fctl = chunks
+        .iter()
+        .filter(|chunk| &chunk.name == b"fcTL")
+        .map(|chunk| chunk.data)
+        .collect::<Vec<_>>();
+
+    assert_eq!(fctl.len(), 3);
+    assert_eq!(
+        chunks.iter().filter(|chunk| &chunk.name == b"fdAT").count(),
+        2
+    );
+
+    // width, height, x and y offsets
+    assert_eq!(
+        &fctl[0][4..20],
+        &[0, 0, 0, 10, 0, 0, 0, 10, 0, 0, 0, 0, 0, 0, 0, 0]
+    );
+    assert_eq!(
+        &fctl[1][4..20],
+        &[0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 3, 0, 0, 0, 4]
+    );
+    // delay numerator and denominator
+    assert_eq!(&fctl[1][20..24], &[0, 120, 3, 232]);
+    assert_eq!(&fctl[2][20..24], &[0, 30, 3, 232]);
+}
+
+#[test]
+fn encode_indexed() {
+    let palette = vec![[0, 0, 255, 255], [255, 0, 0, 128], [0, 255, 0, 255]];
+    let indices = (0..8 * 8)
+        .map(|i| (i % palette.len()) as u8)
+        .collect::<Vec<_>>();
+
+    let pixels = indices
+        .iter()
+        .flat_map(|&i| palette[i as usize])
+        .collect::<Vec<u8>>();
+    let image = Image::from_u8(&pixels, 8, 8, ColorSpace::RGBA);
+
+    let mut encoder = OxiPngEncoder::new();
+    encoder.set_indexed(IndexedPixels {
+        palette,
+        frames: vec![indices],
+    });
+
+    let mut buf = Cursor::new(vec![]);
+    encoder.encode(&image, &mut buf).unwrap();
+
+    let data = buf.into_inner();
+    let chunks = super::apng::chunks(&data).unwrap();
+    let chunk = |name: &[u8; 4]| {
+        chunks
+            .iter()
+            .find(|chunk| &chunk.name == name)
+            .map(|chunk| chunk.data)
+            .unwrap()
+    };
+
+    // bit depth and color type
+    assert_eq!(&chunk(b"IHDR")[8..10], &[2, 3]);
+    assert_eq!(chunk(b"PLTE"), &[0, 0, 255, 255, 0, 0, 0, 255, 0]);
+    assert_eq!(chunk(b"tRNS"), &[255, 128]);
+}
+
+#[test]
+fn encode_indexed_mismatch() {
+    let image = create_test_image_u8(20, 20, ColorSpace::RGB);
+
+    let mut encoder = OxiPngEncoder::new();
+    encoder.set_indexed(IndexedPixels {
+        palette: vec![[1, 2, 3, 255]],
+        frames: vec![vec![0; 10 * 10]],
+    });
+
+    let mut buf = Cursor::new(vec![]);
+    encoder.encode(&image, &mut buf).unwrap();
+
+    let data = buf.into_inner();
+    let chunks = super::apng::chunks(&data).unwrap();
+
+    assert!(chunks.iter().all(|chunk| &chunk.name != b"PLTE"));
+}
+
+#[test]
+fn encode_advanced() {
+    let image = create_test_image_u8(20, 20, ColorSpace::RGB);
+
+    let mut options = oxipng::Options::from_preset(1);
+    options.interlace = Some(oxipng::Interlacing::Adam7);
+    options.strip = oxipng::StripChunks::All;
+
+    let mut buf = Cursor::new(vec![]);
+    OxiPngEncoder::new_with_options(options.into())
+        .encode(&image, &mut buf)
+        .unwrap();
+
+    let data = buf.into_inner();
+    let chunks = super::apng::chunks(&data).unwrap();
+
+    let ihdr = chunks.iter().find(|chunk| &chunk.name == b"IHDR").unwrap();
+
+    // interlace method is the last byte of IHDR
+    assert_eq!(ihdr.data[12], 1);
+}
+
+#[test]
+fn encode_icc() {
+    let chunk_names = |icc: Vec<u8>| {
+        let mut image = create_test_image_u8(20, 20, ColorSpace::RGB);
+        image.metadata_mut().set_icc_chunk(icc);
+
+        let mut buf = Cursor::new(vec![]);
+        OxiPngEncoder::new().encode(&image, &mut buf).unwrap();
+
+        super::apng::chunks(&buf.into_inner())
+            .unwrap()
+            .iter()
+            .map(|chunk| chunk.name)
+            .collect::<Vec<_>>()
+    };
+
+    let srgb = chunk_names(std::fs::read("tests/files/icc/tinysrgb.icc").unwrap());
+
+    assert!(srgb.contains(b"sRGB"));
+    assert!(srgb.contains(b"cICP"));
+    assert!(!srgb.contains(b"iCCP"));
+
+    let wide_gamut = chunk_names(create_wide_gamut_profile());
+
+    assert!(wide_gamut.contains(b"iCCP"));
+    assert!(!wide_gamut.contains(b"sRGB"));
+}
@@ -1,3 +1,4 @@
+mod apng;
 mod encoder;
 
 pub use encoder::*;
@@ -0,0 +1,237 @@
+use rawloader::{Orientation, RawImage, RawImageData};
+
+use super::RawTransfer;
+
+/// Linear sRGB primaries from CIE XYZ with D65 white point
+const XYZ_TO_SRGB: [[f32; 3]; 3] = [
+    [3.240_454_2, -1.537_138_5, -0.498_531_4],
+    [-0.969_266, 1.876_010_8, 0.041_556],
+    [0.055_643_4, -0.204_025_9, 1.057_225_2],
+];
+
+/// Dimensions of the developed image, after crop and orientation
+pub(super) fn dimensions(raw: &RawImage) -> (usize, usize) {
+    let (width, height) = cropped_dimensions(raw);
+
+    if swaps_axes(raw.orientation) {
+        (height, width)
+    } else {
+        (width, height)
+    }
+}
+
+fn cropped_dimensions(raw: &RawImage) -> (usize, usize) {
+    let [top, right, bottom, left] = raw.crops;
+
+    match (
+        raw.width.checked_sub(left + right),
+        raw.height.checked_sub(top + bottom),
+    ) {
+        (Some(width), Some(height)) if width > 0 && height > 0 => (width, height),
+        _ => (raw.width, raw.height),
+    }
+}
+
+fn swaps_axes(orientation: Orientation) -> bool {
+    matches!(
+        orientation,
+        Orientation::Transpose
+            | Orientation::Rotate90
+            | Orientation::Transverse
+            | Orientation::Rotate270
+    )
+}
+
+/// Develop sensor data of `raw` into 16-bit RGB samples
+pub(super) fn develop(raw: &RawImage, transfer: RawTransfer) -> Vec<u16> {
+    let (width, height) = cropped_dimensions(raw);
+    let (left, top) = if (width, height) == (raw.width, raw.height) {
+        (0, 0)
+    } else {
+        (raw.crops[3], raw.crops[0])
+    };
+
+    let samples = normalize(raw);
+    let matrix = color_matrix(raw);
+    let wb = white_balance(raw);
+
+    let mut pixels = Vec::with_capacity(width * height);
+
+    for y in top..top + height {
+        for x in left..left + width {
+            let cam = if raw.cpp == 3 {
+                let offset = (y * raw.width + x) * 3;
+                [samples[offset], samples[offset + 1], samples[offset + 2]]
+            } else {
+                demosaic(raw, &samples, x, y)
+            };
+
+            let cam = [cam[0] * wb[0], cam[1] * wb[1], cam[2] * wb[2]];
+
+            pixels.push(matrix.map(|row| {
+                let value = row[0] * cam[0] + row[1] * cam[1] + row[2] * cam[2];
+
+                let value = match transfer {
+                    RawTransfer::Linear => value.clamp(0., 1.),
+                    RawTransfer::Srgb => srgb_transfer(value.clamp(0., 1.)),
+                };
+
+                (value * 65535.).round() as u16
+            }));
+        }
+    }
+
+    orient(&pixels, width, height, raw.orientation)
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// RGB index of the CFA color at (`x`, `y`), second green is treated as green
+fn cfa_color(raw: &RawImage, x: usize, y: usize) -> usize {
+    match raw.cfa.color_at(y, x) {
+        3 => 1,
+        color => color.min(2),
+    }
+}
+
+/// Samples scaled to `0.0..=1.0` between the black and white levels
+fn normalize(raw: &RawImage) -> Vec<f32> {
+    let data = match &raw.data {
+        RawImageData::Integer(data) => data,
+        // float data is stored normalized
+        RawImageData::Float(data) => return data.clone(),
+    };
+
+    data.iter()
+        .enumerate()
+        .map(|(index, &value)| {
+            let color = match raw.cpp {
+                3 => index % 3,
+                _ => raw
+                    .cfa
+                    .color_at(index / raw.width, index % raw.width)
+                    .min(3),
+            };
+
+            let black = f32::from(raw.blacklevels[color]);
+            let white = f32::from(raw.whitelevels[color]);
+
+            ((f32::from(value) - black) / (white - black).max(1.)).max(0.)
+        })
+        .collect()
+}
+
+/// Bilinear interpolation of the colors missing at (`x`, `y`)
+fn demosaic(raw: &RawImage, samples: &[f32], x: usize, y: usize) -> [f32; 3] {
+    let mut sum = [0.; 3];
+    let mut count = [0u32; 3];
+
+    for ny in y.saturating_sub(1)..(y + 2).min(raw.height) {
+        for nx in x.saturating_sub(1)..(x + 2).min(raw.width) {
+            let color = cfa_color(raw, nx, ny);
+
+            sum[color] += samples[ny * raw.width + nx];
+            count[color] += 1;
+        }
+    }
+
+    let own = cfa_color(raw, x, y);
+    let own_value = samples[y * raw.width + x];
+
+    [0, 1, 2].map(|color| match color {
+        _ if color == own => own_value,
+        _ if count[color] == 0 => 0.,
+        _ => sum[color] / count[color] as f32,
+    })
+}
+
+/// As shot white balance multipliers relative to green
+fn white_balance(raw: &RawImage) -> [f32; 3] {
+    let [r, g, b, _] = raw.wb_coeffs;
+
+    if [r, g, b].iter().any(|c| !c.is_finite() || *c <= 0.) {
+        return [1.; 3];
+    }
+
+    [r / g, 1., b / g]
+}
+
+/// Matrix converting white balanced camera colors to linear sRGB
+fn color_matrix(raw: &RawImage) -> [[f32; 3]; 3] {
+    let unknown = raw.xyz_to_cam.iter().flatten().all(|&v| v == 0.);
+
+    if unknown {
+        log::warn!("No color matrix for {} {}", raw.clean_make, raw.clean_model);
+
+        return [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]];
+    }
+
+    let cam_to_xyz = raw.cam_to_xyz_normalized();
+
+    XYZ_TO_SRGB.map(|row| [0, 1, 2].map(|col| (0..3).map(|k| row[k] * cam_to_xyz[k][col]).sum()))
+}
+
+pub(super) fn srgb_transfer(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1. / 2.4) - 0.055
+    }
+}
+
+/// Turn `pixels` of `width` and `height` upright according to `orientation`
+pub(super) fn orient<T: Copy>(
+    pixels: &[T],
+    width: usize,
+    height: usize,
+    orientation: Orientation,
+) -> Vec<T> {
+    let (out_width, out_height) = if swaps_axes(orientation) {
+        (height, width)
+    } else {
+        (width, height)
+    };
+
+    let mut out = Vec::with_capacity(pixels.len());
+
+    for y in 0..out_height {
+        for x in 0..out_width {
+            // source pixel of the output position
+            let (sx, sy) = match orientation {
+                Orientation::HorizontalFlip => (width - 1 - x, y),
+                Orientation::Rotate180 => (width - 1 - x, height - 1 - y),
+                Orientation::VerticalFlip => (x, height - 1 - y),
+                Orientation::Transpose => (y, x),
+                Orientation::Rotate90 => (y, height - 1 - x),
+                Orientation::Transverse => (width - 1 - y, height - 1 - x),
+                Orientation::Rotate270 => (width - 1 - y, x),
+                _ => (x, y),
+            };
+
+            out.push(pixels[sy * width + sx]);
+        }
+    }
+
+    out
+}
+
+/// ICC profile with sRGB primaries and linear tone curves
+#[cfg(feature = "icc")]
+pub(super) fn linear_srgb_profile() -> Result<Vec<u8>, zune_image::errors::ImageErrors> {
+    use lcms2::{CIExyY, CIExyYTRIPLE, Profile, ToneCurve, ToneCurveRef};
+
+    let primary = |x, y| CIExyY { x, y, Y: 1. };
+    let primaries = CIExyYTRIPLE {
+        Red: primary(0.64, 0.33),
+        Green: primary(0.30, 0.60),
+        Blue: primary(0.15, 0.06),
+    };
+
+    let curve = ToneCurve::new(1.);
+    let curves: [&ToneCurveRef; 3] = [&curve, &curve, &curve];
+
+    Profile::new_rgb(&primary(0.3127, 0.3290), &primaries, &curves)
+        .and_then(|profile| profile.icc())
+        .map_err(|e| zune_image::errors::ImageErrors::ImageDecodeErrors(e.to_string()))
+}
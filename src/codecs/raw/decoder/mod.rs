@@ -0,0 +1,156 @@
+use std::{
+    io::{Cursor, Read},
+    marker::PhantomData,
+};
+
+use rawloader::RawImage;
+use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace, options::DecoderOptions};
+use zune_image::{errors::ImageErrors, frame::Frame, image::Image, traits::DecoderTrait};
+
+mod develop;
+mod preview;
+
+/// Transfer function of the developed image
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RawTransfer {
+    /// Linear light with sRGB primaries
+    ///
+    /// With `icc` feature enabled, a matching linear ICC profile is attached,
+    /// so the image can be converted with
+    /// [`ApplySRGB`](crate::operations::icc::ApplySRGB).
+    Linear,
+    /// sRGB transfer curve
+    #[default]
+    Srgb,
+}
+
+/// Advanced options for RAW decoding
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RawDecoderOptions {
+    /// Transfer function of the developed image
+    pub transfer: RawTransfer,
+    /// Decode the largest embedded JPEG preview instead of the sensor data
+    ///
+    /// Much faster than developing the image, but the preview may be smaller
+    /// and is processed by the camera.
+    pub preview: bool,
+}
+
+/// Data decoded from the file
+enum Source {
+    Sensor(Box<RawImage>),
+    Preview(Image),
+}
+
+/// A camera RAW and DNG decoder
+///
+/// Sensor data is demosaiced, white balanced with the as shot coefficients and
+/// converted from the camera colors to sRGB primaries, the result is a 16-bit RGB image.
+pub struct RawDecoder<R: Read> {
+    source: Option<Source>,
+    options: RawDecoderOptions,
+    dimensions: (usize, usize),
+    phantom: PhantomData<R>,
+}
+
+impl<R: Read> RawDecoder<R> {
+    /// Create a new raw decoder that reads data from `source`
+    pub fn try_new(source: R) -> Result<RawDecoder<R>, ImageErrors> {
+        Self::try_new_with_options(source, RawDecoderOptions::default())
+    }
+
+    /// Create a new raw decoder with specified options that reads data from `source`
+    pub fn try_new_with_options(
+        mut source: R,
+        options: RawDecoderOptions,
+    ) -> Result<RawDecoder<R>, ImageErrors> {
+        let mut buf = Vec::new();
+        source.read_to_end(&mut buf)?;
+
+        let source = if options.preview {
+            let jpeg = preview::find_jpeg(&buf).ok_or_else(|| {
+                ImageErrors::ImageDecodeErrors("RAW file has no embedded JPEG preview".to_string())
+            })?;
+
+            Source::Preview(Image::read(
+                Cursor::new(jpeg.to_vec()),
+                DecoderOptions::default(),
+            )?)
+        } else {
+            let raw = rawloader::decode(&mut Cursor::new(&buf)).map_err(|e| {
+                ImageErrors::ImageDecodeErrors(format!("Unable to decode RAW file: {e}"))
+            })?;
+
+            Source::Sensor(Box::new(raw))
+        };
+
+        let dimensions = match &source {
+            Source::Sensor(raw) => develop::dimensions(raw),
+            Source::Preview(image) => image.dimensions(),
+        };
+
+        Ok(RawDecoder {
+            source: Some(source),
+            options,
+            dimensions,
+            phantom: PhantomData,
+        })
+    }
+}
+
+impl<R> DecoderTrait for RawDecoder<R>
+where
+    R: Read,
+{
+    fn decode(&mut self) -> Result<Image, ImageErrors> {
+        let raw = match self.source.take() {
+            Some(Source::Sensor(raw)) => raw,
+            Some(Source::Preview(image)) => return Ok(image),
+            None => {
+                return Err(ImageErrors::ImageDecodeErrors(
+                    "RAW file is already decoded".to_string(),
+                ))
+            }
+        };
+
+        let (width, height) = self.dimensions;
+        let data = develop::develop(&raw, self.options.transfer);
+
+        let frame = Frame::from_u16(&data, ColorSpace::RGB, 0, 0);
+        #[allow(unused_mut)]
+        let mut image = Image::new_frames(
+            vec![frame],
+            BitDepth::Sixteen,
+            width,
+            height,
+            ColorSpace::RGB,
+        );
+
+        #[cfg(feature = "icc")]
+        if self.options.transfer == RawTransfer::Linear {
+            image
+                .metadata_mut()
+                .set_icc_chunk(develop::linear_srgb_profile()?);
+        }
+
+        Ok(image)
+    }
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        Some(self.dimensions)
+    }
+
+    fn out_colorspace(&self) -> ColorSpace {
+        match &self.source {
+            Some(Source::Preview(image)) => image.colorspace(),
+            _ => ColorSpace::RGB,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "raw"
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,190 @@
+const COMPRESSION: u16 = 0x0103;
+const STRIP_OFFSETS: u16 = 0x0111;
+const STRIP_BYTE_COUNTS: u16 = 0x0117;
+const SUB_IFDS: u16 = 0x014A;
+const JPEG_OFFSET: u16 = 0x0201;
+const JPEG_LENGTH: u16 = 0x0202;
+const EXIF_IFD: u16 = 0x8769;
+
+/// Maximum number of IFDs visited, protects against loops in malformed files
+const MAX_IFDS: usize = 64;
+
+/// Find the largest lossy JPEG stored in the IFDs of TIFF based RAW `data`
+///
+/// Previews are referenced either with `JPEGInterchangeFormat` tags or as
+/// single strip JPEG compressed images, e.g. in sub-IFDs of DNG files.
+pub(super) fn find_jpeg(data: &[u8]) -> Option<&[u8]> {
+    let reader = Reader::new(data)?;
+
+    let mut pending = vec![reader.u32(4)? as usize];
+    let mut visited = vec![];
+    let mut largest: Option<&[u8]> = None;
+
+    while let Some(offset) = pending.pop() {
+        if offset == 0 || visited.contains(&offset) || visited.len() >= MAX_IFDS {
+            continue;
+        }
+        visited.push(offset);
+
+        let ifd = match reader.ifd(offset) {
+            Some(ifd) => ifd,
+            None => continue,
+        };
+
+        pending.extend(ifd.next);
+        pending.extend(ifd.children.iter().map(|&offset| offset as usize));
+
+        let candidates = [
+            ifd.jpeg,
+            // preview subfile stored as a single JPEG strip
+            ifd.strip
+                .filter(|_| ifd.compression == Some(6) || ifd.compression == Some(7)),
+        ];
+
+        for (offset, length) in candidates.into_iter().flatten() {
+            let (offset, length) = (offset as usize, length as usize);
+            let jpeg = data.get(offset..offset.saturating_add(length));
+
+            if let Some(jpeg) = jpeg.filter(|jpeg| is_lossy_jpeg(jpeg)) {
+                if largest.map_or(true, |largest| jpeg.len() > largest.len()) {
+                    largest = Some(jpeg);
+                }
+            }
+        }
+    }
+
+    largest
+}
+
+/// Check whether `data` is a baseline or progressive JPEG
+///
+/// DNG stores the sensor data as lossless JPEG, which can't be used as a preview.
+fn is_lossy_jpeg(data: &[u8]) -> bool {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return false;
+    }
+
+    let mut offset = 2;
+
+    while let Some(&[0xFF, marker, hi, lo]) = data.get(offset..offset + 4) {
+        match marker {
+            0xC0..=0xC2 => return true,
+            0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF | 0xDA => return false,
+            _ => offset += 2 + usize::from(u16::from_be_bytes([hi, lo])),
+        }
+    }
+
+    false
+}
+
+/// Tags of a single IFD needed to locate previews
+#[derive(Default)]
+struct Ifd {
+    compression: Option<u32>,
+    strip: Option<(u32, u32)>,
+    jpeg: Option<(u32, u32)>,
+    children: Vec<u32>,
+    next: Option<usize>,
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    big_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Option<Self> {
+        let big_endian = match data.get(..2)? {
+            b"II" => false,
+            b"MM" => true,
+            _ => return None,
+        };
+
+        Some(Self { data, big_endian })
+    }
+
+    fn u16(&self, offset: usize) -> Option<u16> {
+        let bytes = self.data.get(offset..offset + 2)?.try_into().ok()?;
+
+        Some(if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    }
+
+    fn u32(&self, offset: usize) -> Option<u32> {
+        let bytes = self.data.get(offset..offset + 4)?.try_into().ok()?;
+
+        Some(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    /// Values of the SHORT, LONG or IFD entry at `offset`
+    fn values(&self, offset: usize) -> Option<Vec<u32>> {
+        let kind = self.u16(offset + 2)?;
+        let count = self.u32(offset + 4)? as usize;
+
+        let size = match kind {
+            3 => 2,
+            4 | 13 => 4,
+            _ => return None,
+        };
+
+        // values that fit into 4 bytes are stored in place of the offset
+        let start = if count * size <= 4 {
+            offset + 8
+        } else {
+            self.u32(offset + 8)? as usize
+        };
+
+        (0..count.min(MAX_IFDS))
+            .map(|index| match size {
+                2 => self.u16(start + index * 2).map(u32::from),
+                _ => self.u32(start + index * 4),
+            })
+            .collect()
+    }
+
+    fn ifd(&self, offset: usize) -> Option<Ifd> {
+        let count = usize::from(self.u16(offset)?);
+        let mut ifd = Ifd::default();
+
+        let mut strip_offset = None;
+        let mut strip_length = None;
+        let mut jpeg_offset = None;
+        let mut jpeg_length = None;
+
+        for index in 0..count {
+            let entry = offset + 2 + index * 12;
+            let tag = self.u16(entry)?;
+
+            // only single value tags are used, except of sub-IFDs
+            let first = || {
+                self.values(entry)
+                    .and_then(|values| values.first().copied())
+            };
+
+            match tag {
+                COMPRESSION => ifd.compression = first(),
+                STRIP_OFFSETS => strip_offset = first(),
+                STRIP_BYTE_COUNTS => strip_length = first(),
+                JPEG_OFFSET => jpeg_offset = first(),
+                JPEG_LENGTH => jpeg_length = first(),
+                SUB_IFDS | EXIF_IFD => ifd.children.extend(self.values(entry).unwrap_or_default()),
+                _ => {}
+            }
+        }
+
+        ifd.strip = strip_offset.zip(strip_length);
+        ifd.jpeg = jpeg_offset.zip(jpeg_length);
+        ifd.next = self
+            .u32(offset + 2 + count * 12)
+            .map(|offset| offset as usize);
+
+        Some(ifd)
+    }
+}
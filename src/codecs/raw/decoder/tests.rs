@@ -0,0 +1,116 @@
+use std::io::Cursor;
+
+use rawloader::Orientation;
+
+use super::*;
+
+/// Little endian TIFF with the `preview` referenced from IFD0 and a lossless JPEG
+/// strip in a sub-IFD, like DNG stores the sensor data
+fn tiff_with_preview(preview: &[u8]) -> Vec<u8> {
+    let entry = |tag: u16, kind: u16, value: u32| {
+        let mut entry = vec![];
+        entry.extend_from_slice(&tag.to_le_bytes());
+        entry.extend_from_slice(&kind.to_le_bytes());
+        entry.extend_from_slice(&1u32.to_le_bytes());
+        entry.extend_from_slice(&value.to_le_bytes());
+        entry
+    };
+
+    // lossless JPEG is larger than the preview
+    let mut lossless = vec![0xFF, 0xD8, 0xFF, 0xC3, 0x00, 0x02];
+    lossless.resize(preview.len() * 2, 0);
+
+    // header, IFD0 with 3 entries and sub-IFD with 3 entries
+    let sub_ifd = 8 + 2 + 3 * 12 + 4;
+    let lossless_offset = sub_ifd + 2 + 3 * 12 + 4;
+    let preview_offset = lossless_offset + lossless.len();
+
+    let mut data = b"II\x2a\0\x08\0\0\0".to_vec();
+
+    data.extend_from_slice(&3u16.to_le_bytes());
+    data.extend(entry(0x014A, 4, sub_ifd as u32));
+    data.extend(entry(0x0201, 4, preview_offset as u32));
+    data.extend(entry(0x0202, 4, preview.len() as u32));
+    data.extend_from_slice(&0u32.to_le_bytes());
+
+    data.extend_from_slice(&3u16.to_le_bytes());
+    data.extend(entry(0x0103, 3, 7));
+    data.extend(entry(0x0111, 4, lossless_offset as u32));
+    data.extend(entry(0x0117, 4, lossless.len() as u32));
+    data.extend_from_slice(&0u32.to_le_bytes());
+
+    data.extend(lossless);
+    data.extend_from_slice(preview);
+
+    data
+}
+
+#[test]
+fn find_preview() {
+    let jpeg = std::fs::read("tests/files/jpg/f1t.jpg").unwrap();
+    let data = tiff_with_preview(&jpeg);
+
+    assert_eq!(preview::find_jpeg(&data), Some(jpeg.as_slice()));
+    assert_eq!(preview::find_jpeg(&jpeg), None);
+}
+
+#[test]
+fn decode_preview() {
+    let jpeg = std::fs::read("tests/files/jpg/f1t.jpg").unwrap();
+
+    let decoder = RawDecoder::try_new_with_options(
+        Cursor::new(tiff_with_preview(&jpeg)),
+        RawDecoderOptions {
+            preview: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(decoder.dimensions(), Some((48, 80)));
+
+    let img = Image::from_decoder(decoder).unwrap();
+
+    assert_eq!(img.dimensions(), (48, 80));
+}
+
+#[test]
+fn decode_invalid() {
+    for preview in [false, true] {
+        let result = RawDecoder::try_new_with_options(
+            Cursor::new(b"not a raw file".to_vec()),
+            RawDecoderOptions {
+                preview,
+                ..Default::default()
+            },
+        );
+
+        assert!(result.is_err());
+    }
+}
+
+#[test]
+fn orient_pixels() {
+    // 3x2 image
+    let pixels = [1, 2, 3, 4, 5, 6];
+
+    assert_eq!(
+        develop::orient(&pixels, 3, 2, Orientation::Rotate90),
+        [4, 1, 5, 2, 6, 3]
+    );
+    assert_eq!(
+        develop::orient(&pixels, 3, 2, Orientation::Rotate270),
+        [3, 6, 2, 5, 1, 4]
+    );
+    assert_eq!(
+        develop::orient(&pixels, 3, 2, Orientation::HorizontalFlip),
+        [3, 2, 1, 6, 5, 4]
+    );
+}
+
+#[test]
+fn srgb_transfer() {
+    assert_eq!(develop::srgb_transfer(0.), 0.);
+    assert!((develop::srgb_transfer(1.) - 1.).abs() < 1e-6);
+    assert!((develop::srgb_transfer(0.18) - 0.4614).abs() < 1e-3);
+}
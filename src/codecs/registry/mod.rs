@@ -0,0 +1,540 @@
+use std::{
+    fs::File,
+    io::{Cursor, Read},
+    path::Path,
+};
+
+use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace, options::DecoderOptions};
+use zune_image::{codecs::ImageFormat, errors::ImageErrors, image::Image};
+
+use super::dynamic::{encoder, DynEncoder, ENCODER_NAMES};
+
+/// Image formats known to the registry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// JPEG image
+    Jpeg,
+    /// PNG or APNG image
+    Png,
+    /// AVIF image or image sequence
+    Avif,
+    /// WebP image, still or animated
+    WebP,
+    /// TIFF image, possibly with multiple pages
+    Tiff,
+    /// GIF image, still or animated
+    Gif,
+    /// JPEG XL image, bare codestream or container
+    JpegXl,
+    /// Camera RAW or DNG image
+    Raw,
+    /// QOI image
+    Qoi,
+    /// Binary PPM, PGM, PAM or PFM image
+    Ppm,
+    /// Farbfeld image
+    FarbFeld,
+    /// BMP image
+    Bmp,
+    /// Radiance HDR image
+    Hdr,
+    /// Photoshop document
+    Psd,
+    /// Windows icon
+    Ico,
+    /// DirectDraw Surface texture
+    Dds,
+    /// KTX2 texture
+    Ktx2,
+}
+
+impl Format {
+    /// Detect the format from the first bytes of the file
+    ///
+    /// DNG and most camera RAW formats are TIFF containers, they are told apart
+    /// only by [`detect`](crate::codecs::registry::detect) which looks into the first IFD.
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0xFF, 0xD8, 0xFF, ..] => Some(Self::Jpeg),
+            [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n', ..] => Some(Self::Png),
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some(Self::WebP),
+            // Canon CR2, Olympus ORF, Panasonic RW2, Fujifilm RAF, Canon CRW and Minolta MRW
+            [b'I', b'I', b'*', 0, _, _, _, _, b'C', b'R', ..]
+            | [b'I', b'I', b'R', b'O' | b'S', ..]
+            | [b'M', b'M', b'O', b'R', ..]
+            | [b'I', b'I', b'U', 0, ..]
+            | [b'F', b'U', b'J', b'I', b'F', b'I', b'L', b'M', ..]
+            | [b'I', b'I', 0x1A, 0, 0, 0, b'H', b'E', b'A', b'P', ..]
+            | [0, b'M', b'R', b'M', ..] => Some(Self::Raw),
+            [b'I', b'I', b'*' | b'+', 0, ..] | [b'M', b'M', 0, b'*' | b'+', ..] => Some(Self::Tiff),
+            [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => Some(Self::Gif),
+            [_, _, _, _, b'f', b't', b'y', b'p', ..] if is_avif_ftyp(bytes) => Some(Self::Avif),
+            [0xFF, 0x0A, ..] | [0, 0, 0, 0x0C, b'J', b'X', b'L', b' ', ..] => Some(Self::JpegXl),
+            [b'q', b'o', b'i', b'f', ..] => Some(Self::Qoi),
+            [b'P', b'5' | b'6' | b'7' | b'F' | b'f', c, ..] if c.is_ascii_whitespace() => {
+                Some(Self::Ppm)
+            }
+            [b'f', b'a', b'r', b'b', b'f', b'e', b'l', b'd', ..] => Some(Self::FarbFeld),
+            [b'B', b'M', ..] => Some(Self::Bmp),
+            [b'#', b'?', b'R', b'A', b'D', b'I', b'A', b'N', b'C', b'E', ..]
+            | [b'#', b'?', b'R', b'G', b'B', b'E', ..] => Some(Self::Hdr),
+            [b'8', b'B', b'P', b'S', ..] => Some(Self::Psd),
+            [b'D', b'D', b'S', b' ', ..] => Some(Self::Dds),
+            [0xAB, b'K', b'T', b'X', b' ', b'2', b'0', 0xBB, ..] => Some(Self::Ktx2),
+            // reserved field followed by the icon type and a non-zero image count
+            [0, 0, 1, 0, n, _, ..] if *n != 0 => Some(Self::Ico),
+            _ => None,
+        }
+    }
+
+    /// Detect the format from the file extension, case insensitive
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        ALL.iter().copied().find(|format| {
+            format
+                .extensions()
+                .iter()
+                .any(|ext| extension.eq_ignore_ascii_case(ext))
+        })
+    }
+
+    /// File extensions of the format, the first one is preferred
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Self::Jpeg => &["jpg", "jpeg", "jfif"],
+            Self::Png => &["png", "apng"],
+            Self::Avif => &["avif"],
+            Self::WebP => &["webp"],
+            Self::Tiff => &["tiff", "tif"],
+            Self::Gif => &["gif"],
+            Self::JpegXl => &["jxl"],
+            Self::Raw => &[
+                "dng", "cr2", "crw", "nef", "nrw", "arw", "srf", "sr2", "orf", "rw2", "raf", "pef",
+                "srw", "mrw", "3fr", "erf", "kdc", "dcr", "mos", "iiq",
+            ],
+            Self::Qoi => &["qoi"],
+            Self::Ppm => &["ppm", "pgm", "pam", "pfm"],
+            Self::FarbFeld => &["ff"],
+            Self::Bmp => &["bmp"],
+            Self::Hdr => &["hdr"],
+            Self::Psd => &["psd"],
+            Self::Ico => &["ico"],
+            Self::Dds => &["dds"],
+            Self::Ktx2 => &["ktx2"],
+        }
+    }
+
+    /// MIME type of the format
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            Self::Jpeg => "image/jpeg",
+            Self::Png => "image/png",
+            Self::Avif => "image/avif",
+            Self::WebP => "image/webp",
+            Self::Tiff => "image/tiff",
+            Self::Gif => "image/gif",
+            Self::JpegXl => "image/jxl",
+            Self::Raw => "image/x-dcraw",
+            Self::Qoi => "image/qoi",
+            Self::Ppm => "image/x-portable-anymap",
+            Self::FarbFeld => "image/x-farbfeld",
+            Self::Bmp => "image/bmp",
+            Self::Hdr => "image/vnd.radiance",
+            Self::Psd => "image/vnd.adobe.photoshop",
+            Self::Ico => "image/vnd.microsoft.icon",
+            Self::Dds => "image/vnd-ms.dds",
+            Self::Ktx2 => "image/ktx2",
+        }
+    }
+
+    /// Matching format of zune-image, if it has one
+    fn zune_format(self) -> Option<ImageFormat> {
+        match self {
+            Self::Jpeg => Some(ImageFormat::JPEG),
+            Self::Png => Some(ImageFormat::PNG),
+            Self::JpegXl => Some(ImageFormat::JPEG_XL),
+            Self::Qoi => Some(ImageFormat::QOI),
+            Self::Ppm => Some(ImageFormat::PPM),
+            Self::FarbFeld => Some(ImageFormat::Farbfeld),
+            Self::Bmp => Some(ImageFormat::BMP),
+            Self::Hdr => Some(ImageFormat::HDR),
+            Self::Psd => Some(ImageFormat::PSD),
+            _ => None,
+        }
+    }
+}
+
+/// Every known format, in order of [`Format::from_extension`] lookup
+const ALL: &[Format] = &[
+    Format::Jpeg,
+    Format::Png,
+    Format::Avif,
+    Format::WebP,
+    Format::Tiff,
+    Format::Gif,
+    Format::JpegXl,
+    Format::Raw,
+    Format::Qoi,
+    Format::Ppm,
+    Format::FarbFeld,
+    Format::Bmp,
+    Format::Hdr,
+    Format::Psd,
+    Format::Ico,
+    Format::Dds,
+    Format::Ktx2,
+];
+
+fn is_avif_ftyp(bytes: &[u8]) -> bool {
+    let size = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+    let ftyp = &bytes[..size.min(bytes.len())];
+
+    // major brand followed by the minor version and the compatible brands
+    ftyp.get(8..12)
+        .into_iter()
+        .chain(ftyp.get(16..).into_iter().flat_map(|b| b.chunks_exact(4)))
+        .any(|brand| brand == b"avif" || brand == b"avis")
+}
+
+/// Detect the format of the whole file `data`
+///
+/// Unlike [`Format::detect`] recognizes DNG files, which otherwise look like plain TIFF.
+pub fn detect(data: &[u8]) -> Option<Format> {
+    match Format::detect(data)? {
+        Format::Tiff if has_dng_version(data) => Some(Format::Raw),
+        format => Some(format),
+    }
+}
+
+/// Check whether the first IFD of TIFF `data` has the DNGVersion tag
+fn has_dng_version(data: &[u8]) -> bool {
+    const DNG_VERSION: u16 = 0xC612;
+
+    let big_endian = data.starts_with(b"MM");
+    let read = |offset: usize, len: usize| -> Option<u32> {
+        let bytes = data.get(offset..offset + len)?;
+
+        Some(bytes.iter().enumerate().fold(0, |acc, (i, &b)| {
+            let shift = if big_endian { len - 1 - i } else { i } * 8;
+            acc | u32::from(b) << shift
+        }))
+    };
+
+    let check = || -> Option<bool> {
+        let ifd = read(4, 4)? as usize;
+        let count = read(ifd, 2)? as usize;
+
+        Some((0..count).any(|i| read(ifd + 2 + i * 12, 2) == Some(u32::from(DNG_VERSION))))
+    };
+
+    check().unwrap_or(false)
+}
+
+/// Options of the decoders picked by the registry
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DecodeOptions {
+    /// Options passed to zune-image decoders
+    pub zune: DecoderOptions,
+    /// Options of the camera RAW decoder
+    #[cfg(feature = "raw")]
+    pub raw: crate::codecs::raw::RawDecoderOptions,
+}
+
+/// Properties of the source image that can't be stored in [`Image`]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SourceInfo {
+    /// Number of times animation is played, `0` means infinite
+    pub loop_count: u32,
+    /// Background color of the animation canvas in RGBA
+    pub background_color: [u8; 4],
+    /// Raw XMP packet
+    pub xmp: Option<Vec<u8>>,
+}
+
+/// Decoded image along with properties of its source
+pub struct Decoded {
+    /// Image with all frames of the source
+    pub image: Image,
+    /// Properties kept by the decoder of the source format
+    pub source: SourceInfo,
+}
+
+impl From<Image> for Decoded {
+    fn from(image: Image) -> Self {
+        Self {
+            image,
+            source: SourceInfo::default(),
+        }
+    }
+}
+
+/// Compiled-in decoder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecoderInfo {
+    /// Name of the decoder
+    pub name: &'static str,
+    /// Format read by the decoder
+    pub format: Format,
+}
+
+/// Compiled-in encoder with its capabilities
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncoderInfo {
+    /// Name of the encoder
+    pub name: &'static str,
+    /// Format written by the encoder
+    pub format: Format,
+    /// Colorspaces accepted without conversion
+    pub colorspaces: &'static [ColorSpace],
+    /// Bit depths accepted without conversion
+    pub depths: &'static [BitDepth],
+    /// Whether all frames of animated images are written
+    pub animated: bool,
+}
+
+impl EncoderInfo {
+    fn new(encoder: &dyn DynEncoder) -> Self {
+        Self {
+            name: encoder.name(),
+            format: encoder.format(),
+            colorspaces: encoder.supported_colorspaces(),
+            depths: encoder.supported_bit_depth(),
+            animated: encoder.supports_animated_images(),
+        }
+    }
+}
+
+/// Decoders available with enabled features
+///
+/// Formats read by zune-image come first, they are listed only when
+/// zune-image is built with them.
+#[allow(unused_mut)]
+pub fn decoders() -> Vec<DecoderInfo> {
+    let mut decoders: Vec<DecoderInfo> = ALL
+        .iter()
+        .filter(|format| format.zune_format().is_some_and(|f| f.has_decoder()))
+        .map(|&format| DecoderInfo {
+            name: "zune-image",
+            format,
+        })
+        .collect();
+
+    #[cfg(feature = "mozjpeg")]
+    if !ImageFormat::JPEG.has_decoder() {
+        decoders.push(DecoderInfo {
+            name: "mozjpeg-decoder",
+            format: Format::Jpeg,
+        });
+    }
+
+    #[cfg(feature = "avif")]
+    decoders.push(DecoderInfo {
+        name: "avif-decoder (aom)",
+        format: Format::Avif,
+    });
+
+    #[cfg(feature = "webp")]
+    decoders.push(DecoderInfo {
+        name: "webp",
+        format: Format::WebP,
+    });
+
+    #[cfg(feature = "tiff")]
+    decoders.push(DecoderInfo {
+        name: "tiff-decoder",
+        format: Format::Tiff,
+    });
+
+    #[cfg(feature = "gif")]
+    decoders.push(DecoderInfo {
+        name: "gif",
+        format: Format::Gif,
+    });
+
+    #[cfg(feature = "raw")]
+    decoders.push(DecoderInfo {
+        name: "raw",
+        format: Format::Raw,
+    });
+
+    decoders
+}
+
+/// Encoders of this crate available with enabled features
+///
+/// Encoders of zune-image itself are not listed.
+pub fn encoders() -> Vec<EncoderInfo> {
+    ENCODER_NAMES
+        .iter()
+        .filter_map(|name| encoder(name))
+        .map(|encoder| EncoderInfo::new(encoder.as_ref()))
+        .collect()
+}
+
+/// Decode image of any supported format from `source`
+///
+/// # Example
+///
+/// ```
+/// use std::fs::File;
+///
+/// let file = File::open("tests/files/png/f1t.png").unwrap();
+///
+/// let image = rimage::decode(file).unwrap();
+///
+/// assert_eq!(image.dimensions(), (48, 80));
+/// ```
+pub fn decode<R: Read>(source: R) -> Result<Image, ImageErrors> {
+    decode_with_options(source, &DecodeOptions::default())
+}
+
+/// Decode image of any supported format from `source` with specified options
+pub fn decode_with_options<R: Read>(
+    mut source: R,
+    options: &DecodeOptions,
+) -> Result<Image, ImageErrors> {
+    let mut data = Vec::new();
+    source.read_to_end(&mut data)?;
+
+    Ok(decode_data(data, None, options)?.image)
+}
+
+/// Open and decode image of any supported format at `path`
+///
+/// TIFF based camera RAW files without a distinct signature are
+/// recognized by their extension.
+pub fn open<P: AsRef<Path>>(path: P) -> Result<Image, ImageErrors> {
+    open_with_options(path, &DecodeOptions::default())
+}
+
+/// Open and decode image of any supported format at `path` with specified options
+pub fn open_with_options<P: AsRef<Path>>(
+    path: P,
+    options: &DecodeOptions,
+) -> Result<Image, ImageErrors> {
+    let path = path.as_ref();
+
+    let mut data = Vec::new();
+    File::open(path)?.read_to_end(&mut data)?;
+
+    let extension = path.extension().and_then(|ext| ext.to_str());
+
+    Ok(decode_data(data, extension, options)?.image)
+}
+
+/// Decode file `data` of any supported format along with properties of the source,
+/// like loop count of animations and XMP
+///
+/// `extension` of the file recognizes TIFF based camera RAW files without a distinct signature.
+pub fn decode_data(
+    data: Vec<u8>,
+    extension: Option<&str>,
+    options: &DecodeOptions,
+) -> Result<Decoded, ImageErrors> {
+    let extension = extension.and_then(Format::from_extension);
+
+    let format = match detect(&data) {
+        // NEF, ARW, PEF and others are indistinguishable from TIFF by content
+        Some(Format::Tiff) if extension == Some(Format::Raw) => Some(Format::Raw),
+        format => format,
+    };
+
+    decode_format(data, format, options)
+}
+
+#[allow(unused_variables)]
+fn decode_format(
+    data: Vec<u8>,
+    format: Option<Format>,
+    options: &DecodeOptions,
+) -> Result<Decoded, ImageErrors> {
+    let unsupported = || {
+        ImageErrors::ImageDecoderNotImplemented(
+            format
+                .and_then(Format::zune_format)
+                .unwrap_or(ImageFormat::Unknown),
+        )
+    };
+
+    let format = format.ok_or_else(unsupported)?;
+
+    if format.zune_format().is_some_and(|f| f.has_decoder()) {
+        return Ok(Image::read(Cursor::new(data), options.zune)?.into());
+    }
+
+    let source = Cursor::new(data);
+
+    match format {
+        #[cfg(feature = "mozjpeg")]
+        Format::Jpeg => Ok(
+            Image::from_decoder(crate::codecs::mozjpeg::MozJpegDecoder::try_new(source)?)?.into(),
+        ),
+        #[cfg(feature = "avif")]
+        Format::Avif => {
+            let decoder = crate::codecs::avif::AvifDecoder::try_new(source)?;
+
+            let source = SourceInfo {
+                loop_count: decoder.loop_count(),
+                xmp: decoder.xmp().map(<[u8]>::to_vec),
+                ..Default::default()
+            };
+
+            Ok(Decoded {
+                image: Image::from_decoder(decoder)?,
+                source,
+            })
+        }
+        #[cfg(feature = "webp")]
+        Format::WebP => {
+            let decoder = crate::codecs::webp::WebPDecoder::try_new(source)?;
+
+            let source = SourceInfo {
+                loop_count: decoder.loop_count(),
+                background_color: decoder.background_color(),
+                xmp: decoder.xmp().map(<[u8]>::to_vec),
+            };
+
+            Ok(Decoded {
+                image: Image::from_decoder(decoder)?,
+                source,
+            })
+        }
+        #[cfg(feature = "tiff")]
+        Format::Tiff => {
+            let decoder = crate::codecs::tiff::TiffDecoder::try_new(source)?;
+
+            let source = SourceInfo {
+                xmp: decoder.xmp().map(<[u8]>::to_vec),
+                ..Default::default()
+            };
+
+            Ok(Decoded {
+                image: Image::from_decoder(decoder)?,
+                source,
+            })
+        }
+        #[cfg(feature = "gif")]
+        Format::Gif => {
+            let decoder = crate::codecs::gif::GifDecoder::try_new(source)?;
+
+            let source = SourceInfo {
+                loop_count: decoder.loop_count(),
+                ..Default::default()
+            };
+
+            Ok(Decoded {
+                image: Image::from_decoder(decoder)?,
+                source,
+            })
+        }
+        #[cfg(feature = "raw")]
+        Format::Raw => Ok(Image::from_decoder(
+            crate::codecs::raw::RawDecoder::try_new_with_options(source, options.raw)?,
+        )?
+        .into()),
+        #[allow(unreachable_patterns)]
+        _ => Err(unsupported()),
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,128 @@
+use std::fs::File;
+
+use super::*;
+
+#[test]
+fn detect_files() {
+    let files = [
+        ("tests/files/png/f1t.png", Format::Png),
+        ("tests/files/jpg/f1t.jpg", Format::Jpeg),
+        ("tests/files/avif/f1t.avif", Format::Avif),
+        ("tests/files/webp/f1t.webp", Format::WebP),
+        ("tests/files/tiff/f1t.tif", Format::Tiff),
+        ("tests/files/gif/f1t.gif", Format::Gif),
+        ("tests/files/jxl/f1t.jxl", Format::JpegXl),
+    ];
+
+    for (path, format) in files {
+        let data = std::fs::read(path).unwrap();
+
+        assert_eq!(detect(&data), Some(format), "{path}");
+    }
+}
+
+#[test]
+fn detect_magic() {
+    let magic: [(&[u8], Option<Format>); 8] = [
+        (b"qoif\0\0\0\x01", Some(Format::Qoi)),
+        (b"P6\n48 80\n255\n", Some(Format::Ppm)),
+        (b"farbfeld\0\0\0\x01", Some(Format::FarbFeld)),
+        (b"DDS \x7c\0\0\0", Some(Format::Dds)),
+        (b"\xABKTX 20\xBB\r\n\x1A\n", Some(Format::Ktx2)),
+        (b"II*\0\x10\0\0\0CR\x02\0", Some(Format::Raw)),
+        (b"FUJIFILMCCD-RAW ", Some(Format::Raw)),
+        (b"not an image", None),
+    ];
+
+    for (bytes, format) in magic {
+        assert_eq!(Format::detect(bytes), format, "{bytes:?}");
+    }
+}
+
+#[test]
+fn detect_dng() {
+    // little endian TIFF with a single DNGVersion entry in the first IFD
+    let mut dng = b"II*\0\x08\0\0\0\x01\0".to_vec();
+    dng.extend_from_slice(&[0x12, 0xC6, 1, 0, 4, 0, 0, 0, 1, 4, 0, 0]);
+    dng.extend_from_slice(&[0, 0, 0, 0]);
+
+    assert_eq!(Format::detect(&dng), Some(Format::Tiff));
+    assert_eq!(detect(&dng), Some(Format::Raw));
+}
+
+#[test]
+fn from_extension() {
+    assert_eq!(Format::from_extension("JPEG"), Some(Format::Jpeg));
+    assert_eq!(Format::from_extension("tif"), Some(Format::Tiff));
+    assert_eq!(Format::from_extension("nef"), Some(Format::Raw));
+    assert_eq!(Format::from_extension("txt"), None);
+}
+
+#[test]
+fn decode_files() {
+    let files = [
+        "tests/files/png/f1t.png",
+        "tests/files/jpg/f1t.jpg",
+        #[cfg(feature = "avif")]
+        "tests/files/avif/f1t.avif",
+        #[cfg(feature = "webp")]
+        "tests/files/webp/f1t.webp",
+        #[cfg(feature = "tiff")]
+        "tests/files/tiff/f1t.tif",
+        #[cfg(feature = "gif")]
+        "tests/files/gif/f1t.gif",
+    ];
+
+    for path in files {
+        let image = decode(File::open(path).unwrap()).unwrap();
+
+        assert_eq!(image.dimensions(), (48, 80), "{path}");
+        assert_eq!(open(path).unwrap().dimensions(), (48, 80), "{path}");
+    }
+}
+
+#[test]
+#[cfg(feature = "webp")]
+fn decode_source_info() {
+    use crate::codecs::webp::WebPDecoder;
+
+    let data = std::fs::read("tests/files/webp/f1t.webp").unwrap();
+
+    let decoded = decode_data(data.clone(), None, &DecodeOptions::default()).unwrap();
+    let decoder = WebPDecoder::try_new(Cursor::new(data)).unwrap();
+
+    assert_eq!(decoded.image.dimensions(), (48, 80));
+    assert_eq!(decoded.source.loop_count, decoder.loop_count());
+    assert_eq!(decoded.source.background_color, decoder.background_color());
+    assert_eq!(decoded.source.xmp.as_deref(), decoder.xmp());
+}
+
+#[test]
+fn decode_unknown() {
+    let result = decode(b"not an image".as_slice());
+
+    assert!(matches!(
+        result,
+        Err(ImageErrors::ImageDecoderNotImplemented(_))
+    ));
+}
+
+#[test]
+fn list_codecs() {
+    let decoders = decoders();
+
+    assert!(decoders.iter().any(|d| d.format == Format::Png));
+    #[cfg(feature = "webp")]
+    assert!(decoders.iter().any(|d| d.format == Format::WebP));
+
+    #[cfg(feature = "gif")]
+    {
+        let gif = encoders()
+            .into_iter()
+            .find(|e| e.format == Format::Gif)
+            .unwrap();
+
+        assert!(gif.animated);
+        assert_eq!(gif.depths, &[BitDepth::Eight]);
+    }
+}
@@ -0,0 +1,120 @@
+use ddsfile::{AlphaMode, D3D10ResourceDimension, Dds, DxgiFormat, NewDxgiParams};
+use zune_core::{
+    bit_depth::BitDepth,
+    bytestream::{ZByteWriterTrait, ZWriter},
+    colorspace::ColorSpace,
+};
+use zune_image::{
+    codecs::ImageFormat,
+    errors::{ImageErrors, ImgEncodeErrors},
+    image::Image,
+    traits::EncoderTrait,
+};
+
+use super::{compress_levels, TextureCompression, TextureOptions};
+
+/// A DDS encoder
+///
+/// Writes the first frame of the image as a block compressed 2D texture with
+/// the DX10 header.
+#[derive(Default)]
+pub struct DdsEncoder {
+    options: TextureOptions,
+}
+
+impl DdsEncoder {
+    /// Create a new encoder
+    pub fn new() -> DdsEncoder {
+        DdsEncoder::default()
+    }
+
+    /// Create a new encoder with specified options
+    pub fn new_with_options(options: TextureOptions) -> DdsEncoder {
+        DdsEncoder { options }
+    }
+
+    fn dxgi_format(&self) -> DxgiFormat {
+        let srgb = self.options.is_srgb();
+
+        match self.options.compression {
+            TextureCompression::Bc1 if srgb => DxgiFormat::BC1_UNorm_sRGB,
+            TextureCompression::Bc1 => DxgiFormat::BC1_UNorm,
+            TextureCompression::Bc3 if srgb => DxgiFormat::BC3_UNorm_sRGB,
+            TextureCompression::Bc3 => DxgiFormat::BC3_UNorm,
+            TextureCompression::Bc4 => DxgiFormat::BC4_UNorm,
+            TextureCompression::Bc5 => DxgiFormat::BC5_UNorm,
+            TextureCompression::Bc7 if srgb => DxgiFormat::BC7_UNorm_sRGB,
+            TextureCompression::Bc7 => DxgiFormat::BC7_UNorm,
+        }
+    }
+}
+
+impl EncoderTrait for DdsEncoder {
+    fn name(&self) -> &'static str {
+        "dds"
+    }
+
+    fn encode_inner<T: ZByteWriterTrait>(
+        &mut self,
+        image: &Image,
+        sink: T,
+    ) -> Result<usize, ImageErrors> {
+        let (width, height) = image.dimensions();
+        let levels = compress_levels(image, &self.options)?;
+
+        let mut dds = Dds::new_dxgi(NewDxgiParams {
+            height: height as u32,
+            width: width as u32,
+            depth: None,
+            format: self.dxgi_format(),
+            mipmap_levels: Some(levels.len() as u32),
+            array_layers: None,
+            caps2: None,
+            is_cubemap: false,
+            resource_dimension: D3D10ResourceDimension::Texture2D,
+            alpha_mode: match self.options.compression {
+                TextureCompression::Bc1 => AlphaMode::Opaque,
+                TextureCompression::Bc4 | TextureCompression::Bc5 => AlphaMode::Unknown,
+                _ => AlphaMode::Straight,
+            },
+        })
+        .map_err(dds_error)?;
+
+        // levels are stored one after another starting from the largest
+        dds.data = levels.concat();
+
+        let mut buf = vec![];
+        dds.write(&mut buf).map_err(dds_error)?;
+
+        let mut writer = ZWriter::new(sink);
+
+        writer.write(&buf).map_err(|e| {
+            ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
+        })?;
+
+        Ok(writer.bytes_written())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[ColorSpace::RGBA]
+    }
+
+    fn format(&self) -> ImageFormat {
+        ImageFormat::Unknown
+    }
+
+    fn supported_bit_depth(&self) -> &'static [BitDepth] {
+        &[BitDepth::Eight]
+    }
+
+    fn default_depth(&self, _depth: BitDepth) -> BitDepth {
+        BitDepth::Eight
+    }
+}
+
+fn dds_error(e: ddsfile::Error) -> ImageErrors {
+    ImageErrors::EncodeErrors(ImgEncodeErrors::Generic(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,57 @@
+use std::io::Cursor;
+
+use crate::test_utils::*;
+
+use super::*;
+
+fn encode_with(image: &Image, options: TextureOptions) -> Dds {
+    let mut buf = Cursor::new(vec![]);
+    DdsEncoder::new_with_options(options)
+        .encode(image, &mut buf)
+        .unwrap();
+    buf.set_position(0);
+
+    Dds::read(buf).unwrap()
+}
+
+#[test]
+fn encode_mipmaps() {
+    let image = create_test_image_u8(64, 48, ColorSpace::RGB);
+
+    let dds = encode_with(&image, TextureOptions::default());
+
+    assert_eq!((dds.get_width(), dds.get_height()), (64, 48));
+    assert_eq!(dds.get_dxgi_format(), Some(DxgiFormat::BC7_UNorm_sRGB));
+    assert_eq!(dds.get_num_mipmap_levels(), 7);
+
+    // 64x48, 32x24, 16x12, 8x6, 4x3, 2x1 and 1x1 in 16 byte blocks
+    let blocks = 16 * 12 + 8 * 6 + 4 * 3 + 2 * 2 + 1 + 1 + 1;
+    assert_eq!(dds.data.len(), blocks * 16);
+}
+
+#[test]
+fn encode_formats() {
+    let image = create_test_image_u8(32, 32, ColorSpace::RGBA);
+
+    for (compression, srgb, format) in [
+        (TextureCompression::Bc1, true, DxgiFormat::BC1_UNorm_sRGB),
+        (TextureCompression::Bc3, false, DxgiFormat::BC3_UNorm),
+        (TextureCompression::Bc4, true, DxgiFormat::BC4_UNorm),
+        (TextureCompression::Bc5, true, DxgiFormat::BC5_UNorm),
+        (TextureCompression::Bc7, false, DxgiFormat::BC7_UNorm),
+    ] {
+        let dds = encode_with(
+            &image,
+            TextureOptions {
+                compression,
+                srgb,
+                mipmaps: false,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(dds.get_dxgi_format(), Some(format));
+        assert_eq!(dds.get_num_mipmap_levels(), 1);
+        assert_eq!(dds.data.len(), 64 * compression.block_size());
+    }
+}
@@ -0,0 +1,204 @@
+use zune_core::{
+    bit_depth::BitDepth,
+    bytestream::{ZByteWriterTrait, ZWriter},
+    colorspace::ColorSpace,
+};
+use zune_image::{
+    codecs::ImageFormat,
+    errors::{ImageErrors, ImgEncodeErrors},
+    image::Image,
+    traits::EncoderTrait,
+};
+
+use super::{compress_levels, TextureCompression, TextureOptions};
+
+const IDENTIFIER: [u8; 12] = [
+    0xAB, b'K', b'T', b'X', b' ', b'2', b'0', 0xBB, b'\r', b'\n', 0x1A, b'\n',
+];
+
+/// Length of the header with the index
+const HEADER_LENGTH: usize = 80;
+/// Length of a single entry of the level index
+const LEVEL_INDEX_LENGTH: usize = 24;
+
+/// A KTX2 encoder
+///
+/// Writes the first frame of the image as a block compressed 2D texture without
+/// supercompression.
+#[derive(Default)]
+pub struct Ktx2Encoder {
+    options: TextureOptions,
+}
+
+impl Ktx2Encoder {
+    /// Create a new encoder
+    pub fn new() -> Ktx2Encoder {
+        Ktx2Encoder::default()
+    }
+
+    /// Create a new encoder with specified options
+    pub fn new_with_options(options: TextureOptions) -> Ktx2Encoder {
+        Ktx2Encoder { options }
+    }
+
+    /// Vulkan format of the texture
+    fn vk_format(&self) -> u32 {
+        let srgb = self.options.is_srgb() as u32;
+
+        match self.options.compression {
+            // BC1_RGB_UNORM_BLOCK, BC1_RGB_SRGB_BLOCK
+            TextureCompression::Bc1 => 131 + srgb,
+            // BC3_UNORM_BLOCK, BC3_SRGB_BLOCK
+            TextureCompression::Bc3 => 137 + srgb,
+            // BC4_UNORM_BLOCK
+            TextureCompression::Bc4 => 139,
+            // BC5_UNORM_BLOCK
+            TextureCompression::Bc5 => 141,
+            // BC7_UNORM_BLOCK, BC7_SRGB_BLOCK
+            TextureCompression::Bc7 => 145 + srgb,
+        }
+    }
+
+    /// Basic data format descriptor of the texture, prefixed with its total size
+    fn data_format_descriptor(&self) -> Vec<u8> {
+        let compression = self.options.compression;
+
+        // color model and samples as (channel, bit offset) of 64-bit halves of the block
+        let (color_model, samples): (u32, &[(u32, u32)]) = match compression {
+            TextureCompression::Bc1 => (128, &[(0, 0)]),
+            // alpha block comes first
+            TextureCompression::Bc3 => (130, &[(15, 0), (0, 64)]),
+            TextureCompression::Bc4 => (131, &[(0, 0)]),
+            TextureCompression::Bc5 => (132, &[(0, 0), (1, 64)]),
+            TextureCompression::Bc7 => (134, &[(0, 0)]),
+        };
+        let sample_length = if compression == TextureCompression::Bc7 {
+            128
+        } else {
+            64
+        };
+
+        let block_size = 24 + 16 * samples.len() as u32;
+        // BT.709 primaries with linear or sRGB transfer function
+        let transfer = if self.options.is_srgb() { 2 } else { 1 };
+
+        let mut words = vec![
+            4 + block_size,
+            // vendor and descriptor type
+            0,
+            // version 2 of the descriptor
+            2 | (block_size << 16),
+            color_model | (1 << 8) | (transfer << 16),
+            // 4x4 texel block
+            3 | (3 << 8),
+            compression.block_size() as u32,
+            0,
+        ];
+
+        for &(channel, offset) in samples {
+            words.extend([
+                offset | ((sample_length - 1) << 16) | (channel << 24),
+                0,
+                0,
+                u32::MAX,
+            ]);
+        }
+
+        words.into_iter().flat_map(u32::to_le_bytes).collect()
+    }
+}
+
+impl EncoderTrait for Ktx2Encoder {
+    fn name(&self) -> &'static str {
+        "ktx2"
+    }
+
+    fn encode_inner<T: ZByteWriterTrait>(
+        &mut self,
+        image: &Image,
+        sink: T,
+    ) -> Result<usize, ImageErrors> {
+        let (width, height) = image.dimensions();
+        let levels = compress_levels(image, &self.options)?;
+        let dfd = self.data_format_descriptor();
+
+        let dfd_offset = HEADER_LENGTH + LEVEL_INDEX_LENGTH * levels.len();
+        let block_size = self.options.compression.block_size();
+
+        // smallest levels are stored first, every level is aligned to the block size
+        let mut offsets = vec![0; levels.len()];
+        let mut end = dfd_offset + dfd.len();
+
+        for (index, level) in levels.iter().enumerate().rev() {
+            offsets[index] = end.next_multiple_of(block_size);
+            end = offsets[index] + level.len();
+        }
+
+        let mut buf = Vec::with_capacity(end);
+
+        buf.extend_from_slice(&IDENTIFIER);
+        for value in [
+            self.vk_format(),
+            // type size of block compressed formats
+            1,
+            width as u32,
+            height as u32,
+            // depth, layer count, face count
+            0,
+            0,
+            1,
+            levels.len() as u32,
+            // no supercompression
+            0,
+            dfd_offset as u32,
+            dfd.len() as u32,
+            // no key/value data
+            0,
+            0,
+        ] {
+            buf.extend_from_slice(&value.to_le_bytes());
+        }
+        // no supercompression global data
+        buf.extend_from_slice(&[0; 16]);
+
+        for (offset, level) in offsets.iter().zip(&levels) {
+            for value in [*offset, level.len(), level.len()] {
+                buf.extend_from_slice(&(value as u64).to_le_bytes());
+            }
+        }
+
+        buf.extend_from_slice(&dfd);
+
+        for (offset, level) in offsets.iter().zip(&levels).rev() {
+            buf.resize(*offset, 0);
+            buf.extend_from_slice(level);
+        }
+
+        let mut writer = ZWriter::new(sink);
+
+        writer.write(&buf).map_err(|e| {
+            ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
+        })?;
+
+        Ok(writer.bytes_written())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[ColorSpace::RGBA]
+    }
+
+    fn format(&self) -> ImageFormat {
+        ImageFormat::Unknown
+    }
+
+    fn supported_bit_depth(&self) -> &'static [BitDepth] {
+        &[BitDepth::Eight]
+    }
+
+    fn default_depth(&self, _depth: BitDepth) -> BitDepth {
+        BitDepth::Eight
+    }
+}
+
+#[cfg(test)]
+mod tests;
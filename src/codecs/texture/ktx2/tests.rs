@@ -0,0 +1,110 @@
+use std::io::Cursor;
+
+use ktx2::{BasicDataFormatDescriptor, ColorModel, Format, Reader, TransferFunction};
+
+use crate::test_utils::*;
+
+use super::*;
+
+fn encode_with(image: &Image, options: TextureOptions) -> Vec<u8> {
+    let mut buf = Cursor::new(vec![]);
+    Ktx2Encoder::new_with_options(options)
+        .encode(image, &mut buf)
+        .unwrap();
+
+    buf.into_inner()
+}
+
+#[test]
+fn encode_mipmaps() {
+    let image = create_test_image_u8(64, 48, ColorSpace::RGB);
+
+    let data = encode_with(&image, TextureOptions::default());
+    let reader = Reader::new(&data).unwrap();
+    let header = reader.header();
+
+    assert_eq!(header.format, Some(Format::BC7_SRGB_BLOCK));
+    assert_eq!((header.pixel_width, header.pixel_height), (64, 48));
+    assert_eq!(header.level_count, 7);
+    assert_eq!(
+        reader.levels().map(<[u8]>::len).collect::<Vec<_>>(),
+        [16 * 12, 8 * 6, 4 * 3, 2 * 2, 1, 1, 1].map(|blocks| blocks * 16)
+    );
+}
+
+#[test]
+fn encode_formats() {
+    let image = create_test_image_u8(32, 32, ColorSpace::RGBA);
+
+    for (compression, srgb, format, model, samples) in [
+        (
+            TextureCompression::Bc1,
+            true,
+            Format::BC1_RGB_SRGB_BLOCK,
+            ColorModel::BC1A,
+            1,
+        ),
+        (
+            TextureCompression::Bc3,
+            false,
+            Format::BC3_UNORM_BLOCK,
+            ColorModel::BC3,
+            2,
+        ),
+        (
+            TextureCompression::Bc4,
+            true,
+            Format::BC4_UNORM_BLOCK,
+            ColorModel::BC4,
+            1,
+        ),
+        (
+            TextureCompression::Bc5,
+            true,
+            Format::BC5_UNORM_BLOCK,
+            ColorModel::BC5,
+            2,
+        ),
+        (
+            TextureCompression::Bc7,
+            false,
+            Format::BC7_UNORM_BLOCK,
+            ColorModel::BC7,
+            1,
+        ),
+    ] {
+        let data = encode_with(
+            &image,
+            TextureOptions {
+                compression,
+                srgb,
+                mipmaps: false,
+                ..Default::default()
+            },
+        );
+
+        let reader = Reader::new(&data).unwrap();
+
+        assert_eq!(reader.header().format, Some(format));
+        assert_eq!(
+            reader.levels().next().unwrap().len(),
+            64 * compression.block_size()
+        );
+
+        let dfd = reader.data_format_descriptors().next().unwrap();
+        let basic = BasicDataFormatDescriptor::parse(dfd.data).unwrap();
+
+        assert_eq!(basic.color_model, Some(model));
+        assert_eq!(
+            basic.transfer_function == Some(TransferFunction::SRGB),
+            srgb && compression.has_srgb(),
+            "{compression:?}"
+        );
+        assert_eq!(basic.texel_block_dimensions, [4, 4, 1, 1]);
+        assert_eq!(
+            basic.sample_information().count(),
+            samples,
+            "{compression:?}"
+        );
+    }
+}
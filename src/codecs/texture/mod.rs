@@ -0,0 +1,216 @@
+use intel_tex_2::{bc1, bc3, bc4, bc5, bc7, RSurface, RgSurface, RgbaSurface};
+use zune_core::colorspace::ColorSpace;
+use zune_image::{errors::ImageErrors, image::Image, traits::OperationsTrait};
+
+use crate::{
+    operations::resize::{Resize, ResizeAlg},
+    options::{OptionsError, Validate},
+};
+
+mod dds;
+mod ktx2;
+
+pub use self::dds::*;
+pub use self::ktx2::*;
+
+/// Block compression of the texture data
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum TextureCompression {
+    /// RGB in 4 bits per pixel, alpha is dropped
+    Bc1,
+    /// RGBA in 8 bits per pixel with separately compressed alpha
+    Bc3,
+    /// Single red channel in 4 bits per pixel, e.g. for masks and height maps
+    Bc4,
+    /// Red and green channels in 8 bits per pixel, e.g. for normal maps
+    Bc5,
+    /// RGBA in 8 bits per pixel with the best quality
+    #[default]
+    Bc7,
+}
+
+impl TextureCompression {
+    /// Size of a compressed 4x4 block in bytes
+    fn block_size(self) -> usize {
+        match self {
+            TextureCompression::Bc1 | TextureCompression::Bc4 => 8,
+            _ => 16,
+        }
+    }
+
+    /// Whether the format has an sRGB variant, otherwise data is always linear
+    fn has_srgb(self) -> bool {
+        !matches!(self, TextureCompression::Bc4 | TextureCompression::Bc5)
+    }
+}
+
+/// Advanced options for GPU texture encoding
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct TextureOptions {
+    /// Block compression of the texture data
+    pub compression: TextureCompression,
+    /// Mark color data as sRGB encoded, otherwise as linear
+    ///
+    /// Ignored for [`TextureCompression::Bc4`] and [`TextureCompression::Bc5`],
+    /// which have no sRGB variants.
+    pub srgb: bool,
+    /// Generate a full mipmap chain down to 1x1
+    pub mipmaps: bool,
+    /// Algorithm used to resize the image to every mip level
+    #[cfg_attr(feature = "serde", serde(with = "crate::options::remote::resize_alg"))]
+    pub algorithm: ResizeAlg,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            compression: TextureCompression::Bc7,
+            srgb: true,
+            mipmaps: true,
+            algorithm: ResizeAlg::default(),
+        }
+    }
+}
+
+impl Validate for TextureOptions {
+    /// Every combination of texture options is valid
+    fn validate(&self) -> Result<(), OptionsError> {
+        Ok(())
+    }
+}
+
+impl TextureOptions {
+    fn is_srgb(&self) -> bool {
+        self.srgb && self.compression.has_srgb()
+    }
+}
+
+/// Dimensions of every mip level of `width` and `height`, starting from the base level
+fn level_dimensions(width: usize, height: usize, mipmaps: bool) -> Vec<(usize, usize)> {
+    let mut levels = vec![(width, height)];
+
+    while mipmaps && levels.last() != Some(&(1, 1)) {
+        let (width, height) = levels.last().copied().unwrap();
+        levels.push(((width / 2).max(1), (height / 2).max(1)));
+    }
+
+    levels
+}
+
+/// Resize first frame of RGBA `image` to every mip level and compress it
+///
+/// Blocks of the levels are returned starting from the base level.
+fn compress_levels(image: &Image, options: &TextureOptions) -> Result<Vec<Vec<u8>>, ImageErrors> {
+    let (width, height) = image.dimensions();
+    let base = Image::from_u8(&image.flatten_to_u8()[0], width, height, ColorSpace::RGBA);
+
+    let has_alpha = base.flatten_to_u8()[0]
+        .chunks_exact(4)
+        .any(|px| px[3] != 255);
+
+    level_dimensions(width, height, options.mipmaps)
+        .into_iter()
+        .map(|(level_width, level_height)| {
+            let mut level = base.clone();
+
+            if (level_width, level_height) != (width, height) {
+                Resize::new(level_width, level_height, options.algorithm).execute(&mut level)?;
+            }
+
+            let pixels = &level.flatten_to_u8()[0];
+
+            Ok(compress(
+                pixels,
+                level_width,
+                level_height,
+                options.compression,
+                has_alpha,
+            ))
+        })
+        .collect()
+}
+
+/// Compress RGBA `pixels` into blocks, partial blocks on the edges are padded
+fn compress(
+    pixels: &[u8],
+    width: usize,
+    height: usize,
+    compression: TextureCompression,
+    has_alpha: bool,
+) -> Vec<u8> {
+    let channels = match compression {
+        TextureCompression::Bc4 => 1,
+        TextureCompression::Bc5 => 2,
+        _ => 4,
+    };
+
+    let (data, width, height) = pad_to_blocks(pixels, width, height, channels);
+    let (width, height) = (width as u32, height as u32);
+    let stride = width * channels as u32;
+
+    match compression {
+        TextureCompression::Bc4 => bc4::compress_blocks(&RSurface {
+            data: &data,
+            width,
+            height,
+            stride,
+        }),
+        TextureCompression::Bc5 => bc5::compress_blocks(&RgSurface {
+            data: &data,
+            width,
+            height,
+            stride,
+        }),
+        _ => {
+            let surface = RgbaSurface {
+                data: &data,
+                width,
+                height,
+                stride,
+            };
+
+            match compression {
+                TextureCompression::Bc1 => bc1::compress_blocks(&surface),
+                TextureCompression::Bc3 => bc3::compress_blocks(&surface),
+                _ if has_alpha => bc7::compress_blocks(&bc7::alpha_basic_settings(), &surface),
+                _ => bc7::compress_blocks(&bc7::opaque_basic_settings(), &surface),
+            }
+        }
+    }
+}
+
+/// Keep first `channels` of RGBA `pixels` and repeat edge pixels up to a multiple of 4
+fn pad_to_blocks(
+    pixels: &[u8],
+    width: usize,
+    height: usize,
+    channels: usize,
+) -> (Vec<u8>, usize, usize) {
+    let padded_width = width.div_ceil(4) * 4;
+    let padded_height = height.div_ceil(4) * 4;
+
+    let mut data = Vec::with_capacity(padded_width * padded_height * channels);
+
+    for y in 0..padded_height {
+        for x in 0..padded_width {
+            let offset = (y.min(height - 1) * width + x.min(width - 1)) * 4;
+
+            data.extend_from_slice(&pixels[offset..offset + channels]);
+        }
+    }
+
+    (data, padded_width, padded_height)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,39 @@
+use super::*;
+
+#[test]
+fn mip_levels() {
+    assert_eq!(level_dimensions(5, 3, true), [(5, 3), (2, 1), (1, 1)]);
+    assert_eq!(level_dimensions(64, 64, true).len(), 7);
+    assert_eq!(level_dimensions(64, 64, false), [(64, 64)]);
+}
+
+#[test]
+fn pad_edges() {
+    // 2x1 RGBA image
+    let pixels = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    let (data, width, height) = pad_to_blocks(&pixels, 2, 1, 2);
+
+    assert_eq!((width, height), (4, 4));
+    assert_eq!(data.len(), 4 * 4 * 2);
+    assert_eq!(&data[..8], &[1, 2, 5, 6, 5, 6, 5, 6]);
+    assert_eq!(&data[24..], &data[..8]);
+}
+
+#[test]
+fn compress_block_sizes() {
+    let pixels = vec![128; 6 * 5 * 4];
+
+    for (compression, block_size) in [
+        (TextureCompression::Bc1, 8),
+        (TextureCompression::Bc3, 16),
+        (TextureCompression::Bc4, 8),
+        (TextureCompression::Bc5, 16),
+        (TextureCompression::Bc7, 16),
+    ] {
+        let data = compress(&pixels, 6, 5, compression, false);
+
+        // 2x2 blocks
+        assert_eq!(data.len(), 4 * block_size, "{compression:?}");
+    }
+}
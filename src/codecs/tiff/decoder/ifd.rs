@@ -0,0 +1,226 @@
+//! EXIF attributes read straight from the directories of a TIFF file
+//!
+//! Only the first page directory and the Exif, GPS and Interoperability
+//! directories it points to are read, image data is never loaded.
+
+use std::io::{self, Cursor, Read, Seek};
+
+use exif::{experimental::Writer, Context, Field, In, Rational, SRational, Tag, Value};
+use tiff::decoder::Decoder;
+
+const EXIF_POINTER_TAG: u16 = 0x8769;
+const GPS_POINTER_TAG: u16 = 0x8825;
+const INTEROP_POINTER_TAG: u16 = 0xa005;
+
+/// Values larger than this are skipped instead of being read into memory
+const MAX_VALUE_LEN: u64 = 1 << 20;
+
+/// Baseline tags of the first page kept as EXIF attributes,
+/// tags describing layout of the image data are left out
+const DESCRIPTIVE_TAGS: &[Tag] = &[
+    Tag::ImageDescription,
+    Tag::Make,
+    Tag::Model,
+    Tag::Orientation,
+    Tag::XResolution,
+    Tag::YResolution,
+    Tag::ResolutionUnit,
+    Tag::Software,
+    Tag::DateTime,
+    Tag::Artist,
+    Tag::Copyright,
+];
+
+/// Single entry of a directory
+struct Entry {
+    tag: u16,
+    value: Value,
+}
+
+/// Read EXIF attributes of the first page as a standalone TIFF structure
+///
+/// The result can be written into the files of other formats, malformed EXIF is skipped.
+/// Reader position of the `decoder` is changed, it seeks before reading anything else.
+pub(super) fn read_exif<R: Read + Seek>(decoder: &mut Decoder<R>) -> Option<Vec<u8>> {
+    let (fields, little_endian) = match read_fields(decoder) {
+        Ok(result) => result,
+        Err(e) => {
+            log::warn!("Reading exif failed {e}");
+            return None;
+        }
+    };
+
+    if fields.is_empty() {
+        return None;
+    }
+
+    let mut writer = Writer::new();
+    fields.iter().for_each(|field| writer.push_field(field));
+
+    let mut buf = Cursor::new(vec![]);
+
+    match writer.write(&mut buf, little_endian) {
+        Ok(()) => Some(buf.into_inner()),
+        Err(e) => {
+            log::warn!("Reading exif failed {:?}", e);
+            None
+        }
+    }
+}
+
+/// Fields of the first page along with the byte order of the file
+fn read_fields<R: Read + Seek>(decoder: &mut Decoder<R>) -> io::Result<(Vec<Field>, bool)> {
+    decoder.goto_offset(0)?;
+    let little_endian = decoder.read_byte()? == b'I';
+
+    decoder.goto_offset(2)?;
+    let bigtiff = decoder.read_short()? == 43;
+
+    decoder.goto_offset(if bigtiff { 8 } else { 4 })?;
+    let first = decoder.read_ifd_offset()?;
+
+    let mut fields = vec![];
+    let mut pointers = vec![];
+
+    for entry in read_directory(decoder, first, bigtiff)? {
+        match entry.tag {
+            EXIF_POINTER_TAG => pointers.push((Context::Exif, entry.value)),
+            GPS_POINTER_TAG => pointers.push((Context::Gps, entry.value)),
+            tag if DESCRIPTIVE_TAGS.contains(&Tag(Context::Tiff, tag)) => {
+                fields.push(field(Context::Tiff, entry));
+            }
+            _ => {}
+        }
+    }
+
+    while let Some((context, value)) = pointers.pop() {
+        let Some(offset) = value.get_uint(0) else {
+            continue;
+        };
+
+        for entry in read_directory(decoder, u64::from(offset), bigtiff)? {
+            match entry.tag {
+                INTEROP_POINTER_TAG if context == Context::Exif => {
+                    pointers.push((Context::Interop, entry.value));
+                }
+                _ => fields.push(field(context, entry)),
+            }
+        }
+    }
+
+    Ok((fields, little_endian))
+}
+
+fn field(context: Context, entry: Entry) -> Field {
+    Field {
+        tag: Tag(context, entry.tag),
+        ifd_num: In::PRIMARY,
+        value: entry.value,
+    }
+}
+
+/// Read entries of the directory at `offset`, entries of unknown or oversized values are skipped
+fn read_directory<R: Read + Seek>(
+    decoder: &mut Decoder<R>,
+    offset: u64,
+    bigtiff: bool,
+) -> io::Result<Vec<Entry>> {
+    decoder.goto_offset_u64(offset)?;
+
+    let (count, entry_len, inline_len) = if bigtiff {
+        (decoder.read_long8()?, 20, 8)
+    } else {
+        (u64::from(decoder.read_short()?), 12, 4)
+    };
+
+    let mut entries = vec![];
+
+    for index in 0..count {
+        let start = offset + if bigtiff { 8 } else { 2 } + index * entry_len;
+        decoder.goto_offset_u64(start)?;
+
+        let tag = decoder.read_short()?;
+        let kind = decoder.read_short()?;
+        let count = if bigtiff {
+            decoder.read_long8()?
+        } else {
+            u64::from(decoder.read_long()?)
+        };
+
+        let Some(len) = value_size(kind).map(|size| size.saturating_mul(count)) else {
+            continue;
+        };
+
+        if len > MAX_VALUE_LEN {
+            log::warn!("Skipping EXIF tag {tag:#06x}, its value is {len} bytes long");
+            continue;
+        }
+
+        if len > inline_len {
+            let value_offset = decoder.read_ifd_offset()?;
+            decoder.goto_offset_u64(value_offset)?;
+        }
+
+        let value = read_value(decoder, kind, count as usize)?;
+
+        entries.push(Entry { tag, value });
+    }
+
+    Ok(entries)
+}
+
+/// Size of a single value of the TIFF field type
+fn value_size(kind: u16) -> Option<u64> {
+    match kind {
+        1 | 2 | 6 | 7 => Some(1),
+        3 | 8 => Some(2),
+        4 | 9 | 11 | 13 => Some(4),
+        5 | 10 | 12 => Some(8),
+        _ => None,
+    }
+}
+
+fn read_value<R: Read + Seek>(
+    decoder: &mut Decoder<R>,
+    kind: u16,
+    count: usize,
+) -> io::Result<Value> {
+    fn read_n<R: Read + Seek, T>(
+        decoder: &mut Decoder<R>,
+        count: usize,
+        mut read: impl FnMut(&mut Decoder<R>) -> io::Result<T>,
+    ) -> io::Result<Vec<T>> {
+        (0..count).map(|_| read(decoder)).collect()
+    }
+
+    Ok(match kind {
+        1 => Value::Byte(read_n(decoder, count, Decoder::read_byte)?),
+        2 => Value::Ascii(
+            read_n(decoder, count, Decoder::read_byte)?
+                .split(|&b| b == 0)
+                .filter(|s| !s.is_empty())
+                .map(<[u8]>::to_vec)
+                .collect(),
+        ),
+        3 => Value::Short(read_n(decoder, count, Decoder::read_short)?),
+        4 | 13 => Value::Long(read_n(decoder, count, Decoder::read_long)?),
+        5 => Value::Rational(read_n(decoder, count, |d| {
+            Ok(Rational {
+                num: d.read_long()?,
+                denom: d.read_long()?,
+            })
+        })?),
+        6 => Value::SByte(read_n(decoder, count, |d| Ok(d.read_byte()? as i8))?),
+        7 => Value::Undefined(read_n(decoder, count, Decoder::read_byte)?, 0),
+        8 => Value::SShort(read_n(decoder, count, Decoder::read_sshort)?),
+        9 => Value::SLong(read_n(decoder, count, Decoder::read_slong)?),
+        10 => Value::SRational(read_n(decoder, count, |d| {
+            Ok(SRational {
+                num: d.read_slong()?,
+                denom: d.read_slong()?,
+            })
+        })?),
+        11 => Value::Float(read_n(decoder, count, Decoder::read_float)?),
+        _ => Value::Double(read_n(decoder, count, Decoder::read_double)?),
+    })
+}
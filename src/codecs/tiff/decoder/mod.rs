@@ -1,28 +1,202 @@
 use std::io::{Read, Seek};
 
-use zune_core::colorspace::ColorSpace;
-use zune_image::{errors::ImageErrors, image::Image, traits::DecoderTrait};
+use tiff::{tags::Tag, ColorType};
+use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
+use zune_image::{errors::ImageErrors, frame::Frame, image::Image, traits::DecoderTrait};
+
+use super::{ICC_PROFILE_TAG, XMP_TAG};
+use crate::{Format, ImageInfo};
+
+#[cfg(feature = "metadata")]
+mod ifd;
+
+/// Pages of a multi-page TIFF file that are decoded
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TiffPages {
+    /// Decode every page matching the first one into a separate frame
+    ///
+    /// Pages with dimensions or color type different from the first page,
+    /// like thumbnails or reduced resolution levels, are skipped
+    /// and listed by [`TiffDecoder::skipped_pages`].
+    #[default]
+    AllMatching,
+    /// Decode only the page with the zero-based index
+    Page(usize),
+    /// Decode only the page with the largest resolution, e.g. the base level of a pyramidal TIFF
+    Largest,
+}
+
+/// Advanced options for Tiff decoding
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TiffDecoderOptions {
+    /// Pages decoded from the file
+    pub pages: TiffPages,
+}
 
 /// A Tiff decoder
 pub struct TiffDecoder<R: Read + Seek> {
     inner: tiff::decoder::Decoder<R>,
-    dimensions: Option<(usize, usize)>,
-    colorspace: ColorSpace,
+    pages: Vec<Page>,
+    /// Indices of the pages decoded as frames
+    selected: Vec<usize>,
+    /// Indices of the pages not matching the first one
+    skipped: Vec<usize>,
+    #[cfg(feature = "metadata")]
+    exif: Option<Vec<u8>>,
+}
+
+/// Header information of a single TIFF page
+struct Page {
+    dimensions: (usize, usize),
+    colortype: ColorType,
+    icc: Option<Vec<u8>>,
+    xmp: Option<Vec<u8>>,
 }
 
 impl<R: Read + Seek> TiffDecoder<R> {
     /// Create a new tiff decoder that reads data from `source`
     pub fn try_new(source: R) -> Result<Self, ImageErrors> {
-        let inner = tiff::decoder::Decoder::new(source).map_err(|e| {
+        Self::try_new_with_options(source, TiffDecoderOptions::default())
+    }
+
+    /// Create a new tiff decoder with specified options that reads data from `source`
+    pub fn try_new_with_options(
+        source: R,
+        options: TiffDecoderOptions,
+    ) -> Result<Self, ImageErrors> {
+        let mut inner = tiff::decoder::Decoder::new(source).map_err(|e| {
             ImageErrors::ImageDecodeErrors(format!("Unable to create TIFF decoder: {}", e))
         })?;
 
+        #[cfg(feature = "metadata")]
+        let exif = ifd::read_exif(&mut inner);
+
+        // walking the IFD chain reads only the page headers
+        let mut pages = vec![read_page(&mut inner)?];
+        while inner.more_images() {
+            inner.next_image().map_err(|e| {
+                ImageErrors::ImageDecodeErrors(format!("Unable to read TIFF page - {}", e))
+            })?;
+            pages.push(read_page(&mut inner)?);
+        }
+
+        let mut skipped = vec![];
+
+        let selected = match options.pages {
+            TiffPages::AllMatching => {
+                let first = &pages[0];
+
+                let (selected, mismatched): (Vec<_>, Vec<_>) =
+                    (0..pages.len()).partition(|&index| {
+                        let page = &pages[index];
+                        page.dimensions == first.dimensions && page.colortype == first.colortype
+                    });
+
+                if !mismatched.is_empty() {
+                    log::warn!(
+                        "Skipping TIFF pages {mismatched:?}, their dimensions or color type differ from the first page"
+                    );
+                }
+
+                skipped = mismatched;
+                selected
+            }
+            TiffPages::Page(index) if index < pages.len() => vec![index],
+            TiffPages::Page(index) => {
+                return Err(ImageErrors::ImageDecodeErrors(format!(
+                    "TIFF page {index} does not exist, the file has {} pages",
+                    pages.len()
+                )))
+            }
+            TiffPages::Largest => {
+                // the first page wins among pages of equal size
+                let largest = (0..pages.len())
+                    .rev()
+                    .max_by_key(|&index| {
+                        let (width, height) = pages[index].dimensions;
+                        width * height
+                    })
+                    .unwrap_or(0);
+
+                vec![largest]
+            }
+        };
+
         Ok(Self {
             inner,
-            dimensions: None,
-            colorspace: ColorSpace::Unknown,
+            pages,
+            selected,
+            skipped,
+            #[cfg(feature = "metadata")]
+            exif,
         })
     }
+
+    /// Number of pages in the file, including the pages that are not decoded
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Indices of the pages skipped by [`TiffPages::AllMatching`]
+    pub fn skipped_pages(&self) -> &[usize] {
+        &self.skipped
+    }
+
+    /// Raw XMP packet of the first decoded page
+    pub fn xmp(&self) -> Option<&[u8]> {
+        self.page().xmp.as_deref()
+    }
+
+    /// Image information read from the headers
+    ///
+    /// Describes the first decoded page, `frame_count` is the number of decoded pages.
+    pub fn info(&self) -> ImageInfo {
+        let page = self.page();
+
+        ImageInfo {
+            format: Format::Tiff,
+            dimensions: page.dimensions,
+            colorspace: page.colorspace(),
+            depth: match page.bits_per_sample() {
+                0..=8 => BitDepth::Eight,
+                9..=16 => BitDepth::Sixteen,
+                _ => BitDepth::Float32,
+            },
+            frame_count: self.selected.len(),
+            has_alpha: matches!(page.colortype, ColorType::GrayA(_) | ColorType::RGBA(_)),
+        }
+    }
+
+    /// First decoded page, all other decoded pages share its dimensions and color type
+    fn page(&self) -> &Page {
+        &self.pages[self.selected[0]]
+    }
+}
+
+impl Page {
+    fn bits_per_sample(&self) -> u8 {
+        match self.colortype {
+            ColorType::Gray(bits)
+            | ColorType::RGB(bits)
+            | ColorType::Palette(bits)
+            | ColorType::GrayA(bits)
+            | ColorType::RGBA(bits)
+            | ColorType::CMYK(bits)
+            | ColorType::YCbCr(bits) => bits,
+        }
+    }
+
+    fn colorspace(&self) -> ColorSpace {
+        match self.colortype {
+            ColorType::RGB(_) => ColorSpace::RGB,
+            ColorType::RGBA(_) => ColorSpace::RGBA,
+            ColorType::CMYK(_) => ColorSpace::CMYK,
+            ColorType::Gray(_) => ColorSpace::Luma,
+            ColorType::GrayA(_) => ColorSpace::LumaA,
+            ColorType::YCbCr(_) => ColorSpace::YCbCr,
+            _ => ColorSpace::Unknown,
+        }
+    }
 }
 
 impl<R> DecoderTrait for TiffDecoder<R>
@@ -30,57 +204,64 @@ where
     R: Read + Seek,
 {
     fn decode(&mut self) -> Result<Image, ImageErrors> {
-        let (width, height) = self.inner.dimensions().map_err(|e| {
-            ImageErrors::ImageDecodeErrors(format!("Unable to read dimensions - {}", e))
-        })?;
+        let (width, height) = self.page().dimensions;
+        let colorspace = self.page().colorspace();
 
-        let (width, height) = (width as usize, height as usize);
-        self.dimensions = Some((width, height));
-
-        let colorspace = self
-            .inner
-            .colortype()
-            .map(|colortype| match colortype {
-                tiff::ColorType::RGB(_) => ColorSpace::RGB,
-                tiff::ColorType::RGBA(_) => ColorSpace::RGBA,
-                tiff::ColorType::CMYK(_) => ColorSpace::CMYK,
-                tiff::ColorType::Gray(_) => ColorSpace::Luma,
-                tiff::ColorType::GrayA(_) => ColorSpace::LumaA,
-                tiff::ColorType::YCbCr(_) => ColorSpace::YCbCr,
-                _ => ColorSpace::Unknown,
-            })
-            .map_err(|e| {
-                ImageErrors::ImageDecodeErrors(format!("Unable to read colorspace - {}", e))
+        let mut frames = Vec::with_capacity(self.selected.len());
+        let mut depth = BitDepth::Eight;
+
+        for &index in &self.selected {
+            self.inner.seek_to_image(index).map_err(|e| {
+                ImageErrors::ImageDecodeErrors(format!("Unable to read TIFF page - {}", e))
             })?;
 
-        self.colorspace = colorspace;
+            let result = self.inner.read_image().map_err(|e| {
+                ImageErrors::ImageDecodeErrors(format!("Unable to decode TIFF file - {}", e))
+            })?;
 
-        let result = self.inner.read_image().map_err(|e| {
-            ImageErrors::ImageDecodeErrors(format!("Unable to decode TIFF file - {}", e))
-        })?;
+            let frame = match result {
+                tiff::decoder::DecodingResult::U8(data) => {
+                    depth = BitDepth::Eight;
+                    Frame::from_u8(&data, colorspace, 0, 0)
+                }
+                tiff::decoder::DecodingResult::U16(data) => {
+                    depth = BitDepth::Sixteen;
+                    Frame::from_u16(&data, colorspace, 0, 0)
+                }
+                tiff::decoder::DecodingResult::F32(data) => {
+                    depth = BitDepth::Float32;
+                    Frame::from_f32(&data, colorspace, 0, 0)
+                }
+                _ => {
+                    return Err(ImageErrors::ImageDecodeErrors(
+                        "Tiff Data format not supported".to_string(),
+                    ))
+                }
+            };
 
-        match result {
-            tiff::decoder::DecodingResult::U8(data) => {
-                Ok(Image::from_u8(&data, width, height, colorspace))
-            }
-            tiff::decoder::DecodingResult::U16(data) => {
-                Ok(Image::from_u16(&data, width, height, colorspace))
-            }
-            tiff::decoder::DecodingResult::F32(data) => {
-                Ok(Image::from_f32(&data, width, height, colorspace))
-            }
-            _ => Err(ImageErrors::ImageDecodeErrors(
-                "Tiff Data format not supported".to_string(),
-            )),
+            frames.push(frame);
+        }
+
+        let mut image = Image::new_frames(frames, depth, width, height, colorspace);
+
+        if let Some(icc) = &self.page().icc {
+            image.metadata_mut().set_icc_chunk(icc.clone());
+        }
+
+        #[cfg(feature = "metadata")]
+        if let Some(exif) = &self.exif {
+            image.metadata_mut().parse_raw_exif(exif);
         }
+
+        Ok(image)
     }
 
     fn dimensions(&self) -> Option<(usize, usize)> {
-        self.dimensions
+        Some(self.page().dimensions)
     }
 
     fn out_colorspace(&self) -> ColorSpace {
-        self.colorspace
+        self.page().colorspace()
     }
 
     fn name(&self) -> &'static str {
@@ -88,5 +269,37 @@ where
     }
 }
 
+/// Read header information of the current page
+fn read_page<R: Read + Seek>(decoder: &mut tiff::decoder::Decoder<R>) -> Result<Page, ImageErrors> {
+    let (width, height) = decoder.dimensions().map_err(|e| {
+        ImageErrors::ImageDecodeErrors(format!("Unable to read dimensions - {}", e))
+    })?;
+
+    let colortype = decoder.colortype().map_err(|e| {
+        ImageErrors::ImageDecodeErrors(format!("Unable to read colorspace - {}", e))
+    })?;
+
+    Ok(Page {
+        dimensions: (width as usize, height as usize),
+        colortype,
+        icc: read_bytes_tag(decoder, ICC_PROFILE_TAG),
+        xmp: read_bytes_tag(decoder, XMP_TAG),
+    })
+}
+
+/// Read metadata stored in the `tag` of the current page, malformed metadata is skipped
+fn read_bytes_tag<R: Read + Seek>(
+    decoder: &mut tiff::decoder::Decoder<R>,
+    tag: Tag,
+) -> Option<Vec<u8>> {
+    decoder
+        .find_tag(tag)
+        .and_then(|value| value.map(|v| v.into_u8_vec()).transpose())
+        .unwrap_or_else(|e| {
+            log::warn!("Reading {tag:?} failed {e}");
+            None
+        })
+}
+
 #[cfg(test)]
 mod tests;
@@ -13,3 +13,167 @@ fn decode() {
     assert_eq!(img.dimensions(), (48, 80));
     assert_eq!(img.colorspace(), ColorSpace::RGB);
 }
+
+#[test]
+fn info_without_decoding() {
+    let file_content = File::open("tests/files/tiff/f1t.tif").unwrap();
+
+    let decoder = TiffDecoder::try_new(file_content).unwrap();
+    let info = decoder.info();
+
+    assert_eq!(decoder.dimensions(), Some((48, 80)));
+    assert_eq!(info.depth, BitDepth::Eight);
+    assert_eq!(info.frame_count, 1);
+}
+
+#[test]
+fn decode_metadata() {
+    use std::io::Cursor;
+    use tiff::encoder::{colortype::RGB8, TiffEncoder};
+
+    let icc = std::fs::read("tests/files/icc/tinysrgb.icc").unwrap();
+
+    let mut buf = Cursor::new(vec![]);
+    {
+        let mut encoder = TiffEncoder::new(&mut buf).unwrap();
+        let mut image = encoder.new_image::<RGB8>(2, 2).unwrap();
+
+        image
+            .encoder()
+            .write_tag(ICC_PROFILE_TAG, &icc[..])
+            .unwrap();
+        image
+            .encoder()
+            .write_tag(XMP_TAG, &b"<x:xmpmeta/>"[..])
+            .unwrap();
+        image.write_data(&[0; 12]).unwrap();
+    }
+    buf.set_position(0);
+
+    let mut decoder = TiffDecoder::try_new(buf).unwrap();
+
+    assert_eq!(decoder.xmp(), Some(&b"<x:xmpmeta/>"[..]));
+
+    let img = decoder.decode().unwrap();
+
+    assert_eq!(img.metadata().icc_chunk(), Some(&icc));
+}
+
+/// Multi-page TIFF with two full resolution pages followed by a reduced resolution page
+fn multi_page() -> std::io::Cursor<Vec<u8>> {
+    use std::io::Cursor;
+    use tiff::encoder::{colortype::RGB8, TiffEncoder};
+
+    let mut buf = Cursor::new(vec![]);
+    {
+        let mut encoder = TiffEncoder::new(&mut buf).unwrap();
+
+        encoder.write_image::<RGB8>(4, 4, &[255; 48]).unwrap();
+        encoder.write_image::<RGB8>(4, 4, &[128; 48]).unwrap();
+        encoder.write_image::<RGB8>(2, 2, &[0; 12]).unwrap();
+    }
+    buf.set_position(0);
+
+    buf
+}
+
+#[test]
+fn decode_all_pages() {
+    let mut decoder = TiffDecoder::try_new(multi_page()).unwrap();
+
+    assert_eq!(decoder.page_count(), 3);
+    assert_eq!(decoder.info().frame_count, 2);
+    assert_eq!(decoder.skipped_pages(), &[2]);
+
+    let img = decoder.decode().unwrap();
+    let frames = img.flatten_to_u8();
+
+    assert_eq!(img.dimensions(), (4, 4));
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0][0], 255);
+    assert_eq!(frames[1][0], 128);
+}
+
+#[test]
+fn decode_selected_page() {
+    let options = TiffDecoderOptions {
+        pages: TiffPages::Page(2),
+    };
+    let mut decoder = TiffDecoder::try_new_with_options(multi_page(), options).unwrap();
+
+    assert_eq!(decoder.dimensions(), Some((2, 2)));
+
+    let img = decoder.decode().unwrap();
+
+    assert_eq!(img.frames_ref().len(), 1);
+    assert_eq!(img.flatten_to_u8()[0][0], 0);
+
+    let options = TiffDecoderOptions {
+        pages: TiffPages::Page(3),
+    };
+
+    assert!(TiffDecoder::try_new_with_options(multi_page(), options).is_err());
+}
+
+#[test]
+fn decode_largest_page() {
+    let options = TiffDecoderOptions {
+        pages: TiffPages::Largest,
+    };
+    let mut decoder = TiffDecoder::try_new_with_options(multi_page(), options).unwrap();
+
+    assert_eq!(decoder.dimensions(), Some((4, 4)));
+
+    let img = decoder.decode().unwrap();
+
+    assert_eq!(img.frames_ref().len(), 1);
+    assert_eq!(img.flatten_to_u8()[0][0], 255);
+}
+
+#[cfg(feature = "metadata")]
+#[test]
+fn decode_exif() {
+    use std::io::Cursor;
+
+    use exif::{experimental::Writer, Field, In, Tag, Value};
+    use zune_image::traits::EncoderTrait;
+
+    use crate::{codecs::tiff::TiffEncoder, test_utils::create_test_image_u8};
+
+    let fields = [
+        Field {
+            tag: Tag::Artist,
+            ifd_num: In::PRIMARY,
+            value: Value::Ascii(vec![b"rimage".to_vec()]),
+        },
+        Field {
+            tag: Tag::DateTimeOriginal,
+            ifd_num: In::PRIMARY,
+            value: Value::Ascii(vec![b"2024:01:01 00:00:00".to_vec()]),
+        },
+    ];
+
+    let mut writer = Writer::new();
+    fields.iter().for_each(|field| writer.push_field(field));
+    let mut exif = Cursor::new(vec![]);
+    writer.write(&mut exif, true).unwrap();
+
+    let mut image = create_test_image_u8(8, 8, ColorSpace::RGB);
+    image.metadata_mut().parse_raw_exif(exif.get_ref());
+
+    let mut buf = Cursor::new(vec![]);
+    TiffEncoder::new().encode(&image, &mut buf).unwrap();
+    buf.set_position(0);
+
+    let img = TiffDecoder::try_new(buf).unwrap().decode().unwrap();
+    let exif = img.metadata().exif().unwrap();
+
+    for expected in &fields {
+        let field = exif.iter().find(|field| field.tag == expected.tag).unwrap();
+
+        assert_eq!(
+            field.display_value().to_string(),
+            expected.display_value().to_string()
+        );
+    }
+}
@@ -0,0 +1,475 @@
+use std::io::{Cursor, Seek, Write};
+
+use tiff::{
+    encoder::{
+        colortype::{self, ColorType},
+        compression::{Compression, Deflate, Lzw, Packbits, Uncompressed},
+        TiffEncoder as Encoder, TiffValue,
+    },
+    tags::{PhotometricInterpretation, Predictor, SampleFormat, Tag},
+    TiffResult,
+};
+use zune_core::{
+    bit_depth::BitDepth,
+    bytestream::{ZByteWriterTrait, ZWriter},
+    colorspace::ColorSpace,
+};
+use zune_image::{
+    codecs::ImageFormat,
+    errors::{ImageErrors, ImgEncodeErrors},
+    frame::Frame,
+    image::Image,
+    traits::EncoderTrait,
+};
+
+use super::{ICC_PROFILE_TAG, XMP_TAG};
+use crate::options::{OptionsError, Validate};
+
+/// Compression applied to the image data
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum TiffCompression {
+    /// Store samples as is
+    None,
+    /// Lempel-Ziv-Welch compression
+    #[default]
+    Lzw,
+    /// Deflate compression, also known as Adobe Deflate or ZIP
+    Deflate,
+    /// PackBits run-length compression
+    PackBits,
+}
+
+/// Advanced options for Tiff encoding
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct TiffOptions {
+    /// Compression of the image data
+    pub compression: TiffCompression,
+    /// Apply predictor before compression
+    ///
+    /// Horizontal differencing is used for integer samples and floating point
+    /// predictor for float samples. Ignored for [`TiffCompression::None`] and
+    /// [`TiffCompression::PackBits`], the predictor helps only dictionary based compression.
+    pub predictor: bool,
+    /// Raw XMP packet written along with ICC profile and EXIF of the image
+    pub xmp: Option<Vec<u8>>,
+}
+
+impl Validate for TiffOptions {
+    /// Every combination of Tiff options is valid
+    fn validate(&self) -> Result<(), OptionsError> {
+        Ok(())
+    }
+}
+
+/// A Tiff encoder
+///
+/// Every frame of the image is written as a separate page.
+#[derive(Default)]
+pub struct TiffEncoder {
+    options: TiffOptions,
+}
+
+impl TiffEncoder {
+    /// Create a new encoder
+    pub fn new() -> TiffEncoder {
+        TiffEncoder::default()
+    }
+
+    /// Create a new encoder with specified options
+    pub fn new_with_options(options: TiffOptions) -> TiffEncoder {
+        TiffEncoder { options }
+    }
+}
+
+impl EncoderTrait for TiffEncoder {
+    fn name(&self) -> &'static str {
+        "tiff"
+    }
+
+    fn encode_inner<T: ZByteWriterTrait>(
+        &mut self,
+        image: &Image,
+        sink: T,
+    ) -> Result<usize, ImageErrors> {
+        let mut writer = ZWriter::new(sink);
+
+        let mut buf = Cursor::new(vec![]);
+
+        {
+            let mut encoder = Encoder::new(&mut buf).map_err(|e| {
+                ImgEncodeErrors::ImageEncodeErrors(format!("tiff encoding failed: {e}"))
+            })?;
+
+            for (index, frame) in image.frames_ref().iter().enumerate() {
+                self.write_page(&mut encoder, image, frame, index == 0)?;
+            }
+        }
+
+        writer.write(buf.get_ref()).map_err(|e| {
+            ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
+        })?;
+
+        Ok(writer.bytes_written())
+    }
+
+    fn supported_colorspaces(&self) -> &'static [ColorSpace] {
+        &[
+            ColorSpace::Luma,
+            ColorSpace::LumaA,
+            ColorSpace::RGB,
+            ColorSpace::RGBA,
+            ColorSpace::CMYK,
+        ]
+    }
+
+    /// zune-image has no TIFF format, [`DynEncoder::format`](crate::codecs::DynEncoder::format)
+    /// reports [`Format::Tiff`](crate::Format::Tiff)
+    fn format(&self) -> ImageFormat {
+        ImageFormat::Unknown
+    }
+
+    fn supported_bit_depth(&self) -> &'static [BitDepth] {
+        &[BitDepth::Eight, BitDepth::Sixteen, BitDepth::Float32]
+    }
+
+    fn default_depth(&self, depth: BitDepth) -> BitDepth {
+        match depth {
+            BitDepth::Sixteen => BitDepth::Sixteen,
+            BitDepth::Float32 => BitDepth::Float32,
+            _ => BitDepth::Eight,
+        }
+    }
+
+    fn supports_animated_images(&self) -> bool {
+        true
+    }
+}
+
+impl TiffEncoder {
+    /// Write `frame` of the `image` as a page, metadata of the image is written only once
+    fn write_page<W: Write + Seek>(
+        &self,
+        encoder: &mut Encoder<W>,
+        image: &Image,
+        frame: &Frame,
+        first: bool,
+    ) -> Result<(), ImageErrors> {
+        use colortype::*;
+
+        let colorspace = image.colorspace();
+        let channels = colorspace.num_components();
+        let width = image.dimensions().0;
+        let metadata = PageMetadata {
+            image,
+            xmp: self.options.xmp.as_deref(),
+            first,
+        };
+
+        macro_rules! page {
+            ($colortype:ty, $sample:ty) => {{
+                let mut data = frame.flatten::<$sample>(colorspace);
+                let predictor = self.predictor(|| {
+                    predict_horizontal(&mut data, channels, width, <$sample>::wrapping_sub);
+                    Predictor::Horizontal
+                });
+
+                self.write_data::<W, $colortype>(encoder, image, &data, predictor, &metadata)
+            }};
+            ($colortype:ty) => {{
+                let mut data = frame.flatten::<f32>(colorspace);
+                let predictor = self.predictor(|| {
+                    data = predict_floating_point(&data, channels, width);
+                    Predictor::FloatingPoint
+                });
+
+                self.write_data::<W, $colortype>(encoder, image, &data, predictor, &metadata)
+            }};
+        }
+
+        match (colorspace, image.depth()) {
+            (ColorSpace::Luma, BitDepth::Eight) => page!(Gray8, u8),
+            (ColorSpace::Luma, BitDepth::Sixteen) => page!(Gray16, u16),
+            (ColorSpace::Luma, _) => page!(Gray32Float),
+            (ColorSpace::LumaA, BitDepth::Eight) => page!(GrayAlpha8, u8),
+            (ColorSpace::LumaA, BitDepth::Sixteen) => page!(GrayAlpha16, u16),
+            (ColorSpace::LumaA, _) => page!(GrayAlpha32Float),
+            (ColorSpace::RGB, BitDepth::Eight) => page!(RGB8, u8),
+            (ColorSpace::RGB, BitDepth::Sixteen) => page!(RGB16, u16),
+            (ColorSpace::RGB, _) => page!(RGB32Float),
+            (ColorSpace::RGBA, BitDepth::Eight) => page!(RGBA8, u8),
+            (ColorSpace::RGBA, BitDepth::Sixteen) => page!(RGBA16, u16),
+            (ColorSpace::RGBA, _) => page!(RGBA32Float),
+            (ColorSpace::CMYK, BitDepth::Eight) => page!(CMYK8, u8),
+            (ColorSpace::CMYK, BitDepth::Sixteen) => page!(CMYK16, u16),
+            (ColorSpace::CMYK, _) => page!(CMYK32Float),
+            (cs, _) => Err(ImageErrors::EncodeErrors(
+                ImgEncodeErrors::UnsupportedColorspace(cs, self.supported_colorspaces()),
+            )),
+        }
+    }
+
+    /// Run `predict` if the predictor applies to the selected compression
+    fn predictor(&self, predict: impl FnOnce() -> Predictor) -> Option<Predictor> {
+        match self.options.compression {
+            TiffCompression::Lzw | TiffCompression::Deflate if self.options.predictor => {
+                Some(predict())
+            }
+            _ => None,
+        }
+    }
+
+    fn write_data<W: Write + Seek, C: ColorType>(
+        &self,
+        encoder: &mut Encoder<W>,
+        image: &Image,
+        data: &[C::Inner],
+        predictor: Option<Predictor>,
+        metadata: &PageMetadata,
+    ) -> Result<(), ImageErrors>
+    where
+        [C::Inner]: TiffValue,
+    {
+        let (width, height) = image.dimensions();
+        let (width, height) = (width as u32, height as u32);
+
+        let result = match self.options.compression {
+            TiffCompression::None => {
+                write_image::<W, C, _>(encoder, width, height, Uncompressed, data, None, metadata)
+            }
+            TiffCompression::Lzw => {
+                write_image::<W, C, _>(encoder, width, height, Lzw, data, predictor, metadata)
+            }
+            TiffCompression::Deflate => write_image::<W, C, _>(
+                encoder,
+                width,
+                height,
+                Deflate::default(),
+                data,
+                predictor,
+                metadata,
+            ),
+            TiffCompression::PackBits => {
+                write_image::<W, C, _>(encoder, width, height, Packbits, data, None, metadata)
+            }
+        };
+
+        result.map_err(|e| {
+            ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!(
+                "tiff encoding failed: {e}"
+            )))
+        })
+    }
+}
+
+/// Metadata written into a page
+struct PageMetadata<'a> {
+    image: &'a Image,
+    xmp: Option<&'a [u8]>,
+    /// EXIF and XMP are written only into the first page
+    first: bool,
+}
+
+fn write_image<W: Write + Seek, C: ColorType, D: Compression>(
+    encoder: &mut Encoder<W>,
+    width: u32,
+    height: u32,
+    compression: D,
+    data: &[C::Inner],
+    predictor: Option<Predictor>,
+    metadata: &PageMetadata,
+) -> TiffResult<()>
+where
+    [C::Inner]: TiffValue,
+{
+    let mut image = encoder.new_image_with_compression::<C, D>(width, height, compression)?;
+
+    if let Some(predictor) = predictor {
+        image
+            .encoder()
+            .write_tag(Tag::Predictor, predictor.to_u16())?;
+    }
+
+    if matches!(
+        metadata.image.colorspace(),
+        ColorSpace::LumaA | ColorSpace::RGBA
+    ) {
+        // unassociated alpha
+        image.encoder().write_tag(Tag::ExtraSamples, 2u16)?;
+    }
+
+    if let Some(icc) = metadata.image.metadata().icc_chunk() {
+        image.encoder().write_tag(ICC_PROFILE_TAG, &icc[..])?;
+    }
+
+    if metadata.first {
+        if let Some(xmp) = metadata.xmp {
+            image.encoder().write_tag(XMP_TAG, xmp)?;
+        }
+
+        #[cfg(feature = "metadata")]
+        if let Some(fields) = metadata.image.metadata().exif() {
+            if let Err(e) = write_exif(image.encoder(), fields) {
+                log::warn!("Writing exif failed {:?}", e);
+            }
+        }
+    }
+
+    image.write_data(data)
+}
+
+macro_rules! gray_alpha {
+    ($name:ident, $inner:ty, $bits:expr, $format:ident) => {
+        /// Grayscale with alpha color type, missing in the `tiff` crate
+        struct $name;
+
+        impl ColorType for $name {
+            type Inner = $inner;
+            const TIFF_VALUE: PhotometricInterpretation = PhotometricInterpretation::BlackIsZero;
+            const BITS_PER_SAMPLE: &'static [u16] = &[$bits; 2];
+            const SAMPLE_FORMAT: &'static [SampleFormat] = &[SampleFormat::$format; 2];
+        }
+    };
+}
+
+gray_alpha!(GrayAlpha8, u8, 8, Uint);
+gray_alpha!(GrayAlpha16, u16, 16, Uint);
+gray_alpha!(GrayAlpha32Float, f32, 32, IEEEFP);
+
+/// Write EXIF `fields` of the primary image into the page `directory`
+///
+/// Descriptive fields are baseline TIFF tags and go straight into the directory,
+/// the other fields are written as Exif and GPS sub-directories pointed from it.
+#[cfg(feature = "metadata")]
+fn write_exif<W: Write + Seek>(
+    directory: &mut tiff::encoder::DirectoryEncoder<W, tiff::encoder::TiffKindStandard>,
+    fields: &[exif::Field],
+) -> Result<(), Box<dyn std::error::Error>> {
+    use exif::{experimental::Writer, Context, In, Value};
+
+    const EXIF_POINTER_TAG: u16 = 0x8769;
+    const GPS_POINTER_TAG: u16 = 0x8825;
+
+    let mut writer = Writer::new();
+    let mut sub_directories = false;
+
+    for field in fields.iter().filter(|field| field.ifd_num == In::PRIMARY) {
+        let tag = Tag::Unknown(field.tag.number());
+
+        match (field.tag.context(), &field.value) {
+            (Context::Tiff, Value::Ascii(values)) if DESCRIPTIVE_TAGS.contains(&field.tag) => {
+                if let Some(value) = values.first() {
+                    directory.write_tag(tag, &*String::from_utf8_lossy(value))?;
+                }
+            }
+            (Context::Tiff, Value::Short(values)) if field.tag == exif::Tag::Orientation => {
+                directory.write_tag(tag, &values[..])?;
+            }
+            (Context::Tiff, _) => {}
+            _ => {
+                writer.push_field(field);
+                sub_directories = true;
+            }
+        }
+    }
+
+    if !sub_directories {
+        return Ok(());
+    }
+
+    // IFD offsets are absolute, so the EXIF structure is built at the position it's written to
+    let mut offset = directory.write_data(&[][..] as &[u8])?;
+    if offset % 2 != 0 {
+        offset = directory.write_data(0u8)? + 1;
+    }
+
+    let mut buf = Cursor::new(vec![0; offset as usize]);
+    buf.set_position(offset);
+    writer.write(&mut buf, cfg!(target_endian = "little"))?;
+
+    let data = &buf.get_ref()[offset as usize..];
+    directory.write_data(data)?;
+
+    // the header is followed by the primary directory with pointers to sub-directories
+    let count = u16::from_ne_bytes([data[8], data[9]]) as usize;
+    for entry in data[10..10 + count * 12].chunks_exact(12) {
+        let tag = u16::from_ne_bytes([entry[0], entry[1]]);
+        let value = u32::from_ne_bytes([entry[8], entry[9], entry[10], entry[11]]);
+
+        if tag == EXIF_POINTER_TAG || tag == GPS_POINTER_TAG {
+            directory.write_tag(Tag::Unknown(tag), value)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "metadata")]
+const DESCRIPTIVE_TAGS: &[exif::Tag] = &[
+    exif::Tag::ImageDescription,
+    exif::Tag::Make,
+    exif::Tag::Model,
+    exif::Tag::Software,
+    exif::Tag::DateTime,
+    exif::Tag::Artist,
+    exif::Tag::Copyright,
+];
+
+/// Replace samples in every row with difference to the previous sample of the same channel
+fn predict_horizontal<T: Copy>(
+    data: &mut [T],
+    channels: usize,
+    width: usize,
+    sub: impl Fn(T, T) -> T,
+) {
+    data.chunks_exact_mut(width * channels).for_each(|row| {
+        for i in (channels..row.len()).rev() {
+            row[i] = sub(row[i], row[i - channels]);
+        }
+    });
+}
+
+/// Split float samples in every row into planes of big endian bytes
+/// and apply horizontal differencing to them
+///
+/// The result is reinterpreted back as floats, so the bytes are written into the file as is.
+fn predict_floating_point(data: &[f32], channels: usize, width: usize) -> Vec<f32> {
+    let row_len = width * channels;
+    let mut out = Vec::with_capacity(data.len());
+    let mut bytes = vec![0; row_len * 4];
+
+    for row in data.chunks_exact(row_len) {
+        for (i, sample) in row.iter().enumerate() {
+            for (plane, byte) in sample.to_be_bytes().into_iter().enumerate() {
+                bytes[plane * row_len + i] = byte;
+            }
+        }
+
+        predict_horizontal(
+            &mut bytes,
+            channels,
+            row_len * 4 / channels,
+            u8::wrapping_sub,
+        );
+
+        out.extend(
+            bytes
+                .chunks_exact(4)
+                .map(|b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]])),
+        );
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests;
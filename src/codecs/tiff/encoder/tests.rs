@@ -0,0 +1,107 @@
+use std::io::Cursor;
+
+use zune_core::colorspace::ColorSpace;
+use zune_image::traits::DecoderTrait;
+
+use crate::{codecs::tiff::TiffDecoder, test_utils::*};
+
+use super::*;
+
+#[test]
+fn encode_colorspaces() {
+    let encoder = TiffEncoder::new();
+
+    for colorspace in encoder.supported_colorspaces() {
+        for image in [
+            create_test_image_u8(200, 200, *colorspace),
+            create_test_image_u16(200, 200, *colorspace),
+            create_test_image_f32(200, 200, *colorspace),
+        ] {
+            let mut encoder = TiffEncoder::new();
+
+            let buf = Cursor::new(vec![]);
+
+            let result = encoder.encode(&image, buf);
+
+            if result.is_err() {
+                dbg!(&result);
+            }
+
+            assert!(result.is_ok());
+        }
+    }
+}
+
+#[test]
+fn encode_compressions() {
+    let images = [
+        create_test_image_u8(200, 200, ColorSpace::RGBA),
+        create_test_image_u16(200, 200, ColorSpace::RGB),
+        create_test_image_f32(200, 200, ColorSpace::Luma),
+    ];
+
+    for compression in [
+        TiffCompression::None,
+        TiffCompression::Lzw,
+        TiffCompression::Deflate,
+        TiffCompression::PackBits,
+    ] {
+        for image in &images {
+            let mut encoder = TiffEncoder::new_with_options(TiffOptions {
+                compression,
+                predictor: true,
+                ..Default::default()
+            });
+
+            let mut buf = Cursor::new(vec![]);
+            encoder.encode(image, &mut buf).unwrap();
+            buf.set_position(0);
+
+            let decoded = TiffDecoder::try_new(buf).unwrap().decode().unwrap();
+
+            assert_eq!(decoded.dimensions(), image.dimensions());
+            assert_eq!(decoded.colorspace(), image.colorspace());
+            assert_eq!(decoded.depth(), image.depth());
+            assert_eq!(decoded.flatten_to_u8(), image.flatten_to_u8());
+        }
+    }
+}
+
+#[test]
+fn encode_animated() {
+    let image = create_test_image_animated(200, 200, ColorSpace::RGB);
+    let mut encoder = TiffEncoder::new();
+
+    let mut buf = Cursor::new(vec![]);
+    encoder.encode(&image, &mut buf).unwrap();
+    buf.set_position(0);
+
+    let decoder = TiffDecoder::try_new(buf).unwrap();
+
+    assert_eq!(decoder.page_count(), image.frames_ref().len());
+}
+
+#[test]
+fn encode_metadata() {
+    let icc = std::fs::read("tests/files/icc/tinysrgb.icc").unwrap();
+
+    let mut image = create_test_image_u8(200, 200, ColorSpace::RGB);
+    image.metadata_mut().set_icc_chunk(icc.clone());
+
+    let mut encoder = TiffEncoder::new_with_options(TiffOptions {
+        xmp: Some(b"<x:xmpmeta/>".to_vec()),
+        ..Default::default()
+    });
+
+    let mut buf = Cursor::new(vec![]);
+    encoder.encode(&image, &mut buf).unwrap();
+    buf.set_position(0);
+
+    let mut decoder = TiffDecoder::try_new(buf).unwrap();
+
+    assert_eq!(decoder.xmp(), Some(&b"<x:xmpmeta/>"[..]));
+
+    let decoded = decoder.decode().unwrap();
+
+    assert_eq!(decoded.metadata().icc_chunk(), Some(&icc));
+}
@@ -1,3 +1,10 @@
+use tiff::tags::Tag;
+
 mod decoder;
+mod encoder;
 
 pub use decoder::*;
+pub use encoder::*;
+
+const ICC_PROFILE_TAG: Tag = Tag::Unknown(34675);
+const XMP_TAG: Tag = Tag::Unknown(700);
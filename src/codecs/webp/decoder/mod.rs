@@ -1,12 +1,20 @@
-use std::{io::Read, marker::PhantomData};
+use std::{
+    io::{Read, Seek},
+    marker::PhantomData,
+};
 
-use webp::{AnimDecoder, DecodeAnimImage};
+use webp::AnimDecoder;
 use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
 use zune_image::{errors::ImageErrors, frame::Frame, image::Image, traits::DecoderTrait};
 
+use super::riff::{self, WebPHeader};
+use crate::{codecs::MetadataBlocks, Format, ImageInfo};
+
 /// A WebP decoder
 pub struct WebPDecoder<R: Read> {
-    inner: DecodeAnimImage,
+    inner: Vec<u8>,
+    header: WebPHeader,
+    metadata: MetadataBlocks,
     phantom: PhantomData<R>,
 }
 
@@ -16,14 +24,62 @@ impl<R: Read> WebPDecoder<R> {
         let mut buf = Vec::new();
         source.read_to_end(&mut buf)?;
 
-        let decoder = AnimDecoder::new(&buf);
-        let img = decoder.decode().map_err(ImageErrors::ImageDecodeErrors)?;
+        let header = riff::parse_header(&buf)?;
+
+        let mut metadata = MetadataBlocks::default();
+
+        for chunk in riff::chunks(&buf)? {
+            match &chunk.fourcc {
+                b"ICCP" => metadata.icc = Some(chunk.data.to_vec()),
+                b"EXIF" => metadata.exif = Some(chunk.data.to_vec()),
+                b"XMP " => metadata.xmp = Some(chunk.data.to_vec()),
+                _ => {}
+            }
+        }
 
         Ok(WebPDecoder {
-            inner: img,
+            inner: buf,
+            header,
+            metadata,
             phantom: PhantomData,
         })
     }
+
+    /// Image information read from the headers
+    pub fn info(&self) -> ImageInfo {
+        image_info(&self.header)
+    }
+
+    /// Number of times animation is played, `0` means infinite
+    pub fn loop_count(&self) -> u32 {
+        self.header.loop_count
+    }
+
+    /// Background color of the animation canvas in RGBA
+    pub fn background_color(&self) -> [u8; 4] {
+        self.header.background_color
+    }
+
+    /// Raw XMP packet of the image
+    pub fn xmp(&self) -> Option<&[u8]> {
+        self.metadata.xmp.as_deref()
+    }
+}
+
+/// Read image information of the WebP file without loading its image data
+pub(crate) fn probe<R: Read + Seek>(mut source: R) -> Result<ImageInfo, ImageErrors> {
+    Ok(image_info(&riff::read_header(&mut source)?))
+}
+
+fn image_info(header: &WebPHeader) -> ImageInfo {
+    ImageInfo {
+        format: Format::WebP,
+        dimensions: (header.width, header.height),
+        colorspace: ColorSpace::RGBA,
+        depth: BitDepth::Eight,
+        frame_count: header.frame_count,
+        has_alpha: header.has_alpha,
+    }
 }
 
 impl<R> DecoderTrait for WebPDecoder<R>
@@ -34,37 +90,47 @@ where
         let (width, height) = <WebPDecoder<R> as DecoderTrait>::dimensions(self).unwrap();
         let color = <WebPDecoder<R> as DecoderTrait>::out_colorspace(self);
 
-        let frames = self
-            .inner
+        let img = AnimDecoder::new(&self.inner)
+            .decode()
+            .map_err(ImageErrors::ImageDecodeErrors)?;
+
+        // timestamps mark the end of each frame
+        let mut end = 0;
+
+        let frames = img
             .into_iter()
-            .enumerate()
-            .map(|(idx, frame)| {
-                Frame::from_u8(frame.get_image(), color, idx, frame.get_time_ms() as usize)
+            .map(|frame| {
+                let duration = frame.get_time_ms() - end;
+                end = frame.get_time_ms();
+
+                Frame::from_u8(frame.get_image(), color, duration.max(0) as usize, 1000)
             })
             .collect::<Vec<_>>();
 
-        Ok(Image::new_frames(
-            frames,
-            BitDepth::Eight,
-            width,
-            height,
-            color,
-        ))
+        let mut image = Image::new_frames(frames, BitDepth::Eight, width, height, color);
+
+        if let Some(icc) = &self.metadata.icc {
+            image.metadata_mut().set_icc_chunk(icc.clone());
+        }
+
+        #[cfg(feature = "metadata")]
+        if let Some(exif) = &self.metadata.exif {
+            // some writers keep the jpeg APP1 prefix
+            image
+                .metadata_mut()
+                .parse_raw_exif(exif.strip_prefix(b"Exif\0\0").unwrap_or(exif));
+        }
+
+        Ok(image)
     }
 
     fn dimensions(&self) -> Option<(usize, usize)> {
-        let frame = self.inner.get_frame(0).unwrap();
-
-        Some((frame.width() as usize, frame.height() as usize))
+        Some((self.header.width, self.header.height))
     }
 
     fn out_colorspace(&self) -> ColorSpace {
-        let frame = self.inner.get_frame(0).unwrap();
-
-        match frame.get_layout() {
-            webp::PixelLayout::Rgb => ColorSpace::RGB,
-            webp::PixelLayout::Rgba => ColorSpace::RGBA,
-        }
+        // frames are always decoded with alpha channel
+        ColorSpace::RGBA
     }
 
     fn name(&self) -> &'static str {
@@ -1,4 +1,11 @@
-use std::fs::File;
+use std::{fs::File, io::Cursor};
+
+use zune_image::traits::EncoderTrait;
+
+use crate::codecs::{
+    frame_duration_ms,
+    webp::{WebPEncoder, WebPOptions},
+};
 
 use super::*;
 
@@ -13,3 +20,78 @@ fn decode() {
     assert_eq!(img.dimensions(), (48, 80));
     assert_eq!(img.colorspace(), ColorSpace::RGBA);
 }
+
+#[test]
+fn info_without_decoding() {
+    let file_content = File::open("tests/files/webp/f1t.webp").unwrap();
+
+    let decoder = WebPDecoder::try_new(file_content).unwrap();
+    let info = decoder.info();
+
+    assert_eq!(decoder.dimensions(), Some((48, 80)));
+    assert_eq!(info.frame_count, 1);
+    assert!(!info.has_alpha);
+}
+
+#[test]
+fn decode_animated_timing() {
+    let frames = (1..=3)
+        .map(|i| {
+            Frame::from_u8(
+                &vec![i as u8 * 60; 48 * 80 * 3],
+                ColorSpace::RGB,
+                i * 100,
+                1000,
+            )
+        })
+        .collect();
+    let image = Image::new_frames(frames, BitDepth::Eight, 48, 80, ColorSpace::RGB);
+
+    let mut encoder = WebPEncoder::new_with_options(WebPOptions {
+        loop_count: 3,
+        ..Default::default()
+    });
+
+    let mut buf = Cursor::new(vec![]);
+    encoder.encode(&image, &mut buf).unwrap();
+    buf.set_position(0);
+
+    let decoder = WebPDecoder::try_new(buf).unwrap();
+
+    assert_eq!(decoder.loop_count(), 3);
+
+    let img = Image::from_decoder(decoder).unwrap();
+
+    let durations = img
+        .frames_ref()
+        .iter()
+        .map(frame_duration_ms)
+        .collect::<Vec<_>>();
+
+    assert_eq!(durations, [100, 200, 300]);
+}
+
+#[test]
+fn decode_metadata() {
+    let icc = std::fs::read("tests/files/icc/tinysrgb.icc").unwrap();
+
+    let mut image = Image::from_u8(&[0; 48 * 80 * 3], 48, 80, ColorSpace::RGB);
+    image.metadata_mut().set_icc_chunk(icc.clone());
+
+    let mut encoder = WebPEncoder::new_with_options(WebPOptions {
+        xmp: Some(b"<x:xmpmeta/>".to_vec()),
+        ..Default::default()
+    });
+
+    let mut buf = Cursor::new(vec![]);
+    encoder.encode(&image, &mut buf).unwrap();
+    buf.set_position(0);
+
+    let mut decoder = WebPDecoder::try_new(buf).unwrap();
+
+    assert_eq!(decoder.xmp(), Some(&b"<x:xmpmeta/>"[..]));
+
+    let img = decoder.decode().unwrap();
+
+    assert_eq!(img.metadata().icc_chunk(), Some(&icc));
+}
@@ -10,22 +10,63 @@ use zune_image::{
     traits::EncoderTrait,
 };
 
-/// Alias to [`webp::WebPConfig`]
-pub type WebPOptions = webp::WebPConfig;
+use super::riff;
+use crate::{
+    codecs::{frame_duration_ms, MetadataBlocks},
+    options::{check_range, OptionsError, Validate},
+};
 
-/// A WebP encoder
-pub struct WebPEncoder {
-    options: WebPOptions,
+/// Advanced options for WebP encoding
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct WebPOptions {
+    /// libwebp encoder configuration, see [`webp::WebPConfig`]
+    ///
+    /// Fields missing from a deserialized config keep libwebp defaults.
+    #[cfg_attr(feature = "serde", serde(with = "crate::options::remote::webp_config"))]
+    pub config: webp::WebPConfig,
+    /// Number of times animation is played, `0` means infinite
+    pub loop_count: u32,
+    /// Background color of the animation canvas in RGBA
+    pub background_color: [u8; 4],
+    /// Raw XMP packet written along with ICC profile and EXIF of the image
+    pub xmp: Option<Vec<u8>>,
 }
 
-impl Default for WebPEncoder {
+impl Default for WebPOptions {
     fn default() -> Self {
         Self {
-            options: WebPOptions::new().unwrap(),
+            config: webp::WebPConfig::new().unwrap(),
+            loop_count: 0,
+            background_color: [0; 4],
+            xmp: None,
         }
     }
 }
 
+impl Validate for WebPOptions {
+    fn validate(&self) -> Result<(), OptionsError> {
+        let config = &self.config;
+
+        check_range("quality", config.quality, 0.0..=100.0)?;
+        check_range("method", config.method, 0..=6)?;
+        check_range("alpha_quality", config.alpha_quality, 0..=100)?;
+        check_range("near_lossless", config.near_lossless, 0..=100)?;
+        check_range("segments", config.segments, 1..=4)?;
+        check_range("sns_strength", config.sns_strength, 0..=100)?;
+        check_range("filter_strength", config.filter_strength, 0..=100)
+    }
+}
+
+/// A WebP encoder
+#[derive(Default)]
+pub struct WebPEncoder {
+    options: WebPOptions,
+}
+
 impl WebPEncoder {
     /// Create a new encoder
     pub fn new() -> WebPEncoder {
@@ -36,6 +77,13 @@ impl WebPEncoder {
     pub fn new_with_options(options: WebPOptions) -> WebPEncoder {
         WebPEncoder { options }
     }
+
+    /// Create a new encoder with specified options, rejecting out of range values
+    pub fn try_new_with_options(options: WebPOptions) -> Result<WebPEncoder, OptionsError> {
+        options.validate()?;
+
+        Ok(WebPEncoder::new_with_options(options))
+    }
 }
 
 impl EncoderTrait for WebPEncoder {
@@ -55,37 +103,60 @@ impl EncoderTrait for WebPEncoder {
         if image.is_animated() {
             let frames = image.flatten_to_u8();
 
-            let mut encoder = webp::AnimEncoder::new(width as u32, height as u32, &self.options);
-
-            encoder.set_bgcolor([0, 0, 0, 0]);
-            encoder.set_loop_count(frames.len() as i32);
-
-            frames.iter().try_for_each(|frame| {
-                // TODO: add frame timestamp
-
-                let frame = match image.colorspace() {
-                    ColorSpace::RGB => {
-                        webp::AnimFrame::from_rgb(frame, width as u32, height as u32, 500)
-                    }
-                    ColorSpace::RGBA => {
-                        webp::AnimFrame::from_rgba(frame, width as u32, height as u32, 500)
-                    }
-                    cs => {
-                        return Err(ImageErrors::EncodeErrors(
-                            ImgEncodeErrors::UnsupportedColorspace(
-                                cs,
-                                self.supported_colorspaces(),
-                            ),
-                        ))
-                    }
-                };
-
-                encoder.add_frame(frame);
-
-                Ok(())
-            })?;
-
-            let res = encoder.encode();
+            let mut encoder =
+                webp::AnimEncoder::new(width as u32, height as u32, &self.options.config);
+
+            encoder.set_bgcolor(self.options.background_color);
+            encoder.set_loop_count(self.options.loop_count as i32);
+
+            // frames are added with their start time
+            let mut timestamp = 0;
+
+            frames
+                .iter()
+                .zip(image.frames_ref())
+                .try_for_each(|(data, frame)| {
+                    let duration = frame_duration_ms(frame).max(1);
+
+                    let frame = match image.colorspace() {
+                        ColorSpace::RGB => webp::AnimFrame::from_rgb(
+                            data,
+                            width as u32,
+                            height as u32,
+                            timestamp as i32,
+                        ),
+                        ColorSpace::RGBA => webp::AnimFrame::from_rgba(
+                            data,
+                            width as u32,
+                            height as u32,
+                            timestamp as i32,
+                        ),
+                        cs => {
+                            return Err(ImageErrors::EncodeErrors(
+                                ImgEncodeErrors::UnsupportedColorspace(
+                                    cs,
+                                    self.supported_colorspaces(),
+                                ),
+                            ))
+                        }
+                    };
+
+                    encoder.add_frame(frame);
+                    timestamp += duration;
+
+                    Ok(())
+                })?;
+
+            let mut res = encoder
+                .try_encode()
+                .map_err(|e| {
+                    ImgEncodeErrors::ImageEncodeErrors(format!("webp encoding failed: {e:?}"))
+                })?
+                .to_vec();
+
+            riff::set_last_frame_duration(&mut res, timestamp)?;
+
+            let res = self.with_metadata(image, res)?;
 
             writer.write(&res).map_err(|e| {
                 ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
@@ -105,10 +176,12 @@ impl EncoderTrait for WebPEncoder {
                 }
             };
 
-            let res = encoder.encode_advanced(&self.options).map_err(|e| {
+            let res = encoder.encode_advanced(&self.options.config).map_err(|e| {
                 ImgEncodeErrors::ImageEncodeErrors(format!("webp encoding failed: {e:?}"))
             })?;
 
+            let res = self.with_metadata(image, res.to_vec())?;
+
             writer.write(&res).map_err(|e| {
                 ImageErrors::EncodeErrors(ImgEncodeErrors::ImageEncodeErrors(format!("{e:?}")))
             })?;
@@ -121,7 +194,8 @@ impl EncoderTrait for WebPEncoder {
         &[ColorSpace::RGB, ColorSpace::RGBA]
     }
 
-    // TODO: update when new version with custom image format is released.
+    /// zune-image has no WebP format, [`DynEncoder::format`](crate::codecs::DynEncoder::format)
+    /// reports [`Format::WebP`](crate::Format::WebP)
     fn format(&self) -> ImageFormat {
         ImageFormat::Unknown
     }
@@ -139,5 +213,17 @@ impl EncoderTrait for WebPEncoder {
     }
 }
 
+impl WebPEncoder {
+    fn with_metadata(&self, image: &Image, data: Vec<u8>) -> Result<Vec<u8>, ImageErrors> {
+        let metadata = MetadataBlocks::from_image(image, self.options.xmp.as_deref());
+
+        if metadata.is_empty() {
+            Ok(data)
+        } else {
+            riff::add_metadata(&data, &metadata)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests;
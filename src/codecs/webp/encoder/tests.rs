@@ -156,3 +156,30 @@ fn encode_animated() {
 
     assert!(result.is_ok());
 }
+
+#[test]
+fn encode_metadata() {
+    let icc = std::fs::read("tests/files/icc/tinysrgb.icc").unwrap();
+
+    let mut image = create_test_image_u8(200, 200, ColorSpace::RGB);
+    image.metadata_mut().set_icc_chunk(icc.clone());
+
+    let mut encoder = WebPEncoder::new_with_options(WebPOptions {
+        xmp: Some(b"<x:xmpmeta/>".to_vec()),
+        ..Default::default()
+    });
+
+    let mut buf = Cursor::new(vec![]);
+    encoder.encode(&image, &mut buf).unwrap();
+
+    let data = buf.into_inner();
+    let chunks = super::riff::chunks(&data).unwrap();
+
+    assert_eq!(&chunks[0].fourcc, b"VP8X");
+    assert!(chunks
+        .iter()
+        .any(|chunk| &chunk.fourcc == b"ICCP" && chunk.data == icc));
+    assert!(chunks
+        .iter()
+        .any(|chunk| &chunk.fourcc == b"XMP " && chunk.data == b"<x:xmpmeta/>"));
+}
@@ -0,0 +1,262 @@
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+use zune_image::errors::ImageErrors;
+
+use crate::codecs::MetadataBlocks;
+
+const ICC_FLAG: u8 = 0x20;
+const ALPHA_FLAG: u8 = 0x10;
+const EXIF_FLAG: u8 = 0x08;
+const XMP_FLAG: u8 = 0x04;
+
+/// A chunk of the WebP RIFF container
+pub(crate) struct Chunk<'a> {
+    pub(crate) fourcc: [u8; 4],
+    /// Position of the chunk data in the file
+    pub(crate) offset: usize,
+    pub(crate) data: &'a [u8],
+}
+
+/// Split a WebP file into its top level chunks
+pub(crate) fn chunks(data: &[u8]) -> Result<Vec<Chunk<'_>>, ImageErrors> {
+    if data.len() < 12 || &data[..4] != b"RIFF" || &data[8..12] != b"WEBP" {
+        return Err(ImageErrors::ImageDecodeErrors(
+            "Not a WebP file".to_string(),
+        ));
+    }
+
+    let mut chunks = vec![];
+    let mut offset = 12;
+
+    while let Some(header) = data.get(offset..offset + 8) {
+        let size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+
+        let chunk_data = data
+            .get(offset + 8..offset + 8 + size)
+            .ok_or_else(|| ImageErrors::ImageDecodeErrors("Truncated WebP chunk".to_string()))?;
+
+        chunks.push(Chunk {
+            fourcc: [header[0], header[1], header[2], header[3]],
+            offset: offset + 8,
+            data: chunk_data,
+        });
+
+        // chunks are padded to even size
+        offset += 8 + size + (size & 1);
+    }
+
+    Ok(chunks)
+}
+
+/// Header information of the WebP file
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct WebPHeader {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) has_alpha: bool,
+    pub(crate) frame_count: usize,
+    /// Number of times animation is played, `0` means infinite
+    pub(crate) loop_count: u32,
+    /// Background color of the canvas in RGBA
+    pub(crate) background_color: [u8; 4],
+}
+
+/// Read the canvas size, alpha presence and frame count without decoding the bitstream
+pub(crate) fn parse_header(data: &[u8]) -> Result<WebPHeader, ImageErrors> {
+    read_header(&mut Cursor::new(data))
+}
+
+/// Read the header from `source` chunk by chunk, seeking over the image data
+pub(crate) fn read_header<R: Read + Seek>(source: &mut R) -> Result<WebPHeader, ImageErrors> {
+    let malformed = || ImageErrors::ImageDecodeErrors("Malformed WebP header".to_string());
+
+    let mut riff = [0; 12];
+    if source.read_exact(&mut riff).is_err() || &riff[..4] != b"RIFF" || &riff[8..12] != b"WEBP" {
+        return Err(ImageErrors::ImageDecodeErrors(
+            "Not a WebP file".to_string(),
+        ));
+    }
+
+    let mut dimensions = None;
+    let mut has_alpha = false;
+    let mut frame_count = 0;
+    let mut loop_count = 0;
+    let mut background_color = [0; 4];
+
+    let mut header = [0; 8];
+    while source.read_exact(&mut header).is_ok() {
+        let size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as u64;
+
+        // headers of all chunks fit in the first bytes
+        let mut data = Vec::with_capacity(16);
+        source.by_ref().take(size.min(16)).read_to_end(&mut data)?;
+
+        if data.len() as u64 != size.min(16) {
+            return Err(ImageErrors::ImageDecodeErrors(
+                "Truncated WebP chunk".to_string(),
+            ));
+        }
+
+        match &header[..4] {
+            b"VP8X" => {
+                let data = data.get(..10).ok_or_else(malformed)?;
+
+                has_alpha |= data[0] & ALPHA_FLAG != 0;
+                // canvas size is stored minus one
+                dimensions = Some((u24(&data[4..7]) + 1, u24(&data[7..10]) + 1));
+            }
+            b"VP8 " if dimensions.is_none() => {
+                let data = data.get(..10).ok_or_else(malformed)?;
+
+                if data[3..6] != [0x9D, 0x01, 0x2A] {
+                    return Err(malformed());
+                }
+
+                // upper two bits hold the upscaling factor
+                let width = u16::from_le_bytes([data[6], data[7]]) & 0x3FFF;
+                let height = u16::from_le_bytes([data[8], data[9]]) & 0x3FFF;
+
+                dimensions = Some((width as usize, height as usize));
+            }
+            b"VP8L" if dimensions.is_none() => {
+                let data = data.get(..5).ok_or_else(malformed)?;
+
+                if data[0] != 0x2F {
+                    return Err(malformed());
+                }
+
+                // 14 bits of width and height minus one followed by the alpha hint
+                let bits = u32::from_le_bytes([data[1], data[2], data[3], data[4]]);
+
+                has_alpha |= (bits >> 28) & 1 != 0;
+                dimensions = Some((
+                    (bits & 0x3FFF) as usize + 1,
+                    ((bits >> 14) & 0x3FFF) as usize + 1,
+                ));
+            }
+            b"ANIM" => {
+                let data = data.get(..6).ok_or_else(malformed)?;
+
+                // stored in BGRA order
+                background_color = [data[2], data[1], data[0], data[3]];
+                loop_count = u16::from_le_bytes([data[4], data[5]]) as u32;
+            }
+            b"ALPH" => has_alpha = true,
+            b"ANMF" => frame_count += 1,
+            _ => {}
+        }
+
+        // chunks are padded to even size
+        let rest = size + (size & 1) - data.len() as u64;
+        source.seek(SeekFrom::Current(rest as i64))?;
+    }
+
+    let (width, height) = dimensions.ok_or_else(malformed)?;
+
+    Ok(WebPHeader {
+        width,
+        height,
+        has_alpha,
+        frame_count: frame_count.max(1),
+        loop_count,
+        background_color,
+    })
+}
+
+/// Set duration of the last animation frame so the whole animation lasts `total` milliseconds
+///
+/// libwebp gives the last frame an average duration of the previous frames,
+/// the duration of all other frames is already defined by their timestamps.
+pub(crate) fn set_last_frame_duration(data: &mut [u8], total: u32) -> Result<(), ImageErrors> {
+    let frames = chunks(data)?
+        .into_iter()
+        .filter(|chunk| &chunk.fourcc == b"ANMF" && chunk.data.len() >= 16)
+        .map(|chunk| (chunk.offset, u24(&chunk.data[12..15]) as u32))
+        .collect::<Vec<_>>();
+
+    if let Some(((offset, _), previous)) = frames.split_last() {
+        let elapsed = previous.iter().map(|(_, duration)| duration).sum::<u32>();
+        let duration = total.saturating_sub(elapsed).clamp(1, 0xFF_FFFF);
+
+        data[offset + 12..offset + 15].copy_from_slice(&duration.to_le_bytes()[..3]);
+    }
+
+    Ok(())
+}
+
+/// Rewrite the WebP file into an extended container with `metadata` chunks
+///
+/// Metadata chunks already present in the file are replaced.
+pub(crate) fn add_metadata(data: &[u8], metadata: &MetadataBlocks) -> Result<Vec<u8>, ImageErrors> {
+    let header = parse_header(data)?;
+    let chunks = chunks(data)?;
+
+    let mut vp8x = match chunks.iter().find(|chunk| &chunk.fourcc == b"VP8X") {
+        Some(chunk) => chunk.data.to_vec(),
+        None => {
+            let mut vp8x = vec![0; 10];
+
+            if header.has_alpha {
+                vp8x[0] |= ALPHA_FLAG;
+            }
+            vp8x[4..7].copy_from_slice(&(header.width as u32 - 1).to_le_bytes()[..3]);
+            vp8x[7..10].copy_from_slice(&(header.height as u32 - 1).to_le_bytes()[..3]);
+
+            vp8x
+        }
+    };
+
+    vp8x[0] &= !(ICC_FLAG | EXIF_FLAG | XMP_FLAG);
+
+    let metadata_chunks = [
+        (b"ICCP", ICC_FLAG, &metadata.icc),
+        (b"EXIF", EXIF_FLAG, &metadata.exif),
+        (b"XMP ", XMP_FLAG, &metadata.xmp),
+    ];
+
+    for (_, flag, block) in &metadata_chunks {
+        if block.is_some() {
+            vp8x[0] |= flag;
+        }
+    }
+
+    let mut out = Vec::with_capacity(data.len() + 64);
+    out.extend_from_slice(b"RIFF\0\0\0\0WEBP");
+
+    write_chunk(&mut out, b"VP8X", &vp8x);
+
+    // ICC profile goes before the image data, EXIF and XMP after it
+    if let Some(icc) = &metadata.icc {
+        write_chunk(&mut out, b"ICCP", icc);
+    }
+
+    chunks
+        .iter()
+        .filter(|chunk| !matches!(&chunk.fourcc, b"VP8X" | b"ICCP" | b"EXIF" | b"XMP "))
+        .for_each(|chunk| write_chunk(&mut out, &chunk.fourcc, chunk.data));
+
+    for (fourcc, _, block) in &metadata_chunks[1..] {
+        if let Some(block) = block {
+            write_chunk(&mut out, fourcc, block);
+        }
+    }
+
+    let riff_size = (out.len() - 8) as u32;
+    out[4..8].copy_from_slice(&riff_size.to_le_bytes());
+
+    Ok(out)
+}
+
+fn write_chunk(out: &mut Vec<u8>, fourcc: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(fourcc);
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(data);
+
+    if data.len() % 2 == 1 {
+        out.push(0);
+    }
+}
+
+fn u24(bytes: &[u8]) -> usize {
+    bytes[0] as usize | (bytes[1] as usize) << 8 | (bytes[2] as usize) << 16
+}
@@ -15,12 +15,17 @@ Rimage is a powerful Rust image optimization library extending `zune_image` crat
 
 ## Formats
 
-| Image Format | Decoder | Encoder |
-|--------------|---------|---------|
-| jpeg         | -       | mozjpeg |
-| png          | -       | oxipng  |
-| avif         | libavif | ravif   |
-| webp         | webp    | webp    |
+| Image Format | Decoder   | Encoder   |
+|--------------|-----------|-----------|
+| jpeg         | mozjpeg   | mozjpeg   |
+| png          | -         | oxipng    |
+| avif         | libavif   | ravif     |
+| webp         | webp      | webp      |
+| gif          | gif       | gif       |
+| jpeg xl      | -         | libjxl    |
+| ico          | -         | ico       |
+| raw          | rawloader | -         |
+| dds / ktx2   | -         | intel_tex |
 
 ## Usage
 
@@ -43,6 +48,37 @@ let img = Image::from_decoder(decoder).unwrap();
 ```
 > `zune_image` currently doesn't support custom decoders for `read` method. So for decoding you will need hacky approach to satisfy the compiler.
 
+ICC profile and EXIF are kept in the image metadata. `zune_image` has no place for XMP,
+so decoders expose the raw packet with their `xmp` method and encoders take it as the `xmp` option.
+
+### Decoding any format
+
+Format is detected from the file content, every format enabled with features can be decoded
+
+```
+# let path = "tests/files/png/f1t.png";
+let img = rimage::open(path).unwrap();
+
+assert_eq!(img.dimensions(), (48, 80));
+```
+
+Compiled-in codecs are listed by [`codecs::registry::decoders`] and [`codecs::registry::encoders`].
+
+### Probing
+
+Dimensions, colorspace, bit depth, frame count and alpha presence can be read from headers without decoding pixels
+
+```
+use std::fs::File;
+# let path = "tests/files/png/f1t.png";
+
+let file = File::open(path).unwrap();
+
+let info = rimage::probe(file).unwrap();
+
+assert_eq!(info.dimensions, (48, 80));
+```
+
 ### Encoders
 
 With default options
@@ -80,6 +116,40 @@ encoder.encode(&img, &mut result).unwrap();
 ```
 > Note that some codecs have own implementation of options, check their documentation to learn more
 
+Chosen at runtime by name
+
+```
+use rimage::codecs::dynamic::encoder;
+# use zune_image::image::Image;
+# let img = Image::open("tests/files/jpg/f1t.jpg").unwrap();
+
+let mut encoder = encoder("mozjpeg").unwrap();
+
+let mut result: Vec<u8> = vec![];
+
+encoder.encode(&img, &mut result).unwrap();
+
+assert_eq!(encoder.mime_type(), "image/jpeg");
+```
+> Options of such encoders are set with [`codecs::EncoderConfig`]
+
+### Validated options
+
+`try_*` constructors check option ranges and return [`options::OptionsError`].
+With `serde` feature enabled options can be read from configs and checked before any image is processed
+
+```
+# #[cfg(all(feature = "serde", feature = "mozjpeg"))]
+# {
+use rimage::{codecs::EncoderConfig, options::Validate};
+
+let config: EncoderConfig =
+    serde_json::from_str(r#"{ "encoder": "mozjpeg", "options": { "quality": 120 } }"#).unwrap();
+
+assert!(config.validate().is_err());
+# }
+```
+
 ### Operations
 
 Resize
@@ -120,5 +190,13 @@ pub mod operations;
 /// All additional codecs for the zune_image
 pub mod codecs;
 
+/// Validation and serialization of encoder and operation options
+pub mod options;
+
+mod probe;
+
+pub use codecs::registry::{decode, open, Format};
+pub use probe::{probe, ImageInfo};
+
 #[cfg(test)]
 mod test_utils;
@@ -1,5 +1,6 @@
 use std::{
     fs::{self, File},
+    io::Write,
     path::PathBuf,
     sync::{Arc, Mutex},
     time::Duration,
@@ -7,7 +8,7 @@ use std::{
 
 use cli::{
     cli,
-    pipeline::{decode, operations},
+    pipeline::{build_pipeline, decode},
     utils::paths::{collect_files, get_paths},
 };
 use console::{style, Term};
@@ -17,19 +18,16 @@ use indicatif::{
 };
 use indicatif_log_bridge::LogWrapper;
 use rayon::prelude::*;
-use rimage::operations::icc::ApplySRGB;
-use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
-use zune_image::{
-    core_filters::{colorspace::ColorspaceConv, depth::Depth},
-    image::Image,
-    pipelines::Pipeline,
-};
-use zune_imageprocs::auto_orient::AutoOrient;
+use rimage::codecs::registry::SourceInfo;
 
 use crate::cli::pipeline::encoder;
 
 mod cli;
 
+// libjpeg errors unwind through the codec, aborting would end the whole batch
+#[cfg(all(feature = "mozjpeg", panic = "abort"))]
+compile_error!("mozjpeg errors are recovered by unwinding, build with `panic = \"unwind\"`");
+
 macro_rules! handle_error {
     ( $path:expr, $e:expr ) => {
         match $e {
@@ -128,49 +126,51 @@ fn main() {
                     pb.set_message(format!("{}", input.display()));
                     pb.enable_steady_tick(Duration::from_millis(100));
 
-                    let mut pipeline = Pipeline::<Image>::new();
-
                     let input_size = handle_error!(input, input.metadata()).len();
 
-                    let img = handle_error!(input, decode(&input));
-
                     pb.set_style(sty_aux_operations.clone());
 
-                    let mut available_encoder = handle_error!(input, encoder(subcommand, matches));
+                    let mut available_encoder =
+                        handle_error!(input, encoder(subcommand, matches, &SourceInfo::default()));
                     if let Some(ext) = output.extension() {
                         output.set_extension({
                             let mut os_str = ext.to_os_string();
                             os_str.push(".");
-                            os_str.push(available_encoder.to_extension());
+                            os_str.push(available_encoder.extension());
                             os_str
                         });
                     } else {
-                        output.set_extension(available_encoder.to_extension());
+                        output.set_extension(available_encoder.extension());
                     }
 
-                    pipeline.chain_operations(Box::new(Depth::new(BitDepth::Eight)));
-                    pipeline.chain_operations(Box::new(ColorspaceConv::new(ColorSpace::RGBA)));
-
-                    pipeline.chain_operations(Box::new(AutoOrient));
-                    pipeline.chain_operations(Box::new(ApplySRGB));
-
-                    operations(matches, &img)
-                        .into_iter()
-                        .for_each(|(_, operations)| match operations.name() {
-                            "quantize" => {
-                                pipeline.chain_operations(Box::new(ColorspaceConv::new(
-                                    ColorSpace::RGBA,
-                                )));
-                                pipeline.chain_operations(operations);
-                            }
-                            _ => {
-                                pipeline.chain_operations(operations);
-                            }
-                        });
+                    let data = handle_error!(input, fs::read(&input));
+
+                    // lossless modes work on the encoded data without the pipeline
+                    let transcoded = if available_encoder.can_transcode(&data) {
+                        match available_encoder.transcode(&data) {
+                            Some(result) => Some(handle_error!(input, result)),
+                            None => None,
+                        }
+                    } else {
+                        None
+                    };
+
+                    let image = if transcoded.is_none() {
+                        let decoded = handle_error!(input, decode(&input, data, matches));
 
-                    pipeline.chain_decoder(img);
+                        // loop count and other properties of the source are known after decoding
+                        available_encoder =
+                            handle_error!(input, encoder(subcommand, matches, &decoded.source));
 
-                    handle_error!(input, pipeline.advance_to_end());
+                        let pipeline = build_pipeline(matches, decoded, available_encoder.as_ref());
+
+                        Some(handle_error!(
+                            input,
+                            pipeline.run(available_encoder.as_mut())
+                        ))
+                    } else {
+                        None
+                    };
 
                     pb.set_style(sty_aux_encode.clone());
 
@@ -189,12 +189,18 @@ fn main() {
                     }
 
                     handle_error!(output, fs::create_dir_all(output.parent().unwrap()));
-                    let output_file = handle_error!(output, File::create(&output));
-
-                    handle_error!(
-                        output,
-                        available_encoder.encode(&pipeline.images()[0], output_file)
-                    );
+                    let mut output_file = handle_error!(output, File::create(&output));
+
+                    match (transcoded, image) {
+                        (Some(data), _) => handle_error!(output, output_file.write_all(&data)),
+                        (None, Some(image)) => {
+                            handle_error!(
+                                output,
+                                available_encoder.encode(&image, &mut output_file)
+                            );
+                        }
+                        (None, None) => unreachable!(),
+                    }
 
                     let output_size = handle_error!(output, output.metadata()).len();
 
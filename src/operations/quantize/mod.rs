@@ -1,13 +1,24 @@
 use imagequant::Histogram;
 use rgb::FromSlice;
-use zune_core::{bit_depth::BitType, colorspace::ColorSpace};
+use zune_core::{
+    bit_depth::{BitDepth, BitType},
+    colorspace::ColorSpace,
+};
 use zune_image::{
     channel::Channel,
     errors::{ImageErrors, ImageOperationsErrors},
+    image::Image,
     traits::OperationsTrait,
 };
 
+use crate::{
+    codecs::IndexedPixels,
+    options::{check_range, OptionsError, Validate},
+};
+
 /// Reduce image palette
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Quantize {
     quality: u8,
     dithering: Option<f32>,
@@ -23,14 +34,28 @@ impl Quantize {
     pub fn new(quality: u8, dithering: Option<f32>) -> Self {
         Self { quality, dithering }
     }
-}
 
-impl OperationsTrait for Quantize {
-    fn name(&self) -> &'static str {
-        "quantize"
+    /// Create a new quantization operation, rejecting quality above `100`
+    /// and dithering out of `0.0..=1.0`
+    pub fn try_new(quality: u8, dithering: Option<f32>) -> Result<Self, OptionsError> {
+        let quantize = Self::new(quality, dithering);
+        quantize.validate()?;
+
+        Ok(quantize)
     }
 
-    fn execute_impl(&self, image: &mut zune_image::image::Image) -> Result<(), ImageErrors> {
+    /// Quantize the `image` in place and return its pixels as indices into the palette
+    ///
+    /// Encoders supporting indexed output, like `OxiPngEncoder`, can write
+    /// the palette as is instead of rediscovering it from pixels.
+    /// Returns `None` for indexed pixels if frames ended up with different palettes.
+    pub fn quantize(&self, image: &mut Image) -> Result<Option<IndexedPixels>, ImageErrors> {
+        if image.depth() != BitDepth::Eight || image.colorspace() != ColorSpace::RGBA {
+            return Err(ImageErrors::OperationsError(
+                ImageOperationsErrors::Generic("Quantization supports only 8-bit RGBA images"),
+            ));
+        }
+
         let (src_width, src_height) = image.dimensions();
         let channel_len = src_width * src_height * image.depth().size_of();
 
@@ -71,6 +96,10 @@ impl OperationsTrait for Quantize {
                 .map_err(|e| ImageOperationsErrors::GenericString(e.to_string()))?;
         }
 
+        let mut shared_palette: Option<Vec<[u8; 4]>> = None;
+        let mut palette_consistent = true;
+        let mut indices = Vec::with_capacity(frames.len());
+
         frames
             .iter_mut()
             .zip(image.frames_mut())
@@ -79,6 +108,17 @@ impl OperationsTrait for Quantize {
                     .remapped(img)
                     .map_err(|e| ImageOperationsErrors::GenericString(e.to_string()))?;
 
+                let colors = palette
+                    .iter()
+                    .map(|px| [px.r, px.g, px.b, px.a])
+                    .collect::<Vec<_>>();
+
+                // all frames share the histogram, but keep only a palette valid for every frame
+                match &shared_palette {
+                    Some(shared) => palette_consistent &= *shared == colors,
+                    None => shared_palette = Some(colors),
+                }
+
                 let channels = pixels
                     .iter()
                     .map(|px| {
@@ -101,12 +141,40 @@ impl OperationsTrait for Quantize {
                     );
 
                 frame.set_channels(channels);
+                indices.push(pixels);
 
                 Ok::<(), ImageErrors>(())
             })?;
 
+        Ok(shared_palette
+            .filter(|_| palette_consistent)
+            .map(|palette| IndexedPixels {
+                palette,
+                frames: indices,
+            }))
+    }
+}
+
+impl Validate for Quantize {
+    fn validate(&self) -> Result<(), OptionsError> {
+        check_range("quality", self.quality, 0..=100)?;
+
+        if let Some(dithering) = self.dithering {
+            check_range("dithering", dithering, 0.0..=1.0)?;
+        }
+
         Ok(())
     }
+}
+
+impl OperationsTrait for Quantize {
+    fn name(&self) -> &'static str {
+        "quantize"
+    }
+
+    fn execute_impl(&self, image: &mut Image) -> Result<(), ImageErrors> {
+        self.quantize(image).map(|_| ())
+    }
 
     fn supported_types(&self) -> &'static [BitType] {
         &[BitType::U8]
@@ -25,3 +25,31 @@ fn dither_u8() {
 
     assert!(result.is_ok());
 }
+
+#[test]
+fn quantize_indexed() {
+    let quantize = Quantize::new(75, None);
+    let mut image = create_test_image_u8(200, 200, ColorSpace::RGBA);
+
+    let indexed = quantize.quantize(&mut image).unwrap().unwrap();
+    let palette = &indexed.palette;
+
+    assert!(!palette.is_empty() && palette.len() <= 256);
+    assert_eq!(indexed.frames.len(), 1);
+
+    // indices describe the quantized pixels
+    image
+        .flatten_to_u8()
+        .iter()
+        .flat_map(|frame| frame.chunks_exact(4))
+        .zip(&indexed.frames[0])
+        .for_each(|(px, &index)| assert_eq!(px, palette[index as usize]));
+}
+
+#[test]
+fn quantize_sixteen_bit() {
+    let quantize = Quantize::new(75, None);
+    let mut image = create_test_image_u16(20, 20, ColorSpace::RGBA);
+
+    assert!(quantize.quantize(&mut image).is_err());
+}
@@ -10,10 +10,15 @@ use zune_image::{
     traits::OperationsTrait,
 };
 
+use crate::options::{OptionsError, Validate};
+
 /// Resize an image to a new dimensions
 /// using the resize algorithm specified
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Resize {
+    #[cfg_attr(feature = "serde", serde(rename = "dimensions"))]
     new_dimensions: (usize, usize),
+    #[cfg_attr(feature = "serde", serde(with = "crate::options::remote::resize_alg"))]
     algorithm: fr::ResizeAlg,
 }
 
@@ -31,6 +36,30 @@ impl Resize {
             algorithm,
         }
     }
+
+    /// Create a new resize operation, rejecting zero width or height
+    pub fn try_new(
+        width: usize,
+        height: usize,
+        algorithm: fr::ResizeAlg,
+    ) -> Result<Self, OptionsError> {
+        let resize = Self::new(width, height, algorithm);
+        resize.validate()?;
+
+        Ok(resize)
+    }
+}
+
+impl Validate for Resize {
+    fn validate(&self) -> Result<(), OptionsError> {
+        match self.new_dimensions {
+            (0, _) | (_, 0) => Err(OptionsError::Invalid {
+                option: "dimensions",
+                reason: "must not be 0",
+            }),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl OperationsTrait for Resize {
@@ -0,0 +1,94 @@
+use std::{error::Error, fmt};
+
+use zune_image::errors::ImageErrors;
+
+#[cfg(feature = "serde")]
+pub(crate) mod remote;
+
+/// Invalid value of an encoder or operation option
+#[derive(Debug, Clone, PartialEq)]
+pub enum OptionsError {
+    /// Numeric option lies outside of its range
+    OutOfRange {
+        /// Name of the option
+        option: &'static str,
+        /// Given value
+        value: f64,
+        /// Smallest accepted value
+        min: f64,
+        /// Largest accepted value
+        max: f64,
+    },
+    /// Option has a value which can't be used
+    Invalid {
+        /// Name of the option
+        option: &'static str,
+        /// Why the value is rejected
+        reason: &'static str,
+    },
+}
+
+impl fmt::Display for OptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionsError::OutOfRange {
+                option,
+                value,
+                min,
+                max,
+            } => write!(f, "{option} is {value}, expected {min}..={max}"),
+            OptionsError::Invalid { option, reason } => write!(f, "{option} {reason}"),
+        }
+    }
+}
+
+impl Error for OptionsError {}
+
+impl From<OptionsError> for ImageErrors {
+    fn from(e: OptionsError) -> Self {
+        ImageErrors::GenericString(e.to_string())
+    }
+}
+
+/// Range check of options, done by `try_*` constructors
+///
+/// Options deserialized from a config should be validated before they are used,
+/// so invalid values are reported before any image is processed.
+pub trait Validate {
+    /// Check every option, the first invalid one is returned
+    fn validate(&self) -> Result<(), OptionsError>;
+}
+
+/// Check that `value` of the `option` lies in the `range`, NaN is never in range
+#[cfg(any(
+    feature = "avif",
+    feature = "gif",
+    feature = "ico",
+    feature = "jpegxl",
+    feature = "mozjpeg",
+    feature = "oxipng",
+    feature = "quantization",
+    feature = "webp"
+))]
+pub(crate) fn check_range<T>(
+    option: &'static str,
+    value: T,
+    range: std::ops::RangeInclusive<T>,
+) -> Result<(), OptionsError>
+where
+    T: PartialOrd + Into<f64> + Copy,
+{
+    if range.contains(&value) {
+        return Ok(());
+    }
+
+    Err(OptionsError::OutOfRange {
+        option,
+        value: value.into(),
+        min: (*range.start()).into(),
+        max: (*range.end()).into(),
+    })
+}
+
+#[cfg(test)]
+mod tests;
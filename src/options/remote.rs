@@ -0,0 +1,324 @@
+//! Serialization of option types defined by codec crates
+//!
+//! Every module is used with `#[serde(with = "...")]` on the field of a foreign type.
+//! Values are mirrored by local types, enums are written in snake case.
+
+/// [`mozjpeg::ColorSpace`] of the written JPEG
+#[cfg(feature = "mozjpeg")]
+pub(crate) mod jpeg_color_space {
+    use mozjpeg::ColorSpace;
+    use serde::{ser, Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    enum Mirror {
+        Ycbcr,
+        Rgb,
+        Grayscale,
+        Cmyk,
+        Ycck,
+    }
+
+    pub(crate) fn serialize<S: Serializer>(
+        value: &ColorSpace,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mirror = match value {
+            ColorSpace::JCS_YCbCr => Mirror::Ycbcr,
+            ColorSpace::JCS_RGB => Mirror::Rgb,
+            ColorSpace::JCS_GRAYSCALE => Mirror::Grayscale,
+            ColorSpace::JCS_CMYK => Mirror::Cmyk,
+            ColorSpace::JCS_YCCK => Mirror::Ycck,
+            _ => return Err(ser::Error::custom("unsupported JPEG color space")),
+        };
+
+        mirror.serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<ColorSpace, D::Error> {
+        Ok(match Mirror::deserialize(deserializer)? {
+            Mirror::Ycbcr => ColorSpace::JCS_YCbCr,
+            Mirror::Rgb => ColorSpace::JCS_RGB,
+            Mirror::Grayscale => ColorSpace::JCS_GRAYSCALE,
+            Mirror::Cmyk => ColorSpace::JCS_CMYK,
+            Mirror::Ycck => ColorSpace::JCS_YCCK,
+        })
+    }
+}
+
+/// [`mozjpeg::ScanMode`] of DC coefficients
+#[cfg(feature = "mozjpeg")]
+pub(crate) mod jpeg_scan_mode {
+    use mozjpeg::ScanMode;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    enum Mirror {
+        Together,
+        PerComponent,
+        Auto,
+    }
+
+    pub(crate) fn serialize<S: Serializer>(
+        value: &ScanMode,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            ScanMode::AllComponentsTogether => Mirror::Together,
+            ScanMode::ScanPerComponent => Mirror::PerComponent,
+            ScanMode::Auto => Mirror::Auto,
+        }
+        .serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<ScanMode, D::Error> {
+        Ok(match Mirror::deserialize(deserializer)? {
+            Mirror::Together => ScanMode::AllComponentsTogether,
+            Mirror::PerComponent => ScanMode::ScanPerComponent,
+            Mirror::Auto => ScanMode::Auto,
+        })
+    }
+}
+
+/// [`ravif::ColorSpace`] stored in the AVIF file
+#[cfg(feature = "avif")]
+pub(crate) mod avif_color_space {
+    use ravif::ColorSpace;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    enum Mirror {
+        Ycbcr,
+        Rgb,
+    }
+
+    pub(crate) fn serialize<S: Serializer>(
+        value: &ColorSpace,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            ColorSpace::YCbCr => Mirror::Ycbcr,
+            ColorSpace::RGB => Mirror::Rgb,
+        }
+        .serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<ColorSpace, D::Error> {
+        Ok(match Mirror::deserialize(deserializer)? {
+            Mirror::Ycbcr => ColorSpace::YCbCr,
+            Mirror::Rgb => ColorSpace::RGB,
+        })
+    }
+}
+
+/// [`ravif::AlphaColorMode`] of transparent images
+#[cfg(feature = "avif")]
+pub(crate) mod avif_alpha_mode {
+    use ravif::AlphaColorMode;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    enum Mirror {
+        UnassociatedDirty,
+        UnassociatedClean,
+        Premultiplied,
+    }
+
+    pub(crate) fn serialize<S: Serializer>(
+        value: &AlphaColorMode,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            AlphaColorMode::UnassociatedDirty => Mirror::UnassociatedDirty,
+            AlphaColorMode::UnassociatedClean => Mirror::UnassociatedClean,
+            AlphaColorMode::Premultiplied => Mirror::Premultiplied,
+        }
+        .serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<AlphaColorMode, D::Error> {
+        Ok(match Mirror::deserialize(deserializer)? {
+            Mirror::UnassociatedDirty => AlphaColorMode::UnassociatedDirty,
+            Mirror::UnassociatedClean => AlphaColorMode::UnassociatedClean,
+            Mirror::Premultiplied => AlphaColorMode::Premultiplied,
+        })
+    }
+}
+
+/// [`fast_image_resize::ResizeAlg`], filters are written as `{"convolution": "lanczos3"}`
+#[cfg(feature = "resize")]
+pub(crate) mod resize_alg {
+    use fast_image_resize::{FilterType, ResizeAlg};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    enum Filter {
+        Box,
+        Bilinear,
+        Hamming,
+        CatmullRom,
+        Mitchell,
+        Lanczos3,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    enum Mirror {
+        Nearest,
+        Convolution(Filter),
+        SuperSampling(Filter, u8),
+    }
+
+    impl From<FilterType> for Filter {
+        fn from(filter: FilterType) -> Self {
+            match filter {
+                FilterType::Box => Filter::Box,
+                FilterType::Bilinear => Filter::Bilinear,
+                FilterType::Hamming => Filter::Hamming,
+                FilterType::CatmullRom => Filter::CatmullRom,
+                FilterType::Mitchell => Filter::Mitchell,
+                FilterType::Lanczos3 => Filter::Lanczos3,
+            }
+        }
+    }
+
+    impl From<Filter> for FilterType {
+        fn from(filter: Filter) -> Self {
+            match filter {
+                Filter::Box => FilterType::Box,
+                Filter::Bilinear => FilterType::Bilinear,
+                Filter::Hamming => FilterType::Hamming,
+                Filter::CatmullRom => FilterType::CatmullRom,
+                Filter::Mitchell => FilterType::Mitchell,
+                Filter::Lanczos3 => FilterType::Lanczos3,
+            }
+        }
+    }
+
+    pub(crate) fn serialize<S: Serializer>(
+        value: &ResizeAlg,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match *value {
+            ResizeAlg::Nearest => Mirror::Nearest,
+            ResizeAlg::Convolution(filter) => Mirror::Convolution(filter.into()),
+            ResizeAlg::SuperSampling(filter, multiplicity) => {
+                Mirror::SuperSampling(filter.into(), multiplicity)
+            }
+        }
+        .serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<ResizeAlg, D::Error> {
+        Ok(match Mirror::deserialize(deserializer)? {
+            Mirror::Nearest => ResizeAlg::Nearest,
+            Mirror::Convolution(filter) => ResizeAlg::Convolution(filter.into()),
+            Mirror::SuperSampling(filter, multiplicity) => {
+                ResizeAlg::SuperSampling(filter.into(), multiplicity)
+            }
+        })
+    }
+}
+
+/// [`webp::WebPConfig`], fields missing from the input keep libwebp defaults
+///
+/// `image_hint` and `show_compressed` are not written.
+#[cfg(feature = "webp")]
+pub(crate) mod webp_config {
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+    use webp::WebPConfig;
+
+    macro_rules! mirror {
+        ($($(#[$attr:meta])* $field:ident: $ty:ty,)*) => {
+            #[allow(non_snake_case)]
+            #[derive(Serialize, Deserialize)]
+            #[serde(default)]
+            struct Mirror {
+                $($(#[$attr])* $field: $ty,)*
+            }
+
+            impl From<&WebPConfig> for Mirror {
+                fn from(config: &WebPConfig) -> Self {
+                    Self { $($field: config.$field,)* }
+                }
+            }
+
+            impl Mirror {
+                fn apply(self, config: &mut WebPConfig) {
+                    $(config.$field = self.$field;)*
+                }
+            }
+        };
+    }
+
+    mirror! {
+        lossless: i32,
+        quality: f32,
+        method: i32,
+        target_size: i32,
+        #[serde(rename = "target_psnr")]
+        target_PSNR: f32,
+        segments: i32,
+        sns_strength: i32,
+        filter_strength: i32,
+        filter_sharpness: i32,
+        filter_type: i32,
+        autofilter: i32,
+        alpha_compression: i32,
+        alpha_filtering: i32,
+        alpha_quality: i32,
+        pass: i32,
+        preprocessing: i32,
+        partitions: i32,
+        partition_limit: i32,
+        emulate_jpeg_size: i32,
+        thread_level: i32,
+        low_memory: i32,
+        near_lossless: i32,
+        exact: i32,
+        use_delta_palette: i32,
+        use_sharp_yuv: i32,
+        qmin: i32,
+        qmax: i32,
+    }
+
+    impl Default for Mirror {
+        fn default() -> Self {
+            Self::from(&WebPConfig::new().unwrap())
+        }
+    }
+
+    pub(crate) fn serialize<S: Serializer>(
+        value: &WebPConfig,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        Mirror::from(value).serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<WebPConfig, D::Error> {
+        let mirror = Mirror::deserialize(deserializer)?;
+
+        let mut config =
+            WebPConfig::new().map_err(|_| de::Error::custom("libwebp version mismatch"))?;
+
+        mirror.apply(&mut config);
+
+        Ok(config)
+    }
+}
@@ -0,0 +1,226 @@
+use crate::codecs::{dynamic::ENCODER_NAMES, EncoderConfig};
+
+use super::*;
+
+#[test]
+#[cfg(any(
+    feature = "avif",
+    feature = "gif",
+    feature = "ico",
+    feature = "jpegxl",
+    feature = "mozjpeg",
+    feature = "oxipng",
+    feature = "quantization",
+    feature = "webp"
+))]
+fn range() {
+    assert!(check_range("quality", 1u8, 1..=100).is_ok());
+    assert!(check_range("quality", 100u8, 1..=100).is_ok());
+
+    assert_eq!(
+        check_range("quality", 101u8, 1..=100),
+        Err(OptionsError::OutOfRange {
+            option: "quality",
+            value: 101.,
+            min: 1.,
+            max: 100.,
+        })
+    );
+
+    assert!(check_range("distance", f32::NAN, 0.0..=25.0).is_err());
+}
+
+#[test]
+#[cfg(any(
+    feature = "avif",
+    feature = "gif",
+    feature = "ico",
+    feature = "jpegxl",
+    feature = "mozjpeg",
+    feature = "oxipng",
+    feature = "quantization",
+    feature = "webp"
+))]
+fn error_message() {
+    let error = check_range("effort", 11u8, 1..=10).unwrap_err();
+
+    assert_eq!(error.to_string(), "effort is 11, expected 1..=10");
+}
+
+#[test]
+fn defaults_are_valid() {
+    for &name in ENCODER_NAMES {
+        let config = EncoderConfig::from_name(name).unwrap();
+
+        assert!(config.validate().is_ok(), "{name}");
+    }
+}
+
+#[test]
+#[cfg(feature = "mozjpeg")]
+fn mozjpeg_quality() {
+    use crate::codecs::mozjpeg::{MozJpegEncoder, MozJpegOptions};
+
+    for quality in [0., 100.5, f32::NAN] {
+        let options = MozJpegOptions {
+            quality,
+            ..Default::default()
+        };
+
+        assert!(MozJpegEncoder::try_new_with_options(options).is_err());
+    }
+
+    let options = MozJpegOptions {
+        chroma_subsample: Some((2, 5)),
+        ..Default::default()
+    };
+
+    assert!(MozJpegEncoder::try_new_with_options(options).is_err());
+
+    let options = MozJpegOptions {
+        color_space: mozjpeg::ColorSpace::JCS_EXT_RGBA,
+        ..Default::default()
+    };
+
+    assert!(MozJpegEncoder::try_new_with_options(options).is_err());
+    assert!(MozJpegEncoder::try_new_with_options(MozJpegOptions::default()).is_ok());
+}
+
+#[test]
+#[cfg(feature = "ico")]
+fn ico_sizes() {
+    use crate::codecs::ico::{IcoEncoder, IcoOptions};
+
+    for sizes in [vec![], vec![16, 512]] {
+        let options = IcoOptions {
+            sizes,
+            ..Default::default()
+        };
+
+        assert!(IcoEncoder::try_new_with_options(options).is_err());
+    }
+}
+
+#[test]
+#[cfg(feature = "oxipng")]
+fn invalid_config() {
+    let config = EncoderConfig::OxiPng(crate::codecs::oxipng::OxiPngOptions::from_preset(7));
+
+    assert!(config.try_into_encoder().is_err());
+}
+
+#[test]
+#[cfg(all(feature = "resize", feature = "quantization"))]
+fn operations() {
+    use crate::operations::{
+        quantize::Quantize,
+        resize::{Resize, ResizeAlg},
+    };
+
+    assert!(Resize::try_new(0, 10, ResizeAlg::Nearest).is_err());
+    assert!(Resize::try_new(10, 10, ResizeAlg::Nearest).is_ok());
+
+    assert!(Quantize::try_new(101, None).is_err());
+    assert!(Quantize::try_new(75, Some(1.5)).is_err());
+    assert!(Quantize::try_new(75, Some(0.5)).is_ok());
+}
+
+#[cfg(feature = "serde")]
+mod serialize {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for &name in ENCODER_NAMES {
+            let config = EncoderConfig::from_name(name).unwrap();
+
+            let json = serde_json::to_string(&config).unwrap();
+            let config: EncoderConfig = serde_json::from_str(&json).unwrap();
+
+            assert_eq!(config.name(), name, "{json}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "mozjpeg")]
+    fn partial_mozjpeg() {
+        let config: EncoderConfig = serde_json::from_str(
+            r#"{
+                "encoder": "mozjpeg",
+                "options": {
+                    "quality": 80,
+                    "color_space": "rgb",
+                    "dc_scan_mode": "per_component",
+                    "restart_interval": { "blocks": 16 }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let EncoderConfig::MozJpeg(options) = &config else {
+            panic!("expected mozjpeg options");
+        };
+
+        assert_eq!(options.quality, 80.);
+        assert!(matches!(options.color_space, mozjpeg::ColorSpace::JCS_RGB));
+        assert!(matches!(
+            options.dc_scan_mode,
+            mozjpeg::ScanMode::ScanPerComponent
+        ));
+        assert_eq!(
+            options.restart_interval,
+            Some(crate::codecs::mozjpeg::RestartInterval::Blocks(16))
+        );
+        // missing fields keep defaults
+        assert!(options.progressive);
+
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "webp")]
+    fn partial_webp() {
+        let config: EncoderConfig = serde_json::from_str(
+            r#"{ "encoder": "webp", "options": { "config": { "quality": 150 } } }"#,
+        )
+        .unwrap();
+
+        let EncoderConfig::WebP(options) = &config else {
+            panic!("expected webp options");
+        };
+
+        assert_eq!(options.config.quality, 150.);
+        assert_eq!(options.config.method, 4);
+
+        assert!(config.try_into_encoder().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "resize")]
+    fn resize() {
+        use crate::operations::resize::{FilterType, Resize, ResizeAlg};
+
+        let resize = Resize::new(100, 50, ResizeAlg::Convolution(FilterType::CatmullRom));
+
+        let json = serde_json::to_string(&resize).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"dimensions":[100,50],"algorithm":{"convolution":"catmull_rom"}}"#
+        );
+
+        let resize: Resize = serde_json::from_str(&json).unwrap();
+
+        assert!(resize.validate().is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "quantization")]
+    fn quantize() {
+        use crate::operations::quantize::Quantize;
+
+        let quantize: Quantize = serde_json::from_str(r#"{ "quality": 120 }"#).unwrap();
+
+        assert!(quantize.validate().is_err());
+    }
+}
@@ -0,0 +1,138 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use zune_core::{bit_depth::BitDepth, colorspace::ColorSpace};
+use zune_image::{codecs::ImageFormat, errors::ImageErrors};
+
+use crate::codecs::registry::Format;
+
+/// Image information read from the file headers
+///
+/// Collecting it doesn't decode any pixels, which makes it cheap enough to validate
+/// untrusted images before decoding them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageInfo {
+    /// Format of the image
+    pub format: Format,
+    /// Width and height of the image
+    pub dimensions: (usize, usize),
+    /// Colorspace of the decoded image
+    pub colorspace: ColorSpace,
+    /// Bit depth of the decoded image
+    pub depth: BitDepth,
+    /// Number of frames or pages in the image
+    pub frame_count: usize,
+    /// Whether the image has an alpha channel
+    pub has_alpha: bool,
+}
+
+/// Read image information from `source` without decoding it
+///
+/// # Example
+///
+/// ```
+/// use std::fs::File;
+///
+/// let file = File::open("tests/files/png/f1t.png").unwrap();
+///
+/// let info = rimage::probe(file).unwrap();
+///
+/// assert_eq!(info.dimensions, (48, 80));
+/// ```
+pub fn probe<R: Read + Seek>(mut source: R) -> Result<ImageInfo, ImageErrors> {
+    let mut magic = Vec::with_capacity(64);
+    source.by_ref().take(64).read_to_end(&mut magic)?;
+    source.seek(SeekFrom::Start(0))?;
+
+    let format = Format::detect(&magic).ok_or(ImageErrors::ImageDecoderNotImplemented(
+        ImageFormat::Unknown,
+    ))?;
+
+    match format {
+        Format::Png => probe_png(&mut source),
+        #[cfg(feature = "mozjpeg")]
+        Format::Jpeg => Ok(crate::codecs::mozjpeg::MozJpegDecoder::try_new(source)?.info()),
+        #[cfg(feature = "avif")]
+        Format::Avif => crate::codecs::avif::probe(source),
+        #[cfg(feature = "webp")]
+        Format::WebP => crate::codecs::webp::probe(source),
+        #[cfg(feature = "tiff")]
+        Format::Tiff => Ok(crate::codecs::tiff::TiffDecoder::try_new(source)?.info()),
+        #[cfg(feature = "gif")]
+        Format::Gif => Ok(crate::codecs::gif::GifDecoder::try_new(source)?.info()),
+        #[allow(unreachable_patterns)]
+        _ => Err(ImageErrors::ImageDecoderNotImplemented(
+            ImageFormat::Unknown,
+        )),
+    }
+}
+
+/// Read chunks before the image data, skipping over their contents with seeking
+fn probe_png<R: Read + Seek>(source: &mut R) -> Result<ImageInfo, ImageErrors> {
+    let error = || ImageErrors::ImageDecodeErrors("Malformed PNG header".to_string());
+
+    let mut header = None;
+    let mut has_trns = false;
+    let mut frame_count = 1;
+
+    source.seek(SeekFrom::Start(8))?;
+
+    let mut chunk = [0; 8];
+    while source.read_exact(&mut chunk).is_ok() {
+        let len = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) as u64;
+
+        // IHDR and acTL are the only chunks read, both are short
+        let mut body = Vec::with_capacity(16);
+        source.by_ref().take(len.min(16)).read_to_end(&mut body)?;
+
+        if body.len() as u64 != len.min(16) {
+            return Err(error());
+        }
+
+        match &chunk[4..8] {
+            b"IHDR" if body.len() >= 10 => {
+                let width = u32::from_be_bytes([body[0], body[1], body[2], body[3]]);
+                let height = u32::from_be_bytes([body[4], body[5], body[6], body[7]]);
+
+                header = Some((width as usize, height as usize, body[8], body[9]));
+            }
+            b"acTL" if body.len() >= 4 => {
+                frame_count = u32::from_be_bytes([body[0], body[1], body[2], body[3]]) as usize;
+            }
+            b"tRNS" => has_trns = true,
+            // everything needed comes before the image data
+            b"IDAT" | b"IEND" => break,
+            _ => {}
+        }
+
+        // rest of the chunk data and crc
+        source.seek(SeekFrom::Current((len - body.len() as u64 + 4) as i64))?;
+    }
+
+    let (width, height, bit_depth, color_type) = header.ok_or_else(error)?;
+
+    let (colorspace, has_alpha) = match color_type {
+        0 if has_trns => (ColorSpace::LumaA, true),
+        0 => (ColorSpace::Luma, false),
+        2 | 3 if has_trns => (ColorSpace::RGBA, true),
+        2 | 3 => (ColorSpace::RGB, false),
+        4 => (ColorSpace::LumaA, true),
+        6 => (ColorSpace::RGBA, true),
+        _ => return Err(error()),
+    };
+
+    Ok(ImageInfo {
+        format: Format::Png,
+        dimensions: (width, height),
+        colorspace,
+        depth: if bit_depth == 16 {
+            BitDepth::Sixteen
+        } else {
+            BitDepth::Eight
+        },
+        frame_count,
+        has_alpha,
+    })
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,87 @@
+use std::fs::File;
+
+use super::*;
+
+#[test]
+fn probe_formats() {
+    let files = [
+        ("tests/files/png/f1t.png", Format::Png),
+        #[cfg(feature = "mozjpeg")]
+        ("tests/files/jpg/f1t.jpg", Format::Jpeg),
+        #[cfg(feature = "avif")]
+        ("tests/files/avif/f1t.avif", Format::Avif),
+        #[cfg(feature = "webp")]
+        ("tests/files/webp/f1t.webp", Format::WebP),
+        #[cfg(feature = "tiff")]
+        ("tests/files/tiff/f1t.tif", Format::Tiff),
+        #[cfg(feature = "gif")]
+        ("tests/files/gif/f1t.gif", Format::Gif),
+    ];
+
+    for (path, format) in files {
+        let info = probe(File::open(path).unwrap()).unwrap();
+
+        assert_eq!(info.format, format, "{path}");
+        assert_eq!(info.dimensions, (48, 80), "{path}");
+        assert_eq!(info.frame_count, 1, "{path}");
+        assert!(!info.has_alpha, "{path}");
+    }
+}
+
+#[test]
+fn probe_png_alpha() {
+    let info = probe(File::open("tests/files/png/f1trgba.png").unwrap()).unwrap();
+
+    assert_eq!(info.colorspace, ColorSpace::RGBA);
+    assert!(info.has_alpha);
+}
+
+#[test]
+fn probe_unknown() {
+    let result = probe(std::io::Cursor::new(b"not an image".to_vec()));
+
+    assert!(result.is_err());
+}
+
+/// Reader counting the bytes read from the file
+struct CountingReader<R> {
+    inner: R,
+    read: usize,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read += n;
+        Ok(n)
+    }
+}
+
+impl<R: Seek> Seek for CountingReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+#[test]
+fn probe_reads_headers_only() {
+    let files = [
+        "tests/files/png/f1t.png",
+        #[cfg(feature = "avif")]
+        "tests/files/avif/f1t.avif",
+        #[cfg(feature = "webp")]
+        "tests/files/webp/f1t.webp",
+    ];
+
+    for path in files {
+        let len = std::fs::metadata(path).unwrap().len() as usize;
+        let mut reader = CountingReader {
+            inner: File::open(path).unwrap(),
+            read: 0,
+        };
+
+        probe(&mut reader).unwrap();
+
+        assert!(reader.read < len, "{path} read {} of {len}", reader.read);
+    }
+}
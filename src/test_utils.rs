@@ -53,3 +53,13 @@ pub(crate) fn create_test_image_animated(
 
     Image::new_frames(frames, BitDepth::Eight, width, height, colorspace)
 }
+
+/// ICC profile with sRGB tone curves, but a different red primary
+pub(crate) fn create_wide_gamut_profile() -> Vec<u8> {
+    let mut icc = std::fs::read("tests/files/icc/tinysrgb.icc").unwrap();
+
+    // X component of the rXYZ tag, 0.5 as signed 15.16 fixed point number
+    icc[0x198..0x19c].copy_from_slice(&0x8000u32.to_be_bytes());
+
+    icc
+}
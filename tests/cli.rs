@@ -0,0 +1,76 @@
+use std::{fs, path::PathBuf, process::Command};
+
+/// Empty directory for the files of `test`
+fn test_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rimage-{test}-{}", std::process::id()));
+
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    dir
+}
+
+/// Corrupt JPEG has to fail alone, run with `--release` to check the release profile
+#[test]
+fn corrupt_jpeg_keeps_batch() {
+    let dir = test_dir("corrupt-jpeg");
+    let out = dir.join("out");
+
+    // unsupported sample precision of the frame header is a fatal libjpeg error
+    let mut corrupt = fs::read("tests/files/jpg/f1t.jpg").unwrap();
+    let sof = corrupt
+        .windows(2)
+        .position(|w| w == [0xFF, 0xC0] || w == [0xFF, 0xC2])
+        .unwrap();
+    corrupt[sof + 4] = 7;
+
+    fs::write(dir.join("corrupt.jpg"), &corrupt).unwrap();
+    fs::copy("tests/files/jpg/f1t.jpg", dir.join("valid.jpg")).unwrap();
+
+    for lossless in [true, false] {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_rimage"));
+
+        command
+            .arg("mozjpeg")
+            .arg(dir.join("corrupt.jpg"))
+            .arg(dir.join("valid.jpg"))
+            .arg("-d")
+            .arg(&out)
+            .arg("--quiet")
+            .args(lossless.then_some("--lossless"));
+
+        let status = command.status().unwrap();
+
+        // aborted process has no exit code
+        assert!(status.code().is_some(), "lossless: {lossless}");
+        assert!(out.join("valid.jpg").exists(), "lossless: {lossless}");
+
+        fs::remove_dir_all(&out).unwrap();
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Lossless transcoding skips the pipeline, so operations are rejected
+#[test]
+#[cfg(feature = "resize")]
+fn lossless_conflicts_with_operations() {
+    let dir = test_dir("lossless-operations");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rimage"))
+        .arg("mozjpeg")
+        .arg("--lossless")
+        .arg("--resize")
+        .arg("50%")
+        .arg("tests/files/jpg/f1t.jpg")
+        .arg("-d")
+        .arg(&dir)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with"));
+    assert!(!dir.join("f1t.jpg").exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}